@@ -0,0 +1,20 @@
+-- Your SQL goes here
+ALTER TABLE users
+    ADD COLUMN is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN is_active BOOLEAN NOT NULL DEFAULT TRUE;
+
+-- The account seeded by `add_superuser` ships with a well-known password hash,
+-- so it is disabled unless the operator explicitly keeps it, e.g. by running
+-- the migrations with `PGOPTIONS='-c vimana.keep_legacy_superuser=on'`.
+-- The initial administrator is created through the first-run setup page instead.
+UPDATE users
+SET is_admin = TRUE,
+    is_active = COALESCE(current_setting('vimana.keep_legacy_superuser', TRUE), '') IN ('on', 'true', '1'),
+    updated_at = NOW()
+WHERE id = '7763abad-f33d-4308-b89d-8897e9037d16';
+
+DELETE FROM sessions
+WHERE user_id IN (
+    SELECT id FROM users
+    WHERE id = '7763abad-f33d-4308-b89d-8897e9037d16' AND NOT is_active
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS vehicles_user_id_active_idx;
+ALTER TABLE vehicles DROP COLUMN archived_at;
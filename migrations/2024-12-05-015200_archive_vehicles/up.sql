@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- Vehicles are archived instead of deleted so that the cascading foreign keys on
+-- odometer and refuel don't wipe their history; only admins purge rows for good.
+ALTER TABLE vehicles ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX vehicles_user_id_active_idx ON vehicles (user_id) WHERE archived_at IS NULL;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Who may access a vehicle and how. An invitation is a row with accepted_at still NULL.
+CREATE TABLE vehicle_members (
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    accepted_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (vehicle_id, user_id)
+);
+
+CREATE INDEX vehicle_members_user_id_idx ON vehicle_members (user_id);
+
+-- Every existing vehicle keeps its creator as owner
+INSERT INTO vehicle_members (vehicle_id, user_id, role, accepted_at, created_at)
+SELECT id, user_id, 'owner', NOW(), COALESCE(created_at, NOW())
+FROM vehicles;
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE sessions DROP COLUMN active_organization_id;
+ALTER TABLE vehicles DROP COLUMN organization_id;
+DROP TABLE organization_members;
+DROP TABLE organizations;
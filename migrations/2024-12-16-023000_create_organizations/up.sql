@@ -0,0 +1,56 @@
+-- Your SQL goes here
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE TABLE organization_members (
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('admin', 'member')),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX organization_members_user_id_idx ON organization_members (user_id);
+
+ALTER TABLE vehicles ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE;
+ALTER TABLE sessions ADD COLUMN active_organization_id UUID REFERENCES organizations(id) ON DELETE SET NULL;
+
+-- Every existing user gets a personal organization that they administer and that owns the
+-- vehicles they created
+CREATE TEMPORARY TABLE personal_organizations AS
+SELECT id AS user_id, gen_random_uuid() AS organization_id, full_name
+FROM users;
+
+INSERT INTO organizations (id, name)
+SELECT organization_id, full_name || '''s vehicles'
+FROM personal_organizations;
+
+INSERT INTO organization_members (organization_id, user_id, role)
+SELECT organization_id, user_id, 'admin'
+FROM personal_organizations;
+
+UPDATE vehicles
+SET organization_id = p.organization_id
+FROM personal_organizations p
+WHERE vehicles.user_id = p.user_id;
+
+-- Users a vehicle is already shared with join the organization owning it, so the share keeps working
+INSERT INTO organization_members (organization_id, user_id, role)
+SELECT DISTINCT v.organization_id, m.user_id, 'member'
+FROM vehicle_members m
+JOIN vehicles v ON v.id = m.vehicle_id
+ON CONFLICT DO NOTHING;
+
+UPDATE sessions
+SET active_organization_id = p.organization_id
+FROM personal_organizations p
+WHERE sessions.user_id = p.user_id;
+
+DROP TABLE personal_organizations;
+
+ALTER TABLE vehicles ALTER COLUMN organization_id SET NOT NULL;
+CREATE INDEX vehicles_organization_id_idx ON vehicles (organization_id);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE refuel
+    DROP COLUMN driver_id,
+    DROP COLUMN user_id;
+
+ALTER TABLE odometer
+    DROP COLUMN driver_id,
+    DROP COLUMN user_id;
+
+DROP TABLE driver_assignments;
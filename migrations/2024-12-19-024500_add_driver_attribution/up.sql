@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Who drove a vehicle and when. An assignment with ends_at still NULL is open-ended.
+CREATE TABLE driver_assignments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    assigned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ends_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+CREATE INDEX driver_assignments_vehicle_id_starts_at_idx ON driver_assignments (vehicle_id, starts_at);
+
+-- Who submitted each reading, and who was driving. Existing readings stay unattributed.
+ALTER TABLE odometer
+    ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN driver_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE refuel
+    ADD COLUMN user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN driver_id UUID REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- Mileage logbook. Each trip records its start and end readings as odometer rows, the same way
+-- refuel links to its odometer reading.
+CREATE TABLE trips (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    start_odometer_id UUID NOT NULL REFERENCES odometer(id) ON DELETE CASCADE,
+    end_odometer_id UUID NOT NULL REFERENCES odometer(id) ON DELETE CASCADE,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    ended_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    origin TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    purpose TEXT NOT NULL,
+    category TEXT NOT NULL CHECK (category IN ('business', 'private')),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    driver_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (ended_at > started_at)
+);
+
+CREATE INDEX trips_vehicle_id_started_at_idx ON trips (vehicle_id, started_at);
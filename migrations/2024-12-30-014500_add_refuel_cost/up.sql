@@ -0,0 +1,3 @@
+-- Your SQL goes here
+-- What a refuel cost, in the organization's currency. Unknown for existing refuels.
+ALTER TABLE refuel ADD COLUMN cost REAL CHECK (cost IS NULL OR cost >= 0);
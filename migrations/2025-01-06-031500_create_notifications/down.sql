@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE notification_deliveries;
+DROP TABLE notifications;
+ALTER TABLE users DROP COLUMN email;
@@ -0,0 +1,36 @@
+-- Your SQL goes here
+-- Address for email notifications; users without one only get in-app notifications.
+ALTER TABLE users ADD COLUMN email TEXT;
+
+-- One row per notification per recipient. `dedup_key` identifies what the notification is
+-- about (e.g. a reminder window of a registration expiry date), so each fires only once.
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    vehicle_id UUID REFERENCES vehicles(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    dedup_key TEXT NOT NULL,
+    read_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (user_id, dedup_key)
+);
+
+CREATE INDEX notifications_user_id_created_at_idx ON notifications (user_id, created_at);
+
+-- Delivery of a notification through one channel (inbox, email, webhook), retried until sent.
+-- Channels that cannot reach a recipient (e.g. email without an address) skip the delivery.
+CREATE TABLE notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    notification_id UUID NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+    channel TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'sent', 'skipped', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    delivered_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    UNIQUE (notification_id, channel)
+);
+
+CREATE INDEX notification_deliveries_status_idx ON notification_deliveries (status);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX notifications_inbox_idx;
+CREATE INDEX notifications_user_id_created_at_idx ON notifications (user_id, created_at);
+
+ALTER TABLE notifications DROP COLUMN dismissed_at;
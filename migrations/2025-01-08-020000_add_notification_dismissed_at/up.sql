@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- Dismissed notifications are hidden from the inbox but kept, so their event is not notified again.
+ALTER TABLE notifications ADD COLUMN dismissed_at TIMESTAMP WITH TIME ZONE;
+
+DROP INDEX notifications_user_id_created_at_idx;
+CREATE INDEX notifications_inbox_idx ON notifications (user_id, created_at)
+    WHERE dismissed_at IS NULL;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+-- Files attached to a vehicle, and optionally to one of its refuels. The files themselves are
+-- stored on disk under `DOCUMENTS_DIR`; `storage_key` and `thumbnail_key` are relative to it.
+CREATE TABLE documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    refuel_id UUID REFERENCES refuel(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('insurance', 'registration', 'receipt', 'other')),
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    storage_key TEXT NOT NULL,
+    thumbnail_key TEXT,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX documents_vehicle_id_created_at_idx ON documents (vehicle_id, created_at);
+CREATE INDEX documents_refuel_id_idx ON documents (refuel_id);
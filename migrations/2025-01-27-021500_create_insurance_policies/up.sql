@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- Insurance policies of a vehicle. A vehicle is insured until the end of its latest-ending policy;
+-- `document_id` links the policy document, uploaded as a vehicle document.
+CREATE TABLE insurance_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    policy_number TEXT NOT NULL,
+    coverage_type TEXT NOT NULL
+        CHECK (coverage_type IN ('comprehensive', 'third_party', 'total_loss_only', 'other')),
+    premium REAL NOT NULL CHECK (premium >= 0),
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    document_id UUID REFERENCES documents(id) ON DELETE SET NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (end_date >= start_date)
+);
+
+CREATE INDEX insurance_policies_vehicle_id_end_date_idx ON insurance_policies (vehicle_id, end_date);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE vehicles
+    DROP COLUMN vin,
+    DROP COLUMN model_year,
+    DROP COLUMN colour,
+    DROP COLUMN fuel_type,
+    DROP COLUMN tank_capacity,
+    DROP COLUMN odometer_unit,
+    DROP COLUMN purchase_date,
+    DROP COLUMN purchase_price;
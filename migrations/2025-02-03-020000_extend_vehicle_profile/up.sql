@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Extended vehicle profile. `tank_capacity` is in liters, or in kWh for electric vehicles.
+ALTER TABLE vehicles
+    ADD COLUMN vin TEXT,
+    ADD COLUMN model_year INTEGER,
+    ADD COLUMN colour TEXT,
+    ADD COLUMN fuel_type TEXT CHECK (fuel_type IN ('petrol', 'diesel', 'ev', 'hybrid', 'lpg')),
+    ADD COLUMN tank_capacity REAL CHECK (tank_capacity > 0),
+    ADD COLUMN odometer_unit TEXT NOT NULL DEFAULT 'km' CHECK (odometer_unit IN ('km', 'mi')),
+    ADD COLUMN purchase_date DATE,
+    ADD COLUMN purchase_price REAL CHECK (purchase_price >= 0);
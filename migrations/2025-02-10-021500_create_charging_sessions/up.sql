@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Charging sessions of electric and plug-in hybrid vehicles. Like refuel, each session links to
+-- the odometer reading taken when it started.
+CREATE TABLE charging_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    vehicle_id UUID NOT NULL REFERENCES vehicles(id) ON DELETE CASCADE,
+    odometer_id UUID NOT NULL REFERENCES odometer(id) ON DELETE CASCADE,
+    started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    energy_kwh REAL NOT NULL CHECK (energy_kwh > 0),
+    charger_type TEXT NOT NULL CHECK (charger_type IN ('ac', 'dc')),
+    start_soc REAL CHECK (start_soc BETWEEN 0 AND 100),
+    end_soc REAL CHECK (end_soc BETWEEN 0 AND 100),
+    duration_minutes INTEGER CHECK (duration_minutes > 0),
+    cost REAL CHECK (cost >= 0),
+    location TEXT,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    driver_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+    CHECK (end_soc >= start_soc)
+);
+
+CREATE INDEX charging_sessions_vehicle_id_started_at_idx ON charging_sessions (vehicle_id, started_at);
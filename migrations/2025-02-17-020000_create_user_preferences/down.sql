@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+UPDATE odometer
+SET odometer_value = odometer_value / 1.609344
+FROM vehicles
+WHERE vehicles.id = odometer.vehicle_id AND vehicles.odometer_unit = 'mi';
+
+DROP TABLE user_preferences;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Units and formats each user enters and reads values in. Values are stored in canonical units:
+-- distances in kilometers and fuel in liters.
+CREATE TABLE user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    distance_unit TEXT NOT NULL DEFAULT 'km' CHECK (distance_unit IN ('km', 'mi')),
+    volume_unit TEXT NOT NULL DEFAULT 'l' CHECK (volume_unit IN ('l', 'us_gal', 'imp_gal')),
+    currency TEXT NOT NULL DEFAULT 'USD' CHECK (currency ~ '^[A-Z]{3}$'),
+    date_format TEXT NOT NULL DEFAULT 'iso' CHECK (date_format IN ('iso', 'dmy', 'mdy')),
+    timezone TEXT NOT NULL DEFAULT 'UTC',
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+-- Readings of vehicles with a mile odometer were stored as entered; store them in kilometers
+UPDATE odometer
+SET odometer_value = odometer_value * 1.609344
+FROM vehicles
+WHERE vehicles.id = odometer.vehicle_id AND vehicles.odometer_unit = 'mi';
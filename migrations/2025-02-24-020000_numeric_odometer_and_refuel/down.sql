@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE refuel
+    ALTER COLUMN refuel_quantity TYPE REAL;
+
+ALTER TABLE odometer
+    ALTER COLUMN odometer_value TYPE REAL;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Store odometer readings (kilometers) to the meter and refuel quantities (liters) to the
+-- milliliter. REAL only holds about seven significant digits.
+ALTER TABLE odometer
+    ALTER COLUMN odometer_value TYPE NUMERIC(12, 3) USING round(odometer_value::numeric, 3);
+
+ALTER TABLE refuel
+    ALTER COLUMN refuel_quantity TYPE NUMERIC(10, 3) USING round(refuel_quantity::numeric, 3);
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM organization_members WHERE accepted_at IS NULL;
+
+ALTER TABLE organization_members
+    DROP COLUMN invited_by,
+    DROP COLUMN accepted_at;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Users join an organization by accepting an invitation. An invitation is a row with accepted_at
+-- still NULL.
+ALTER TABLE organization_members
+    ADD COLUMN invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN accepted_at TIMESTAMP WITH TIME ZONE;
+
+UPDATE organization_members SET accepted_at = created_at;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Store refuel costs to the cent. REAL cannot hold most decimal amounts exactly.
+ALTER TABLE refuel
+    ALTER COLUMN cost TYPE NUMERIC(12, 2) USING round(cost::numeric, 2);
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Store insurance premiums to the cent. REAL cannot hold most decimal amounts exactly.
+ALTER TABLE insurance_policies
+    ALTER COLUMN premium TYPE NUMERIC(12, 2) USING round(premium::numeric, 2);
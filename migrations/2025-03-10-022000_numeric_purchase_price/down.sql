@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE vehicles
+    ALTER COLUMN purchase_price TYPE REAL;
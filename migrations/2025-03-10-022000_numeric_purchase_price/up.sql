@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Store purchase prices to the cent. REAL cannot hold most decimal amounts exactly.
+ALTER TABLE vehicles
+    ALTER COLUMN purchase_price TYPE NUMERIC(12, 2) USING round(purchase_price::numeric, 2);
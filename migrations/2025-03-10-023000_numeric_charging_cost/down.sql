@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE charging_sessions
+    ALTER COLUMN cost TYPE REAL;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Store charging costs to the cent. REAL cannot hold most decimal amounts exactly.
+ALTER TABLE charging_sessions
+    ALTER COLUMN cost TYPE NUMERIC(12, 2) USING round(cost::numeric, 2);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER revoke_sessions_of_disabled_user ON users;
+
+DROP FUNCTION revoke_sessions_of_disabled_user();
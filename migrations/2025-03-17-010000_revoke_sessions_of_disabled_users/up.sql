@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Accounts are disabled by clearing users.is_active, so far only outside the application. Their
+-- sessions are revoked in the same statement, so a disabled account is logged out everywhere.
+CREATE FUNCTION revoke_sessions_of_disabled_user() RETURNS TRIGGER AS $$
+BEGIN
+    DELETE FROM sessions WHERE user_id = NEW.id;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER revoke_sessions_of_disabled_user
+    AFTER UPDATE OF is_active ON users
+    FOR EACH ROW
+    WHEN (OLD.is_active AND NOT NEW.is_active)
+    EXECUTE FUNCTION revoke_sessions_of_disabled_user();
+
+DELETE FROM sessions
+WHERE user_id IN (SELECT id FROM users WHERE NOT is_active);
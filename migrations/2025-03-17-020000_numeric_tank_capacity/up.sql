@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Store tank and battery capacities to the milliliter or watt-hour, like refuel quantities.
+ALTER TABLE vehicles
+    ALTER COLUMN tank_capacity TYPE NUMERIC(10, 3) USING round(tank_capacity::numeric, 3);
@@ -0,0 +1,167 @@
+//! Storage of vehicle documents on the local disk.
+//!
+//! Uploaded files are stored under `DOCUMENTS_DIR` (default `documents`, relative to the working
+//! directory), one directory per vehicle, with the document's ID as file name. Their metadata is
+//! kept in the `documents` table. Uploads may be at most `DOCUMENT_MAX_BYTES` bytes (default
+//! 10 MiB) and must be PDFs or JPEG, PNG, GIF or WebP images; the type is detected from the file's
+//! contents, not from its name. A JPEG thumbnail of at most `THUMBNAIL_SIZE` pixels is stored next
+//! to each image.
+//!
+//! The directory is not served by the `/static` file service: files are only sent through the
+//! authenticated document endpoints.
+use image::{codecs::jpeg::JpegEncoder, ImageFormat, ImageReader, Limits};
+use lazy_static::lazy_static;
+use std::env;
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Largest width and height of a thumbnail, in pixels.
+const THUMBNAIL_SIZE: u32 = 320;
+
+/// JPEG quality of thumbnails.
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Largest width and height of an image that is decoded to create a thumbnail.
+const MAX_IMAGE_DIMENSION: u32 = 12_000;
+
+/// Most memory the decoder may allocate while creating a thumbnail.
+const MAX_IMAGE_ALLOC: u64 = 512 * 1024 * 1024;
+
+lazy_static! {
+    /// The directory documents are stored in.
+    pub static ref DOCUMENTS_DIR: PathBuf =
+        PathBuf::from(env::var("DOCUMENTS_DIR").unwrap_or_else(|_| "documents".to_string()));
+
+    /// The largest accepted upload, in bytes.
+    pub static ref DOCUMENT_MAX_BYTES: usize = match env::var("DOCUMENT_MAX_BYTES") {
+        Ok(value) => value.parse::<usize>().ok().filter(|&bytes| bytes > 0).unwrap_or_else(|| {
+            log::warn!("Ignoring invalid DOCUMENT_MAX_BYTES={:?}, using the default", value);
+            10 * 1024 * 1024
+        }),
+        Err(_) => 10 * 1024 * 1024,
+    };
+}
+
+/// Detects the media type of an uploaded file from its first bytes.
+///
+/// # Arguments
+/// - `bytes`: The contents of the file.
+///
+/// # Returns
+/// - `Some(&str)`: The media type, if the file is an accepted type.
+/// - `None`: If the file is of any other type.
+pub fn detect_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    match image::guess_format(bytes).ok()? {
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Returns where a stored file is on disk.
+///
+/// # Arguments
+/// - `key`: The storage key of the file, as stored in the `documents` table.
+pub fn path(key: &str) -> PathBuf {
+    DOCUMENTS_DIR.join(key)
+}
+
+/// Stores an uploaded file and, for images, its thumbnail.
+///
+/// # Arguments
+/// - `vehicle_id`: The vehicle the document belongs to.
+/// - `document_id`: The ID of the document.
+/// - `bytes`: The contents of the file.
+/// - `content_type`: The media type detected by `detect_content_type`.
+///
+/// # Returns
+/// - `Ok((String, Option<String>))`: The storage keys of the file and of its thumbnail. Images that
+///   cannot be decoded are stored without a thumbnail.
+/// - `Err(io::Error)`: If the file could not be written.
+pub fn store(
+    vehicle_id: Uuid,
+    document_id: Uuid,
+    bytes: &[u8],
+    content_type: &str,
+) -> io::Result<(String, Option<String>)> {
+    fs::create_dir_all(DOCUMENTS_DIR.join(vehicle_id.to_string()))?;
+
+    let storage_key = format!("{}/{}", vehicle_id, document_id);
+    fs::write(path(&storage_key), bytes)?;
+
+    if !content_type.starts_with("image/") {
+        return Ok((storage_key, None));
+    }
+    let thumbnail = match thumbnail(bytes) {
+        Ok(thumbnail) => thumbnail,
+        Err(e) => {
+            log::warn!(
+                "Failed to create a thumbnail of document {}: {}",
+                document_id,
+                e
+            );
+            return Ok((storage_key, None));
+        }
+    };
+    let thumbnail_key = format!("{}/{}.thumb.jpg", vehicle_id, document_id);
+    if let Err(e) = fs::write(path(&thumbnail_key), thumbnail) {
+        remove(&storage_key, None);
+        return Err(e);
+    }
+    Ok((storage_key, Some(thumbnail_key)))
+}
+
+/// Creates a JPEG thumbnail of an image.
+fn thumbnail(bytes: &[u8]) -> Result<Vec<u8>, image::ImageError> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_IMAGE_ALLOC);
+
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    reader.limits(limits);
+    let image = reader.decode()?.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+
+    let mut thumbnail = Vec::new();
+    JpegEncoder::new_with_quality(&mut thumbnail, THUMBNAIL_QUALITY)
+        .encode_image(&image.to_rgb8())?;
+    Ok(thumbnail)
+}
+
+/// Removes a stored file and its thumbnail, logging rather than returning failures.
+///
+/// # Arguments
+/// - `storage_key`: The storage key of the file.
+/// - `thumbnail_key`: The storage key of its thumbnail, if any.
+pub fn remove(storage_key: &str, thumbnail_key: Option<&str>) {
+    for key in std::iter::once(storage_key).chain(thumbnail_key) {
+        if let Err(e) = fs::remove_file(path(key)) {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("Failed to remove document file {}: {}", key, e);
+            }
+        }
+    }
+}
+
+/// Removes all stored files of a vehicle, logging rather than returning failures.
+///
+/// # Arguments
+/// - `vehicle_id`: The vehicle whose documents are removed.
+pub fn remove_vehicle(vehicle_id: Uuid) {
+    if let Err(e) = fs::remove_dir_all(DOCUMENTS_DIR.join(vehicle_id.to_string())) {
+        if e.kind() != io::ErrorKind::NotFound {
+            log::warn!(
+                "Failed to remove the documents of vehicle {}: {}",
+                vehicle_id,
+                e
+            );
+        }
+    }
+}
@@ -1,24 +1,81 @@
+use crate::documents;
 use crate::middleware::AuthenticatedRequest;
-use crate::models::NewVehicle;
+use crate::models::{Backup, DocumentFile, NewVehicle, OdometerUnit};
 use crate::requests::{
-    DateIntervalRequest, LoginRequest, NewOdometerRequest, NewRefuelRequest, NewUserRequest,
-    NewVehicleRequest, UpdateVehicleRequest,
+    AssignDriverRequest, AuditLogQuery, DateIntervalRequest, DocumentQuery, DocumentUpload,
+    InviteOrganizationMemberRequest, InviteVehicleMemberRequest, LoginRequest,
+    NewChargingSessionRequest, NewInsurancePolicyRequest, NewOdometerRequest,
+    NewOrganizationRequest, NewRefuelRequest, NewTripRequest, NewUserRequest, NewVehicleRequest,
+    NewWebhookRequest, NotificationQuery, RefuelImportRequest, ReportQuery, SetupRequest,
+    UpdateEmailRequest, UpdateInsurancePolicyRequest, UpdatePreferencesRequest, UpdateTripRequest,
+    UpdateVehicleRequest, UpdateWebhookRequest, VehicleListQuery, WebhookDeliveryQuery,
 };
-use crate::{services, DbPool};
-use actix_web::{web, Error, HttpRequest, HttpResponse};
-use chrono::NaiveDateTime;
+use crate::{services, DbPool, StartedAt};
+use actix_files::NamedFile;
+use actix_multipart::Multipart;
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue, HeaderValue,
+    AUTHORIZATION, X_CONTENT_TYPE_OPTIONS,
+};
+use actix_web::{web, Error, HttpRequest, HttpResponse, HttpResponseBuilder};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
 use uuid::Uuid;
 
-/// Health check handler that verifies server and database connectivity.
+/// Liveness probe handler that reports the server process is up.
+///
+/// This handler does not touch the database, so orchestrators do not restart the container
+/// during a database outage.
+///
+/// Returns:
+/// - `200 OK` with a JSON liveness report.
+pub async fn liveness(started_at: web::Data<StartedAt>) -> HttpResponse {
+    HttpResponse::Ok().json(services::liveness(started_at.0))
+}
+
+/// Readiness probe handler that verifies the database, the connection pool and migrations.
+///
+/// Returns:
+/// - `200 OK` with a JSON readiness report if the database is reachable and up to date.
+/// - `503 ServiceUnavailable` with the same report if any check fails.
+pub async fn readiness(pool: web::Data<DbPool>, started_at: web::Data<StartedAt>) -> HttpResponse {
+    let report = services::readiness(&pool, started_at.0).await;
+    if report.status == "ready" {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::ServiceUnavailable().json(report)
+    }
+}
+
+/// Prometheus metrics handler exposing request, pool, login and domain metrics.
+///
+/// Metrics are only served when the `METRICS_TOKEN` environment variable is set, and the scraper
+/// must send the same token as `Authorization: Bearer <token>`.
 ///
 /// Returns:
-/// - `200 OK` if the server and database are reachable.
-/// - `503 ServiceUnavailable` if there is a database connection issue.
-pub async fn health_check(pool: web::Data<DbPool>) -> HttpResponse {
-    let conn = pool.get();
-    match conn {
-        Ok(_) => HttpResponse::Ok().body("Server and database connection healthy"),
-        Err(_) => HttpResponse::ServiceUnavailable().body("Database connection issue"),
+/// - `200 OK` with metrics in the Prometheus text exposition format.
+/// - `401 Unauthorized` if the bearer token is missing or does not match.
+/// - `404 Not Found` if no metrics token is configured.
+/// - `500 InternalServerError` if the metrics cannot be collected.
+pub async fn metrics(pool: web::Data<DbPool>, req: HttpRequest) -> Result<HttpResponse, Error> {
+    let expected_token = std::env::var("METRICS_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| actix_web::error::ErrorNotFound("Not found"))?;
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if token != Some(expected_token.as_str()) {
+        return Err(actix_web::error::ErrorUnauthorized("Invalid metrics token"));
+    }
+
+    match services::render_metrics(&pool).await {
+        Ok(body) => Ok(HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(body)),
+        Err(e) => Err(e.into()),
     }
 }
 
@@ -102,16 +159,43 @@ pub async fn logout(
     }
 }
 
-/// Handles new user creation, restricted to the superuser.
+/// Handles first-run setup by creating the initial administrator.
+///
+/// This handler is only usable while no active administrator exists. When the `SETUP_TOKEN`
+/// environment variable is set, the request must carry the same token; without it, only an
+/// installation without any users can be set up.
+///
+/// Returns:
+/// - `201 Created` with the new administrator's ID.
+/// - `401 Unauthorized` if the setup token does not match.
+/// - `403 Forbidden` if setup has already been completed, or users exist and no setup token is
+///   configured.
+/// - `422 Unprocessable Entity` if the credentials are invalid.
+pub async fn setup(
+    pool: web::Data<DbPool>,
+    req: web::Json<SetupRequest>,
+) -> Result<HttpResponse, Error> {
+    let expected_token = std::env::var("SETUP_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+
+    match services::setup_initial_admin(&pool, expected_token.as_deref(), &req).await {
+        Ok(admin) => Ok(HttpResponse::Created().body(admin.id.to_string())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handles new user creation, restricted to administrators of the active organization.
 ///
 /// This handler performs the following steps:
-/// 1. Checks if the requester is the superuser.
+/// 1. Checks if the requester administers the active organization, or is a global administrator
+///    when the new user is to be a global administrator too.
 /// 2. Hashes the new user's password and creates a new user record in the database.
 /// 3. Responds with the created user details or appropriate errors.
 ///
 /// Returns:
 /// - `200 OK` with the new user details if creation is successful.
-/// - `403 Forbidden` if the requester is not the superuser.
+/// - `403 Forbidden` if the requester is not an administrator or has no active organization.
 /// - `500 InternalServerError` if there is an error creating the user.
 pub async fn create_user(
     pool: web::Data<DbPool>,
@@ -121,68 +205,52 @@ pub async fn create_user(
     let user_id = req
         .authenticated_user_id()
         .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
 
-    match services::create_user(
-        &pool,
-        user_id,
-        new_user.username.clone(),
-        new_user.password.clone(),
-        new_user.full_name.clone(),
-    )
-    .await
-    {
+    match services::create_user(&pool, user_id, organization_id, new_user.into_inner()).await {
         Ok(new_user) => Ok(HttpResponse::Created().body(new_user.id.to_string())),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to create a new vehicle.
+/// Handler to set or clear the authenticated user's email address for notifications.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `new_vehicle`: The new vehicle data.
+/// - `request`: JSON payload with the new address.
 /// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `201 Created` with the new vehicle data if successful.
+/// - `200 OK` with the updated user.
+/// - `422 Unprocessable Entity` if the address is not valid.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn create_vehicle(
+pub async fn update_email(
     pool: web::Data<DbPool>,
-    new_vehicle: web::Json<NewVehicleRequest>,
+    request: web::Json<UpdateEmailRequest>,
     req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
     let user_id = req
         .authenticated_user_id()
         .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
 
-    let new_vehicle = new_vehicle.into_inner();
-    let new_vehicle = NewVehicle {
-        id: uuid::Uuid::new_v4(),
-        brand: new_vehicle.brand,
-        model: new_vehicle.model,
-        registration: new_vehicle.registration,
-        registration_expiry_date: new_vehicle.registration_expiry_date,
-        user_id,
-    };
-
-    let vehicle_data = services::create_vehicle(&pool, &new_vehicle).await;
-
-    match vehicle_data {
-        Ok(vehicle) => Ok(HttpResponse::Created().json(vehicle)),
+    match services::update_email(&pool, user_id, request.into_inner()).await {
+        Ok(user) => Ok(HttpResponse::Ok().json(user)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get all vehicles owned by the authenticated user.
+/// Handler to get the authenticated user's unit and format preferences.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with a list of vehicles.
+/// - `200 OK` with the preferences, the defaults if none were chosen.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_vehicles_by_user(
+pub async fn get_preferences(
     pool: web::Data<DbPool>,
     req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
@@ -190,260 +258,2765 @@ pub async fn get_vehicles_by_user(
         .authenticated_user_id()
         .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
 
-    match services::get_vehicles_by_user_id(&pool, user_id).await {
-        Ok(vehicles) => Ok(HttpResponse::Ok().json(vehicles)),
+    match services::get_preferences(&pool, user_id).await {
+        Ok(preferences) => Ok(HttpResponse::Ok().json(preferences)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get a vehicle by its ID.
+/// Handler to change the authenticated user's unit and format preferences.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The vehicle ID.
+/// - `request`: JSON payload with the preferences to change.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the vehicle data if found.
+/// - `200 OK` with the updated preferences.
+/// - `422 Unprocessable Entity` if a unit, currency, date format or timezone is unknown.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_vehicle_by_id(
+pub async fn update_preferences(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
+    request: web::Json<UpdatePreferencesRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    match services::get_vehicle_by_id(&pool, vehicle_id.into_inner()).await {
-        Ok(vehicle) => Ok(HttpResponse::Ok().json(vehicle)),
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::update_preferences(&pool, user_id, request.into_inner()).await {
+        Ok(preferences) => Ok(HttpResponse::Ok().json(preferences)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to update a vehicle by its ID.
+/// Handler to list the authenticated user's organizations, flagging the active one.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The vehicle ID.
-/// - `update_data`: The updated vehicle data.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the updated vehicle data if successful.
+/// - `200 OK` with the list of organizations.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn update_vehicle_by_id(
+pub async fn get_organizations(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    update_data: web::Json<UpdateVehicleRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let update_data = update_data.into_inner();
-    match services::update_vehicle_by_id(
-        &pool,
-        vehicle_id.into_inner(),
-        update_data.brand,
-        update_data.model,
-        update_data.registration,
-        update_data.registration_expiry_date,
-    )
-    .await
-    {
-        Ok(vehicle) => Ok(HttpResponse::Ok().json(vehicle)),
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::get_organizations(&pool, user_id, req.active_organization_id()).await {
+        Ok(organizations) => Ok(HttpResponse::Ok().json(organizations)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to delete a vehicle by its ID.
+/// Handler to create a new organization administered by the authenticated user.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The vehicle ID.
+/// - `organization_data`: The new organization data.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `204 No Content` if the vehicle was deleted successfully.
+/// - `201 Created` with the new organization.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn delete_vehicle_by_id(
+pub async fn create_organization(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
+    organization_data: web::Json<NewOrganizationRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    match services::delete_vehicle_by_id(&pool, vehicle_id.into_inner()).await {
-        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::create_organization(&pool, user_id, organization_data.into_inner().name).await {
+        Ok(organization) => Ok(HttpResponse::Created().json(organization)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to create a new odometer entry.
-///
-/// This handler wraps the `create_new_odometer` service function.
+/// Handler to switch the current session to another organization.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
-/// - `odometer_data`: The odometer data from the request.
+/// - `organization_id`: The ID of the organization to switch to.
+/// - `req`: HTTP request object (for extracting user ID and session token).
 ///
 /// # Returns
-/// - `201 Created` with the created odometer data.
+/// - `204 No Content` if the session switched organizations.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn create_odometer(
+pub async fn switch_organization(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    odometer_data: web::Json<NewOdometerRequest>,
+    organization_id: web::Path<Uuid>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let odometer_data = odometer_data.into_inner();
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
 
-    match services::create_new_odometer(
-        &pool,
-        vehicle_id.into_inner(),
-        odometer_data.odometer_value,
-        odometer_data.timestamp,
-    )
-    .await
+    let token = req
+        .cookie("session_token")
+        .map(|cookie| cookie.value().to_string())
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("Unauthorized"))?;
+
+    match services::switch_organization(&pool, user_id, &token, organization_id.into_inner()).await
     {
-        Ok(odometer) => Ok(HttpResponse::Created().json(odometer)),
+        Ok(()) => Ok(HttpResponse::NoContent().finish()),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get the latest odometer entry for a vehicle.
-///
-/// This handler wraps the `get_latest_odometer` service function.
+/// Handler to list the members of an organization.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
+/// - `organization_id`: The organization ID.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the latest odometer data.
+/// - `200 OK` with the list of members.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_latest_odometer(
+pub async fn get_organization_members(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
+    organization_id: web::Path<Uuid>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    match services::get_latest_odometer(&pool, vehicle_id.into_inner()).await {
-        Ok(odometer) => Ok(HttpResponse::Ok().json(odometer)),
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::get_organization_members(&pool, user_id, organization_id.into_inner()).await {
+        Ok(members) => Ok(HttpResponse::Ok().json(members)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to create a new refuel event.
+/// Handler to invite an existing user to an organization (organization administrators only).
 ///
-/// This handler wraps the `create_new_refuel` service function.
+/// The response is the same whether or not the username exists, or is already a member.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
-/// - `refuel_data`: The refuel data from the request.
+/// - `organization_id`: The organization ID.
+/// - `invite_data`: The username to invite and the role offered.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `201 Created` with the created refuel data.
+/// - `202 Accepted` once the invitation is handled.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn create_refuel(
+pub async fn invite_organization_member(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    refuel_data: web::Json<NewRefuelRequest>,
+    organization_id: web::Path<Uuid>,
+    invite_data: web::Json<InviteOrganizationMemberRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let refuel_data = refuel_data.into_inner();
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
 
-    match services::create_new_refuel(
+    let invite_data = invite_data.into_inner();
+    match services::invite_organization_member(
         &pool,
-        vehicle_id.into_inner(),
-        refuel_data.refuel_quantity,
-        refuel_data.odometer_value,
-        refuel_data.timestamp,
+        user_id,
+        organization_id.into_inner(),
+        invite_data.username,
+        invite_data.role,
     )
     .await
     {
-        Ok(refuel) => Ok(HttpResponse::Created().json(refuel)),
+        Ok(()) => Ok(HttpResponse::Accepted().finish()),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get the latest refuel event for a vehicle.
-///
-/// This handler wraps the `get_latest_refuel` service function.
+/// Handler to accept the authenticated user's invitation to an organization.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
+/// - `organization_id`: The organization ID.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the latest refuel data.
+/// - `200 OK` with the accepted membership.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_latest_refuel(
+pub async fn accept_organization_invitation(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
+    organization_id: web::Path<Uuid>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    match services::get_latest_refuel(&pool, vehicle_id.into_inner()).await {
-        Ok(refuel) => Ok(HttpResponse::Ok().json(refuel)),
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::accept_organization_invitation(&pool, user_id, organization_id.into_inner())
+        .await
+    {
+        Ok(member) => Ok(HttpResponse::Ok().json(member)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get time-series odometer data for a vehicle.
-///
-/// This handler wraps the `get_odometer_timeseries` service function.
+/// Handler to list the authenticated user's pending organization invitations.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
-/// - `start_date`: Start of the date range.
-/// - `end_date`: End of the date range.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the time-series odometer data.
+/// - `200 OK` with the list of invitations.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_odometer_timeseries(
+pub async fn get_pending_organization_invitations(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    query: web::Query<(NaiveDateTime, NaiveDateTime)>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let (start_date, end_date) = query.into_inner();
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
 
-    match services::get_odometer_timeseries(&pool, vehicle_id.into_inner(), start_date, end_date)
-        .await
-    {
-        Ok(odometer_data) => Ok(HttpResponse::Ok().json(odometer_data)),
+    match services::get_pending_organization_invitations(&pool, user_id).await {
+        Ok(invitations) => Ok(HttpResponse::Ok().json(invitations)),
         Err(e) => Err(e.into()),
     }
 }
 
-/// Handler to get time-series refuel data for a vehicle.
+/// Handler to remove a member from an organization, withdraw an invitation, or to leave or
+/// decline one.
 ///
-/// This handler wraps the `get_refuel_timeseries` service function.
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The organization ID and the ID of the member to remove.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the member was removed.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn remove_organization_member(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    let (organization_id, member_id) = path.into_inner();
+    match services::remove_organization_member(&pool, user_id, organization_id, member_id).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to create a new vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
-/// - `start_date`: Start of the date range.
-/// - `end_date`: End of the date range.
+/// - `new_vehicle`: The new vehicle data.
+/// - `req`: HTTP request object (for extracting user ID).
 ///
 /// # Returns
-/// - `200 OK` with the time-series refuel data.
+/// - `201 Created` with the new vehicle data if successful.
 /// - Appropriate HTTP error code if the operation fails.
-pub async fn get_refuel_timeseries(
+pub async fn create_vehicle(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    query: web::Query<(NaiveDateTime, NaiveDateTime)>,
+    new_vehicle: web::Json<NewVehicleRequest>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let (start_date, end_date) = query.into_inner();
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let units = req.user_preferences().units();
 
-    match services::get_refuel_timeseries(&pool, vehicle_id.into_inner(), start_date, end_date)
-        .await
-    {
-        Ok(refuel_data) => Ok(HttpResponse::Ok().json(refuel_data)),
+    let new_vehicle = new_vehicle.into_inner();
+    let new_vehicle = NewVehicle {
+        id: uuid::Uuid::new_v4(),
+        brand: new_vehicle.brand,
+        model: new_vehicle.model,
+        registration: new_vehicle.registration,
+        registration_expiry_date: new_vehicle.registration_expiry_date,
+        user_id,
+        organization_id,
+        vin: new_vehicle.vin,
+        model_year: new_vehicle.model_year,
+        colour: new_vehicle.colour,
+        fuel_type: new_vehicle.fuel_type,
+        tank_capacity: new_vehicle.tank_capacity,
+        odometer_unit: new_vehicle
+            .odometer_unit
+            .unwrap_or_else(|| OdometerUnit::Kilometers.as_str().to_string()),
+        purchase_date: new_vehicle.purchase_date,
+        purchase_price: new_vehicle.purchase_price,
+    };
+
+    let vehicle_data = services::create_vehicle(&pool, new_vehicle, units).await;
+
+    match vehicle_data {
+        Ok(vehicle) => Ok(HttpResponse::Created().json(vehicle)),
         Err(e) => Err(e.into()),
     }
 }
 
-pub async fn get_traveled_distance(
+/// Handler to get all vehicles owned by the authenticated user.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: Listing options; archived vehicles are only listed with `include_archived=true`.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with a list of vehicles.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_vehicles_by_user(
     pool: web::Data<DbPool>,
-    vehicle_id: web::Path<Uuid>,
-    query: web::Query<DateIntervalRequest>,
+    query: web::Query<VehicleListQuery>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, Error> {
-    let request = query.into_inner();
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let units = req.user_preferences().units();
 
-    match services::get_traveled_distance(
-        &pool,
-        vehicle_id.into_inner(),
-        request.start_date,
-        request.end_date,
-    )
-    .await
+    match services::get_vehicles_by_user_id(&pool, user_id, organization_id, query.include_archived)
+        .await
+    {
+        Ok(vehicles) => Ok(HttpResponse::Ok().json(
+            vehicles
+                .into_iter()
+                .map(|vehicle| units.vehicle_with_role(vehicle))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get a vehicle by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the vehicle data if found.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_vehicle_by_id(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let units = req.user_preferences().units();
+
+    match services::get_vehicle_for_user(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(vehicle) => Ok(HttpResponse::Ok().json(units.vehicle_with_role(vehicle))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to update a vehicle by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `update_data`: The updated vehicle data.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the updated vehicle data if successful.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn update_vehicle_by_id(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    update_data: web::Json<UpdateVehicleRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let units = req.user_preferences().units();
+
+    match services::update_vehicle_by_id(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        update_data.into_inner(),
+        units,
+    )
+    .await
+    {
+        Ok(vehicle) => Ok(HttpResponse::Ok().json(vehicle)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to archive a vehicle by its ID.
+///
+/// The vehicle is hidden from listings but its history is kept; see `restore_vehicle_by_id`.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the vehicle was archived successfully.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn archive_vehicle_by_id(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::archive_vehicle_by_id(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to restore an archived vehicle by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the restored vehicle data if successful.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn restore_vehicle_by_id(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::restore_vehicle_by_id(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(vehicle) => Ok(HttpResponse::Ok().json(vehicle)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to permanently delete a vehicle and its history (administrators only).
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the vehicle was deleted successfully.
+/// - `403 Forbidden` if the user is not an administrator.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn purge_vehicle_by_id(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::purge_vehicle_by_id(&pool, user_id, vehicle_id.into_inner()).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the members of a vehicle, including pending invitations.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the list of members.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_vehicle_members(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_vehicle_members(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(members) => Ok(HttpResponse::Ok().json(members)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to invite a user to a vehicle (vehicle owners only).
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `invite_data`: The username to invite and the role offered.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the pending membership.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn invite_vehicle_member(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    invite_data: web::Json<InviteVehicleMemberRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let invite_data = invite_data.into_inner();
+    match services::invite_vehicle_member(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        invite_data.username,
+        invite_data.role,
+    )
+    .await
+    {
+        Ok(member) => Ok(HttpResponse::Created().json(member)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to accept the authenticated user's invitation to a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the accepted membership.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn accept_vehicle_invitation(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::accept_vehicle_invitation(&pool, user_id, vehicle_id.into_inner()).await {
+        Ok(member) => Ok(HttpResponse::Ok().json(member)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to remove a member from a vehicle, withdraw an invitation, or decline one.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the ID of the member to remove.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the member was removed.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn remove_vehicle_member(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, member_id) = path.into_inner();
+    match services::remove_vehicle_member(&pool, user_id, organization_id, vehicle_id, member_id)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the authenticated user's pending vehicle invitations.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the list of invitations.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_pending_invitations(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::get_pending_invitations(&pool, user_id).await {
+        Ok(invitations) => Ok(HttpResponse::Ok().json(invitations)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to create a new odometer entry.
+///
+/// This handler wraps the `create_new_odometer` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `odometer_data`: The odometer data from the request.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the created odometer data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_odometer(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    odometer_data: web::Json<NewOdometerRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::create_new_odometer(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        units.odometer_request(odometer_data.into_inner()),
+    )
+    .await
+    {
+        Ok(odometer) => Ok(HttpResponse::Created().json(units.odometer(odometer))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get the latest odometer entry for a vehicle.
+///
+/// This handler wraps the `get_latest_odometer` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the latest odometer data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_latest_odometer(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::get_latest_odometer(&pool, user_id, organization_id, vehicle_id).await {
+        Ok(odometer) => Ok(HttpResponse::Ok().json(units.odometer(odometer))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to create a new refuel event.
+///
+/// This handler wraps the `create_new_refuel` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `refuel_data`: The refuel data from the request.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the created refuel data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_refuel(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    refuel_data: web::Json<NewRefuelRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::create_new_refuel(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        units.refuel_request(refuel_data.into_inner()),
+        units,
+    )
+    .await
+    {
+        Ok(refuel) => Ok(HttpResponse::Created().json(units.refuel(refuel))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Largest JSON body accepted by the refuel import and backup restore endpoints, in bytes.
+pub const MAX_IMPORT_BODY: usize = 16 * 1024 * 1024;
+
+/// Handler to import refuels of a vehicle from a CSV document.
+///
+/// This handler wraps the `import_refuels` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `import`: The CSV document, its column mapping and whether this is a dry run.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the import report; rows are only imported when none was rejected.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn import_refuels(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    import: web::Json<RefuelImportRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+
+    match services::import_refuels(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        import.into_inner(),
+        units,
+        timezone,
+    )
+    .await
+    {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get the latest refuel event for a vehicle.
+///
+/// This handler wraps the `get_latest_refuel` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the latest refuel data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_latest_refuel(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::get_latest_refuel(&pool, user_id, organization_id, vehicle_id).await {
+        Ok(refuel) => Ok(HttpResponse::Ok().json(units.refuel(refuel))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get time-series odometer data for a vehicle.
+///
+/// This handler wraps the `get_odometer_timeseries` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: Start of the date range.
+/// - `end_date`: End of the date range.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the time-series odometer data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_odometer_timeseries(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<(DateTime<Utc>, DateTime<Utc>)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    let (start_date, end_date) = query.into_inner();
+
+    match services::get_odometer_timeseries(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        start_date,
+        end_date,
+    )
+    .await
+    {
+        Ok(odometer_data) => Ok(HttpResponse::Ok().json(
+            odometer_data
+                .into_iter()
+                .map(|odometer| units.odometer(odometer))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get time-series refuel data for a vehicle.
+///
+/// This handler wraps the `get_refuel_timeseries` service function.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: Start of the date range.
+/// - `end_date`: End of the date range.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the time-series refuel data.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_refuel_timeseries(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<(DateTime<Utc>, DateTime<Utc>)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    let (start_date, end_date) = query.into_inner();
+
+    match services::get_refuel_timeseries(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        start_date,
+        end_date,
+    )
+    .await
+    {
+        Ok(refuel_data) => Ok(HttpResponse::Ok().json(
+            refuel_data
+                .into_iter()
+                .map(|refuel| units.refuel(refuel))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Starts a `200 OK` response carrying a CSV file to be downloaded as `filename`.
+fn csv_attachment(filename: &str) -> HttpResponseBuilder {
+    let mut response = HttpResponse::Ok();
+    response
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", filename),
+        ));
+    response
+}
+
+/// Handler to export the user's vehicles as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: Whether archived vehicles are exported too (`include_archived`).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the vehicles as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_vehicles_csv(
+    pool: web::Data<DbPool>,
+    query: web::Query<VehicleListQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let units = req.user_preferences().units();
+
+    match services::export_vehicles_csv(
+        &pool,
+        user_id,
+        organization_id,
+        query.include_archived,
+        units,
+    )
+    .await
+    {
+        Ok(csv) => Ok(csv_attachment("vehicles.csv").body(csv)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to export the odometer readings of a vehicle as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `query`: The `start_date` and `end_date` of the export (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the readings streamed as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_odometer_csv(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    export_history_csv(pool, Some(vehicle_id.into_inner()), query, req, "odometer").await
+}
+
+/// Handler to export the odometer readings of every vehicle the user can view as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: The `start_date` and `end_date` of the export (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the readings streamed as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_all_odometer_csv(
+    pool: web::Data<DbPool>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    export_history_csv(pool, None, query, req, "odometer").await
+}
+
+/// Handler to export the refuels of a vehicle as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `query`: The `start_date` and `end_date` of the export (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the refuels streamed as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_refuel_csv(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    export_history_csv(pool, Some(vehicle_id.into_inner()), query, req, "refuel").await
+}
+
+/// Handler to export the refuels of every vehicle the user can view as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: The `start_date` and `end_date` of the export (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the refuels streamed as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_all_refuel_csv(
+    pool: web::Data<DbPool>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    export_history_csv(pool, None, query, req, "refuel").await
+}
+
+/// Streams an odometer (`history` = `odometer`) or refuel (`refuel`) CSV export of one vehicle,
+/// or of every vehicle the user can view when `vehicle_id` is `None`.
+async fn export_history_csv(
+    pool: web::Data<DbPool>,
+    vehicle_id: Option<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+    history: &str,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+
+    let DateIntervalRequest {
+        start_date,
+        end_date,
+    } = query.into_inner();
+    let export = if history == "odometer" {
+        services::export_odometer_csv(
+            &pool,
+            user_id,
+            organization_id,
+            vehicle_id,
+            start_date,
+            end_date,
+            preferences,
+        )
+        .await
+    } else {
+        services::export_refuel_csv(
+            &pool,
+            user_id,
+            organization_id,
+            vehicle_id,
+            start_date,
+            end_date,
+            preferences,
+        )
+        .await
+    };
+
+    let filename = match vehicle_id {
+        Some(vehicle_id) => format!("{}-{}-{}-{}.csv", history, vehicle_id, start_date, end_date),
+        None => format!("{}-{}-{}.csv", history, start_date, end_date),
+    };
+    match export {
+        Ok(csv) => Ok(csv_attachment(&filename).streaming(csv)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub async fn get_traveled_distance(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        preferences.units(),
+    )
+    .await?;
+    let timezone = preferences.timezone();
+
+    let request = query.into_inner();
+
+    match services::get_traveled_distance(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        request.start_date,
+        request.end_date,
+        timezone,
+    )
+    .await
+    {
+        Ok(traveled) => Ok(HttpResponse::Ok().json(traveled.map(|t| units.traveled_distance(t)))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the driver assignments of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the list of assignments, newest first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_driver_assignments(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_driver_assignments(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(assignments) => Ok(HttpResponse::Ok().json(assignments)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to assign a driver to a vehicle (vehicle owners only).
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `assignment_data`: The driver's username and the assignment period.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the new assignment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn assign_driver(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    assignment_data: web::Json<AssignDriverRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::assign_driver(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        assignment_data.into_inner(),
+    )
+    .await
+    {
+        Ok(assignment) => Ok(HttpResponse::Created().json(assignment)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete a driver assignment of a vehicle (vehicle owners only).
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the assignment ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the assignment was deleted.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn remove_driver_assignment(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, assignment_id) = path.into_inner();
+    match services::remove_driver_assignment(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        assignment_id,
+    )
+    .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to report the distance driven and fuel added per driver of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `start_date` and `end_date` of the report (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with one entry per driver.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_driver_report(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+
+    let request = query.into_inner();
+    match services::get_driver_report(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        request.start_date,
+        request.end_date,
+        timezone,
+    )
+    .await
+    {
+        Ok(report) => Ok(HttpResponse::Ok().json(
+            report
+                .into_iter()
+                .map(|usage| units.driver_usage(usage))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the trips of a vehicle that started within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `start_date` and `end_date` of the range (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the list of trips, oldest first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_trips(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        preferences.units(),
+    )
+    .await?;
+    let timezone = preferences.timezone();
+
+    let request = query.into_inner();
+    match services::get_trips(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        request.start_date,
+        request.end_date,
+        timezone,
+    )
+    .await
+    {
+        Ok(trips) => Ok(HttpResponse::Ok().json(
+            trips
+                .into_iter()
+                .map(|trip| units.trip(trip))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to summarize business and private mileage of a vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `start_date` and `end_date` of the range (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the mileage summary.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_trip_summary(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+
+    let request = query.into_inner();
+    match services::get_trip_summary(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        request.start_date,
+        request.end_date,
+        preferences.timezone(),
+    )
+    .await
+    {
+        Ok(summary) => Ok(HttpResponse::Ok().json(units.trip_summary(summary))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get a periodic mileage report of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `period` (`monthly`, `quarterly` or `yearly`) and the `start_date` and
+///   `end_date` of the report (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the report.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_mileage_report(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<ReportQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+
+    match services::get_mileage_report(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        query.into_inner(),
+        timezone,
+    )
+    .await
+    {
+        Ok(report) => Ok(HttpResponse::Ok().json(units.mileage_report(report))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to download a periodic mileage report of a vehicle as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `period` (`monthly`, `quarterly` or `yearly`) and the `start_date` and
+///   `end_date` of the report (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the report as a CSV attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_mileage_report_csv(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<ReportQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+
+    let vehicle_id = vehicle_id.into_inner();
+    match services::get_mileage_report(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        query.into_inner(),
+        timezone,
+    )
+    .await
+    {
+        Ok(report) => {
+            let report = units.mileage_report(report);
+            let filename = format!(
+                "mileage-report-{}-{}-{}.csv",
+                vehicle_id, report.start_date, report.end_date
+            );
+            Ok(csv_attachment(&filename).body(services::mileage_report_csv(&report)))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get a trip of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the trip ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the trip.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_trip(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, trip_id) = path.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+    match services::get_trip(&pool, user_id, organization_id, vehicle_id, trip_id).await {
+        Ok(trip) => Ok(HttpResponse::Ok().json(units.trip(trip))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to log a trip of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `trip_data`: The trip details.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the new trip.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_trip(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    trip_data: web::Json<NewTripRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::create_trip(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        units.trip_request(trip_data.into_inner()),
+    )
+    .await
+    {
+        Ok(trip) => Ok(HttpResponse::Created().json(units.trip(trip))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to update a logged trip of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the trip ID.
+/// - `update_data`: The fields to change.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the updated trip.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn update_trip(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    update_data: web::Json<UpdateTripRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, trip_id) = path.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+    match services::update_trip(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        trip_id,
+        units.trip_update(update_data.into_inner()),
+    )
+    .await
+    {
+        Ok(trip) => Ok(HttpResponse::Ok().json(units.trip(trip))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete a logged trip of a vehicle, along with its odometer readings.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the trip ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the trip was deleted.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn delete_trip(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, trip_id) = path.into_inner();
+    match services::delete_trip(&pool, user_id, organization_id, vehicle_id, trip_id).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to record a charging session of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `session_data`: The session details.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the new session and its efficiency since the previous one.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_charging_session(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    session_data: web::Json<NewChargingSessionRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        req.user_preferences().units(),
+    )
+    .await?;
+
+    match services::create_charging_session(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        units.charging_request(session_data.into_inner()),
+    )
+    .await
+    {
+        Ok(session) => Ok(HttpResponse::Created().json(units.charging_session(session))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the charging sessions of a vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `start_date` and `end_date` of the range (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the list of sessions, oldest first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_charging_sessions(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let vehicle_id = vehicle_id.into_inner();
+    let units = services::vehicle_units(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        preferences.units(),
+    )
+    .await?;
+    let timezone = preferences.timezone();
+
+    let request = query.into_inner();
+    match services::get_charging_sessions(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        request.start_date,
+        request.end_date,
+        timezone,
+    )
+    .await
+    {
+        Ok(sessions) => Ok(HttpResponse::Ok().json(
+            sessions
+                .into_iter()
+                .map(|session| units.charging_session(session))
+                .collect::<Vec<_>>(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to summarize the charging sessions and efficiency of a vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The vehicle ID.
+/// - `query`: The `start_date` and `end_date` of the range (inclusive).
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the charging summary.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_charging_summary(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DateIntervalRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+
+    let request = query.into_inner();
+    match services::get_charging_summary(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        request.start_date,
+        request.end_date,
+        preferences.timezone(),
+    )
+    .await
+    {
+        Ok(summary) => Ok(HttpResponse::Ok().json(units.charging_summary(summary))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete a charging session of a vehicle, along with its odometer reading.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the session ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the session was deleted.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn delete_charging_session(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, session_id) = path.into_inner();
+    match services::delete_charging_session(&pool, user_id, organization_id, vehicle_id, session_id)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Largest text field (`kind` or `refuel_id`) accepted in a document upload, in bytes.
+const MAX_UPLOAD_FIELD: usize = 256;
+
+/// Reads a document upload from a `multipart/form-data` request.
+///
+/// The `file` part is read up to `DOCUMENT_MAX_BYTES`, so oversized uploads are rejected without
+/// buffering them; unknown parts are ignored.
+async fn read_document_upload(mut payload: Multipart) -> Result<DocumentUpload, Error> {
+    let mut file: Option<(String, Vec<u8>)> = None;
+    let mut kind = None;
+    let mut refuel_id = None;
+
+    while let Some(mut field) = payload.try_next().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        let limit = match name.as_str() {
+            "file" => *documents::DOCUMENT_MAX_BYTES,
+            "kind" | "refuel_id" => MAX_UPLOAD_FIELD,
+            _ => continue,
+        };
+
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.try_next().await? {
+            if bytes.len() + chunk.len() > limit {
+                return Err(match name.as_str() {
+                    "file" => actix_web::error::ErrorPayloadTooLarge(format!(
+                        "File must be at most {} bytes",
+                        limit
+                    )),
+                    _ => actix_web::error::ErrorUnprocessableEntity(format!(
+                        "Field {} is too long",
+                        name
+                    )),
+                });
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        match name.as_str() {
+            "file" => {
+                let filename = field
+                    .content_disposition()
+                    .and_then(|disposition| disposition.get_filename())
+                    .unwrap_or_default()
+                    .to_string();
+                file = Some((filename, bytes));
+            }
+            _ => {
+                let value = String::from_utf8(bytes)
+                    .map_err(|_| actix_web::error::ErrorUnprocessableEntity("Invalid field"))?;
+                if name == "kind" {
+                    kind = Some(value);
+                } else if !value.trim().is_empty() {
+                    refuel_id = Some(value.trim().parse::<Uuid>().map_err(|_| {
+                        actix_web::error::ErrorUnprocessableEntity("Invalid refuel_id")
+                    })?);
+                }
+            }
+        }
+    }
+
+    let (filename, bytes) =
+        file.ok_or_else(|| actix_web::error::ErrorUnprocessableEntity("Missing file"))?;
+    Ok(DocumentUpload {
+        filename,
+        bytes,
+        kind,
+        refuel_id,
+    })
+}
+
+/// Handler to upload a document to a vehicle.
+///
+/// This handler wraps the `upload_document` service function. The caller's access to the
+/// vehicle is checked before the body is read, so that unauthorized uploads are not buffered.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `payload`: A `multipart/form-data` body with the `file` and optionally its `kind` and the
+///   `refuel_id` to attach it to.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the new document.
+/// - `413 Payload Too Large` if the file exceeds `DOCUMENT_MAX_BYTES`.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn upload_document(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    payload: Multipart,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+    let vehicle_id = vehicle_id.into_inner();
+
+    services::authorize_document_upload(&pool, user_id, organization_id, vehicle_id).await?;
+    let upload = read_document_upload(payload).await?;
+    match services::upload_document(&pool, user_id, organization_id, vehicle_id, upload).await {
+        Ok(document) => Ok(HttpResponse::Created().json(document)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the documents of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `query`: Optional `refuel_id` to filter by.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the documents, newest first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_documents(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<DocumentQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_documents(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        query.into_inner(),
+    )
+    .await
+    {
+        Ok(documents) => Ok(HttpResponse::Ok().json(documents)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Sends a stored document file inline under its original name.
+///
+/// The media type is the one detected on upload, and `nosniff` keeps browsers from
+/// reinterpreting the file as anything else.
+async fn serve_document_file(file: DocumentFile, req: &HttpRequest) -> Result<HttpResponse, Error> {
+    let content_type = file
+        .content_type
+        .parse::<mime::Mime>()
+        .unwrap_or(mime::APPLICATION_OCTET_STREAM);
+
+    let mut parameters = vec![DispositionParam::Filename(
+        file.filename
+            .chars()
+            .map(|c| {
+                if c.is_ascii() && !c.is_ascii_control() {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect(),
+    )];
+    if !file.filename.is_ascii() {
+        parameters.push(DispositionParam::FilenameExt(ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: file.filename.into_bytes(),
+        }));
+    }
+
+    let named_file = NamedFile::open_async(&file.path).await.map_err(|e| {
+        log::error!(
+            "Failed to open document file {}: {}",
+            file.path.display(),
+            e
+        );
+        actix_web::error::ErrorNotFound("Document file not found")
+    })?;
+    let mut response = named_file
+        .set_content_type(content_type)
+        .set_content_disposition(ContentDisposition {
+            disposition: DispositionType::Inline,
+            parameters,
+        })
+        .into_response(req);
+    response
+        .headers_mut()
+        .insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    Ok(response)
+}
+
+/// Handler to download a document of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the document ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the file.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn download_document(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, document_id) = path.into_inner();
+    match services::get_document_file(&pool, user_id, organization_id, vehicle_id, document_id)
+        .await
+    {
+        Ok(file) => serve_document_file(file, &req).await,
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get the thumbnail of an image document of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the document ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the JPEG thumbnail.
+/// - `404 Not Found` if the document is not an image.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_document_thumbnail(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, document_id) = path.into_inner();
+    match services::get_document_thumbnail(&pool, user_id, organization_id, vehicle_id, document_id)
+        .await
+    {
+        Ok(file) => serve_document_file(file, &req).await,
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete a document of a vehicle, including its stored file.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the document ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the document was deleted.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn delete_document(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, document_id) = path.into_inner();
+    match services::delete_document(&pool, user_id, organization_id, vehicle_id, document_id).await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the insurance policies of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the policies, latest-ending first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_insurance_policies(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_insurance_policies(&pool, user_id, organization_id, vehicle_id.into_inner())
+        .await
+    {
+        Ok(policies) => Ok(HttpResponse::Ok().json(policies)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to record an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `policy_data`: The policy details.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the new policy.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_insurance_policy(
+    pool: web::Data<DbPool>,
+    vehicle_id: web::Path<Uuid>,
+    policy_data: web::Json<NewInsurancePolicyRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::create_insurance_policy(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id.into_inner(),
+        policy_data.into_inner(),
+    )
+    .await
+    {
+        Ok(policy) => Ok(HttpResponse::Created().json(policy)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to get an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the policy ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the policy.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_insurance_policy(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, policy_id) = path.into_inner();
+    match services::get_insurance_policy(&pool, user_id, organization_id, vehicle_id, policy_id)
+        .await
+    {
+        Ok(policy) => Ok(HttpResponse::Ok().json(policy)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to update an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the policy ID.
+/// - `update_data`: The fields to update.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the updated policy.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn update_insurance_policy(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    update_data: web::Json<UpdateInsurancePolicyRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, policy_id) = path.into_inner();
+    match services::update_insurance_policy(
+        &pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        policy_id,
+        update_data.into_inner(),
+    )
+    .await
+    {
+        Ok(policy) => Ok(HttpResponse::Ok().json(policy)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `path`: The vehicle ID and the policy ID.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the policy was deleted.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn delete_insurance_policy(
+    pool: web::Data<DbPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    let (vehicle_id, policy_id) = path.into_inner();
+    match services::delete_insurance_policy(&pool, user_id, organization_id, vehicle_id, policy_id)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to download a backup of the vehicles the user owns in the active organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the backup as a JSON attachment.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn export_backup(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::export_backup(&pool, user_id, organization_id).await {
+        Ok(backup) => Ok(HttpResponse::Ok()
+            .insert_header((
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"vimana-backup-{}.json\"",
+                    backup
+                        .exported_at
+                        .with_timezone(&req.user_preferences().timezone())
+                        .date_naive()
+                ),
+            ))
+            .json(backup)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to restore a backup into the active organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `backup`: The backup document.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the restored vehicles.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn restore_backup(
+    pool: web::Data<DbPool>,
+    backup: web::Json<Backup>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::restore_backup(&pool, user_id, organization_id, backup.into_inner()).await {
+        Ok(vehicles) => Ok(HttpResponse::Created().json(vehicles)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the authenticated user's notifications.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: Optional `unread` and `limit` filters.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the notifications that were not dismissed, newest first.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_notifications(
+    pool: web::Data<DbPool>,
+    query: web::Query<NotificationQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::get_notifications(&pool, user_id, query.into_inner()).await {
+        Ok(notifications) => Ok(HttpResponse::Ok().json(notifications)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to count the authenticated user's unread notifications.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the number of unread notifications.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn count_unread_notifications(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::count_unread_notifications(&pool, user_id).await {
+        Ok(count) => Ok(HttpResponse::Ok().json(count)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to mark one of the authenticated user's notifications as read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `notification_id`: The ID of the notification.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the read notification.
+/// - `404 Not Found` if the user has no such notification in their inbox.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn mark_notification_read(
+    pool: web::Data<DbPool>,
+    notification_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::mark_notification_read(&pool, user_id, notification_id.into_inner()).await {
+        Ok(notification) => Ok(HttpResponse::Ok().json(notification)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to mark all of the authenticated user's notifications as read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the number of unread notifications left.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn mark_all_notifications_read(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::mark_all_notifications_read(&pool, user_id).await {
+        Ok(count) => Ok(HttpResponse::Ok().json(count)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to dismiss one of the authenticated user's notifications from their inbox.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `notification_id`: The ID of the notification.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the notification was dismissed.
+/// - `404 Not Found` if the user has no such notification in their inbox.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn dismiss_notification(
+    pool: web::Data<DbPool>,
+    notification_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::dismiss_notification(&pool, user_id, notification_id.into_inner()).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to list the authenticated user's webhooks in the active organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the webhooks, without their secrets.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_webhooks(
+    pool: web::Data<DbPool>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_webhooks(&pool, user_id, organization_id).await {
+        Ok(webhooks) => Ok(HttpResponse::Ok().json(webhooks)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to add an outgoing webhook in the active organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `request`: JSON payload with the URL, the subscribed events and an optional secret.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `201 Created` with the webhook and its signing secret.
+/// - `422 Unprocessable Entity` if the URL, events or secret are not valid.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn create_webhook(
+    pool: web::Data<DbPool>,
+    request: web::Json<NewWebhookRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::create_webhook(&pool, user_id, organization_id, request.into_inner()).await {
+        Ok(webhook) => Ok(HttpResponse::Created().json(webhook)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to change, pause or resume one of the authenticated user's webhooks.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `request`: JSON payload with the fields to change.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the updated webhook.
+/// - `404 Not Found` if the user has no such webhook in the active organization.
+/// - `422 Unprocessable Entity` if the URL or events are not valid.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn update_webhook(
+    pool: web::Data<DbPool>,
+    webhook_id: web::Path<Uuid>,
+    request: web::Json<UpdateWebhookRequest>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::update_webhook(
+        &pool,
+        user_id,
+        organization_id,
+        webhook_id.into_inner(),
+        request.into_inner(),
+    )
+    .await
+    {
+        Ok(webhook) => Ok(HttpResponse::Ok().json(webhook)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to delete one of the authenticated user's webhooks.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `204 No Content` if the webhook was deleted.
+/// - `404 Not Found` if the user has no such webhook in the active organization.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn delete_webhook(
+    pool: web::Data<DbPool>,
+    webhook_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::delete_webhook(&pool, user_id, organization_id, webhook_id.into_inner()).await {
+        Ok(_) => Ok(HttpResponse::NoContent().finish()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to read the delivery log of one of the authenticated user's webhooks.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `query`: Optional `status` and `limit` filters.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the deliveries, newest first.
+/// - `404 Not Found` if the user has no such webhook in the active organization.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_webhook_deliveries(
+    pool: web::Data<DbPool>,
+    webhook_id: web::Path<Uuid>,
+    query: web::Query<WebhookDeliveryQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::get_webhook_deliveries(
+        &pool,
+        user_id,
+        organization_id,
+        webhook_id.into_inner(),
+        query.into_inner(),
+    )
+    .await
     {
-        Ok(refuel_data) => Ok(HttpResponse::Ok().json(refuel_data)),
+        Ok(deliveries) => Ok(HttpResponse::Ok().json(deliveries)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to send a test `ping` event to one of the authenticated user's webhooks.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `202 Accepted` with the queued delivery.
+/// - `404 Not Found` if the user has no such webhook in the active organization.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn ping_webhook(
+    pool: web::Data<DbPool>,
+    webhook_id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+    let organization_id = req
+        .active_organization_id()
+        .ok_or_else(|| actix_web::error::ErrorForbidden("No active organization"))?;
+
+    match services::ping_webhook(&pool, user_id, organization_id, webhook_id.into_inner()).await {
+        Ok(delivery) => Ok(HttpResponse::Accepted().json(delivery)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Handler to query the audit log, restricted to administrators.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `query`: Optional `user_id`, `entity_type`, `entity_id`, `start`, `end` and `limit` filters.
+/// - `req`: HTTP request object (for extracting user ID).
+///
+/// # Returns
+/// - `200 OK` with the matching audit entries, newest first.
+/// - `403 Forbidden` if the requester is not an administrator.
+/// - Appropriate HTTP error code if the operation fails.
+pub async fn get_audit_logs(
+    pool: web::Data<DbPool>,
+    query: web::Query<AuditLogQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, Error> {
+    let user_id = req
+        .authenticated_user_id()
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Internal server error"))?;
+
+    match services::get_audit_logs(&pool, user_id, query.into_inner()).await {
+        Ok(entries) => Ok(HttpResponse::Ok().json(entries)),
         Err(e) => Err(e.into()),
     }
 }
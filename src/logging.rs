@@ -0,0 +1,109 @@
+//! Log output configuration.
+//!
+//! `LOG_FORMAT=json` switches to one JSON object per line for log shippers; anything else keeps
+//! the human-readable text format. Every line emitted while handling a request carries the
+//! request ID set by `middleware::RequestIdMiddleware`, and structured key-values passed to the
+//! `log` macros (e.g. `info!(status = 200; "...")`) become fields of their own.
+use env_logger::fmt::Formatter;
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use serde_json::{Map, Value as JsonValue};
+use std::env;
+use std::io::Write;
+
+use crate::middleware::current_request_id;
+
+/// Collects a record's structured key-values as JSON values.
+struct JsonFields<'a>(&'a mut Map<String, JsonValue>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(b) = value.to_bool() {
+            JsonValue::from(b)
+        } else if let Some(i) = value.to_i64() {
+            JsonValue::from(i)
+        } else if let Some(u) = value.to_u64() {
+            JsonValue::from(u)
+        } else if let Some(f) = value.to_f64() {
+            JsonValue::from(f)
+        } else {
+            JsonValue::from(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// Collects a record's structured key-values as ` key=value` pairs.
+struct TextFields<'a>(&'a mut String);
+
+impl<'kvs> VisitSource<'kvs> for TextFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.push_str(&format!(" {}={}", key, value));
+        Ok(())
+    }
+}
+
+/// Formats a record as a single-line JSON object.
+fn format_json(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = Map::new();
+    line.insert(
+        "timestamp".to_string(),
+        JsonValue::from(chrono::Utc::now().to_rfc3339()),
+    );
+    line.insert(
+        "level".to_string(),
+        JsonValue::from(record.level().to_string()),
+    );
+    line.insert("target".to_string(), JsonValue::from(record.target()));
+    line.insert(
+        "message".to_string(),
+        JsonValue::from(record.args().to_string()),
+    );
+    if let Some(request_id) = current_request_id() {
+        line.insert("request_id".to_string(), JsonValue::from(request_id));
+    }
+
+    let mut fields = Map::new();
+    let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+    for (key, value) in fields {
+        line.entry(key).or_insert(value);
+    }
+
+    writeln!(buf, "{}", JsonValue::Object(line))
+}
+
+/// Formats a record in the human-readable text format.
+fn format_text(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut fields = String::new();
+    if let Some(request_id) = current_request_id() {
+        fields.push_str(&format!(" request_id={}", request_id));
+    }
+    let _ = record.key_values().visit(&mut TextFields(&mut fields));
+
+    writeln!(
+        buf,
+        "{} [{}] {}: {}{}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        record.level(),
+        record.target(),
+        record.args(),
+        fields
+    )
+}
+
+/// Installs the global logger, choosing the output format from `LOG_FORMAT`.
+pub fn init() {
+    let json = env::var("LOG_FORMAT")
+        .map(|format| format.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(log::LevelFilter::Info);
+    if json {
+        builder.format(format_json);
+    } else {
+        builder.format(format_text);
+    }
+    builder.init();
+}
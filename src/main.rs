@@ -1,37 +1,52 @@
 /// Main module and entry point for the Actix Web server application.
 ///
 /// This module sets up the application server, establishes a connection to the database,
-/// and configures middleware for logging, CORS, and authentication. The server provides
-/// both public and protected routes, and employs a structured `DbPool` for efficient
-/// database access.
+/// and configures middleware for request IDs and access logging, metrics, CORS, and authentication.
+/// It also starts the notification scheduler for registration expiry reminders and fuel economy
+/// alerts, and the delivery worker of outgoing webhooks. The server provides both public and
+/// protected routes, and employs a structured `DbPool` for efficient database access.
+mod documents;
 mod handlers;
+mod logging;
+mod metrics;
 mod middleware;
 mod models;
+mod notifications;
 mod queries;
 mod renders;
 mod requests;
+mod responses;
 mod schema;
 mod services;
+mod units;
+mod webhooks;
 
 use actix_cors::Cors;
 use actix_files as fs;
-use actix_web::middleware::Logger;
 use actix_web::{web, App, HttpServer};
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
 use dotenv::dotenv;
-use middleware::AuthMiddleware;
+use middleware::{AuthMiddleware, MetricsMiddleware, RequestIdMiddleware};
 use std::env;
-use std::io::Write;
+use std::time::Instant;
 use tera::Tera;
 
 /// Type alias for a Diesel connection pool for PostgreSQL, enabling shared access to database connections.
 type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
+/// The instant the server started, shared with handlers to report uptime.
+#[derive(Clone, Copy)]
+pub struct StartedAt(pub Instant);
+
 /// Initializes the main application, setting up the Actix Web server with middleware, routing,
 /// and database connection pooling. The server provides a set of routes with different access levels:
-/// - Public: login route
-/// - Protected: health check, logout, and user creation routes
+/// - Public: login, first-run setup, liveness and readiness routes, and the metrics route, which
+///   checks the `METRICS_TOKEN` bearer token itself
+/// - Protected: logout, user creation, email address and preferences, audit log, backup and restore,
+///   invitations, notifications, webhooks, organization (and organization member and invitation),
+///   vehicle (and vehicle member, driver, trip, charging session, document, insurance policy and
+///   mileage report), odometer and refuel routes, scoped to the session's active organization
 ///
 /// # Returns
 ///
@@ -39,6 +54,7 @@ type DbPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     dotenv().ok();
+    let started_at = StartedAt(Instant::now());
 
     let tera = Tera::new("templates/**/*").unwrap();
     let base_url = if env::var("DEPLOY_PROD").unwrap_or_else(|_| "0".to_string()) == "1" {
@@ -47,19 +63,7 @@ async fn main() -> std::io::Result<()> {
         ""
     };
 
-    env_logger::Builder::new()
-        .filter_level(log::LevelFilter::Info)
-        .format(|buf, record| {
-            writeln!(
-                buf,
-                "{} [{}] {}: {}",
-                chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
-                record.level(),
-                record.target(),
-                record.args()
-            )
-        })
-        .init();
+    logging::init();
 
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let manager = ConnectionManager::<PgConnection>::new(database_url);
@@ -67,12 +71,17 @@ async fn main() -> std::io::Result<()> {
         .build(manager)
         .expect("Failed to create pool.");
 
+    notifications::spawn_scheduler(pool.clone());
+    webhooks::spawn_worker(pool.clone());
+
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(tera.clone()))
             .app_data(web::Data::new(base_url.to_string()))
-            .wrap(Logger::default())
+            .app_data(web::Data::new(started_at))
+            .wrap(MetricsMiddleware)
+            .wrap(RequestIdMiddleware)
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -80,6 +89,8 @@ async fn main() -> std::io::Result<()> {
                     .allow_any_header(),
             )
             .route("/", web::get().to(renders::render_login))
+            .route("/setup", web::get().to(renders::render_setup))
+            .route("/metrics", web::get().to(handlers::metrics))
             .route(
                 "/home",
                 web::get()
@@ -98,23 +109,125 @@ async fn main() -> std::io::Result<()> {
                     .to(renders::render_vehicle)
                     .wrap(AuthMiddleware::new(pool.clone())),
             )
+            .route(
+                "/vehicles/{vehicle_id}/report",
+                web::get()
+                    .to(renders::render_report)
+                    .wrap(AuthMiddleware::new(pool.clone())),
+            )
             .service(
                 web::scope("/api")
                     .service(
                         web::scope("/public")
                             .route("/login", web::post().to(handlers::login))
-                            .route("/health", web::get().to(handlers::health_check)),
+                            .route("/setup", web::post().to(handlers::setup))
+                            .route("/health", web::get().to(handlers::readiness))
+                            .route("/health/live", web::get().to(handlers::liveness))
+                            .route("/health/ready", web::get().to(handlers::readiness)),
                     )
                     .service(
                         web::scope("/protected")
                             .wrap(AuthMiddleware::new(pool.clone()))
-                            .route("/health", web::get().to(handlers::health_check))
                             .route("/logout", web::post().to(handlers::logout))
                             .route("/users", web::post().to(handlers::create_user))
+                            .route("/users/me/email", web::put().to(handlers::update_email))
+                            .route(
+                                "/users/me/preferences",
+                                web::get().to(handlers::get_preferences),
+                            )
+                            .route(
+                                "/users/me/preferences",
+                                web::put().to(handlers::update_preferences),
+                            )
+                            .route("/audit", web::get().to(handlers::get_audit_logs))
+                            .service(
+                                web::resource("/backup")
+                                    .app_data(
+                                        web::JsonConfig::default().limit(handlers::MAX_IMPORT_BODY),
+                                    )
+                                    .route(web::get().to(handlers::export_backup))
+                                    .route(web::post().to(handlers::restore_backup)),
+                            )
+                            .route(
+                                "/invitations",
+                                web::get().to(handlers::get_pending_invitations),
+                            )
+                            .service(
+                                web::scope("/notifications")
+                                    .route("/", web::get().to(handlers::get_notifications))
+                                    .route(
+                                        "/unread_count",
+                                        web::get().to(handlers::count_unread_notifications),
+                                    )
+                                    .route(
+                                        "/read",
+                                        web::post().to(handlers::mark_all_notifications_read),
+                                    )
+                                    .route(
+                                        "/{notification_id}/read",
+                                        web::post().to(handlers::mark_notification_read),
+                                    )
+                                    .route(
+                                        "/{notification_id}",
+                                        web::delete().to(handlers::dismiss_notification),
+                                    ),
+                            )
+                            .service(
+                                web::scope("/webhooks")
+                                    .route("/", web::get().to(handlers::get_webhooks))
+                                    .route("/", web::post().to(handlers::create_webhook))
+                                    .route("/{webhook_id}", web::put().to(handlers::update_webhook))
+                                    .route(
+                                        "/{webhook_id}",
+                                        web::delete().to(handlers::delete_webhook),
+                                    )
+                                    .route(
+                                        "/{webhook_id}/deliveries",
+                                        web::get().to(handlers::get_webhook_deliveries),
+                                    )
+                                    .route(
+                                        "/{webhook_id}/ping",
+                                        web::post().to(handlers::ping_webhook),
+                                    ),
+                            )
+                            .service(
+                                web::scope("/organizations")
+                                    .route("/", web::get().to(handlers::get_organizations))
+                                    .route("/", web::post().to(handlers::create_organization))
+                                    .route(
+                                        "/invitations",
+                                        web::get()
+                                            .to(handlers::get_pending_organization_invitations),
+                                    )
+                                    .route(
+                                        "/{organization_id}/switch",
+                                        web::post().to(handlers::switch_organization),
+                                    )
+                                    .route(
+                                        "/{organization_id}/members",
+                                        web::get().to(handlers::get_organization_members),
+                                    )
+                                    .route(
+                                        "/{organization_id}/members",
+                                        web::post().to(handlers::invite_organization_member),
+                                    )
+                                    .route(
+                                        "/{organization_id}/members/accept",
+                                        web::post().to(handlers::accept_organization_invitation),
+                                    )
+                                    .route(
+                                        "/{organization_id}/members/{user_id}",
+                                        web::delete().to(handlers::remove_organization_member),
+                                    ),
+                            )
                             .service(
                                 web::scope("/vehicles")
                                     .route("/", web::post().to(handlers::create_vehicle))
                                     .route("/", web::get().to(handlers::get_vehicles_by_user))
+                                    .route(
+                                        "/export.csv",
+                                        web::get().to(handlers::export_vehicles_csv),
+                                    )
                                     .route(
                                         "/{vehicle_id}",
                                         web::get().to(handlers::get_vehicle_by_id),
@@ -125,7 +238,135 @@ async fn main() -> std::io::Result<()> {
                                     )
                                     .route(
                                         "/{vehicle_id}",
-                                        web::delete().to(handlers::delete_vehicle_by_id),
+                                        web::delete().to(handlers::archive_vehicle_by_id),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/restore",
+                                        web::post().to(handlers::restore_vehicle_by_id),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/purge",
+                                        web::delete().to(handlers::purge_vehicle_by_id),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/members",
+                                        web::get().to(handlers::get_vehicle_members),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/members",
+                                        web::post().to(handlers::invite_vehicle_member),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/members/accept",
+                                        web::post().to(handlers::accept_vehicle_invitation),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/members/{user_id}",
+                                        web::delete().to(handlers::remove_vehicle_member),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/drivers",
+                                        web::get().to(handlers::get_driver_assignments),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/drivers",
+                                        web::post().to(handlers::assign_driver),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/drivers/report",
+                                        web::get().to(handlers::get_driver_report),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/drivers/{assignment_id}",
+                                        web::delete().to(handlers::remove_driver_assignment),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips",
+                                        web::get().to(handlers::get_trips),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips",
+                                        web::post().to(handlers::create_trip),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips/summary",
+                                        web::get().to(handlers::get_trip_summary),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips/{trip_id}",
+                                        web::get().to(handlers::get_trip),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips/{trip_id}",
+                                        web::put().to(handlers::update_trip),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/trips/{trip_id}",
+                                        web::delete().to(handlers::delete_trip),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/charging",
+                                        web::get().to(handlers::get_charging_sessions),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/charging",
+                                        web::post().to(handlers::create_charging_session),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/charging/summary",
+                                        web::get().to(handlers::get_charging_summary),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/charging/{session_id}",
+                                        web::delete().to(handlers::delete_charging_session),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/documents",
+                                        web::get().to(handlers::get_documents),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/documents",
+                                        web::post().to(handlers::upload_document),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/documents/{document_id}",
+                                        web::get().to(handlers::download_document),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/documents/{document_id}",
+                                        web::delete().to(handlers::delete_document),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/documents/{document_id}/thumbnail",
+                                        web::get().to(handlers::get_document_thumbnail),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/insurance",
+                                        web::get().to(handlers::get_insurance_policies),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/insurance",
+                                        web::post().to(handlers::create_insurance_policy),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/insurance/{policy_id}",
+                                        web::get().to(handlers::get_insurance_policy),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/insurance/{policy_id}",
+                                        web::put().to(handlers::update_insurance_policy),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/insurance/{policy_id}",
+                                        web::delete().to(handlers::delete_insurance_policy),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/report",
+                                        web::get().to(handlers::get_mileage_report),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/report.csv",
+                                        web::get().to(handlers::get_mileage_report_csv),
                                     ),
                             )
                             .service(
@@ -142,21 +383,45 @@ async fn main() -> std::io::Result<()> {
                                         "/{vehicle_id}/timeseries",
                                         web::get().to(handlers::get_odometer_timeseries),
                                     )
+                                    .route(
+                                        "/export.csv",
+                                        web::get().to(handlers::export_all_odometer_csv),
+                                    )
                                     .route(
                                         "/{vehicle_id}/traveled",
                                         web::get().to(handlers::get_traveled_distance),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/export.csv",
+                                        web::get().to(handlers::export_odometer_csv),
                                     ),
                             )
                             .service(
                                 web::scope("/refuel")
                                     .route("/{vehicle_id}", web::post().to(handlers::create_refuel))
+                                    .service(
+                                        web::resource("/{vehicle_id}/import")
+                                            .app_data(
+                                                web::JsonConfig::default()
+                                                    .limit(handlers::MAX_IMPORT_BODY),
+                                            )
+                                            .route(web::post().to(handlers::import_refuels)),
+                                    )
                                     .route(
                                         "/{vehicle_id}/latest",
                                         web::get().to(handlers::get_latest_refuel),
                                     )
+                                    .route(
+                                        "/export.csv",
+                                        web::get().to(handlers::export_all_refuel_csv),
+                                    )
                                     .route(
                                         "/{vehicle_id}/timeseries",
                                         web::get().to(handlers::get_refuel_timeseries),
+                                    )
+                                    .route(
+                                        "/{vehicle_id}/export.csv",
+                                        web::get().to(handlers::export_refuel_csv),
                                     ),
                             ),
                     ),
@@ -0,0 +1,136 @@
+//! Prometheus metrics exposed on `/metrics`, to scrapers holding the `METRICS_TOKEN` bearer token.
+//!
+//! Request metrics are recorded by `middleware::MetricsMiddleware`, login outcomes by
+//! `services::login`, and the pool and domain gauges are refreshed on every scrape.
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+
+use crate::models::DomainCounts;
+
+lazy_static! {
+    /// Total HTTP requests, labelled by method, matched route pattern and status code.
+    pub static ref HTTP_REQUESTS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "vimana_http_requests_total",
+        "Total number of HTTP requests handled",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register vimana_http_requests_total");
+
+    /// HTTP request latency in seconds, labelled by method, matched route pattern and status code.
+    pub static ref HTTP_REQUEST_DURATION_SECONDS: HistogramVec = register_histogram_vec!(
+        "vimana_http_request_duration_seconds",
+        "HTTP request latency in seconds",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register vimana_http_request_duration_seconds");
+
+    /// Login attempts, labelled by outcome (`success` or `failure`).
+    pub static ref LOGIN_ATTEMPTS_TOTAL: IntCounterVec = register_int_counter_vec!(
+        "vimana_login_attempts_total",
+        "Total number of login attempts",
+        &["outcome"]
+    )
+    .expect("Failed to register vimana_login_attempts_total");
+
+    /// Connection pool state, labelled by `state` (`max`, `open` or `idle`).
+    pub static ref DB_POOL_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "vimana_db_pool_connections",
+        "Database connection pool connections",
+        &["state"]
+    )
+    .expect("Failed to register vimana_db_pool_connections");
+
+    /// Number of sessions that have not expired yet.
+    pub static ref ACTIVE_SESSIONS: IntGauge = register_int_gauge!(
+        "vimana_active_sessions",
+        "Number of unexpired sessions"
+    )
+    .expect("Failed to register vimana_active_sessions");
+
+    /// Number of registered users.
+    pub static ref USERS: IntGauge = register_int_gauge!(
+        "vimana_users",
+        "Number of registered users"
+    )
+    .expect("Failed to register vimana_users");
+
+    /// Number of vehicles recorded.
+    pub static ref VEHICLES: IntGauge = register_int_gauge!(
+        "vimana_vehicles",
+        "Number of vehicles recorded"
+    )
+    .expect("Failed to register vimana_vehicles");
+
+    /// Number of odometer readings recorded.
+    pub static ref ODOMETER_READINGS: IntGauge = register_int_gauge!(
+        "vimana_odometer_readings",
+        "Number of odometer readings recorded"
+    )
+    .expect("Failed to register vimana_odometer_readings");
+
+    /// Number of refuels recorded.
+    pub static ref REFUELS: IntGauge = register_int_gauge!(
+        "vimana_refuels",
+        "Number of refuels recorded"
+    )
+    .expect("Failed to register vimana_refuels");
+}
+
+/// Records a handled HTTP request.
+///
+/// # Arguments
+/// - `method`: The HTTP method of the request.
+/// - `route`: The matched route pattern, so path parameters don't explode label cardinality.
+/// - `status`: The response status code.
+/// - `seconds`: Time taken to produce the response.
+pub fn observe_request(method: &str, route: &str, status: u16, seconds: f64) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    HTTP_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(seconds);
+}
+
+/// Records the outcome of a login attempt.
+pub fn observe_login(success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    LOGIN_ATTEMPTS_TOTAL.with_label_values(&[outcome]).inc();
+}
+
+/// Updates the connection pool gauges from an r2d2 state snapshot.
+pub fn set_pool_state(max_size: u32, connections: u32, idle_connections: u32) {
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["max"])
+        .set(i64::from(max_size));
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["open"])
+        .set(i64::from(connections));
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(i64::from(idle_connections));
+}
+
+/// Updates the session and domain gauges from freshly queried counts.
+pub fn set_domain_counts(counts: &DomainCounts) {
+    ACTIVE_SESSIONS.set(counts.active_sessions);
+    USERS.set(counts.users);
+    VEHICLES.set(counts.vehicles);
+    ODOMETER_READINGS.set(counts.odometer_readings);
+    REFUELS.set(counts.refuels);
+}
+
+/// Encodes every registered metric in the Prometheus text exposition format.
+///
+/// # Returns
+/// The encoded metrics, or an error message if encoding fails.
+pub fn encode() -> Result<String, String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(|e| e.to_string())?;
+    String::from_utf8(buffer).map_err(|e| e.to_string())
+}
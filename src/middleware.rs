@@ -1,7 +1,8 @@
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
-    error::ErrorUnauthorized,
-    Error, HttpMessage, HttpRequest,
+    error::{ErrorInternalServerError, ErrorUnauthorized, InternalError},
+    http::header::{HeaderName, HeaderValue},
+    web, Error, HttpMessage, HttpRequest,
 };
 use chrono::Utc;
 use diesel::{
@@ -12,10 +13,11 @@ use futures::future::LocalBoxFuture;
 use std::{
     future::{ready, Ready},
     rc::Rc,
+    time::Instant,
 };
 use uuid::Uuid;
 
-use crate::queries;
+use crate::{metrics, models::UserPreferences, queries};
 
 /// Custom error type representing possible authentication errors in the middleware.
 ///
@@ -51,10 +53,17 @@ impl From<AuthError> for Error {
     }
 }
 
+/// Organization the authenticated session is working with, stored in the request's extensions.
+///
+/// Wrapped in its own type because extensions are keyed by type and the user ID is a bare `Uuid`.
+#[derive(Clone, Copy)]
+struct ActiveOrganization(Uuid);
+
 /// Middleware struct responsible for authenticating requests based on session tokens.
 ///
 /// This middleware fetches the session token from cookies and verifies it against the database.
-/// If valid, it inserts the `user_id` into the request's extensions for further use by handlers.
+/// If valid, it inserts the `user_id`, the user's preferences and the session's active
+/// organization into the request's extensions for further use by handlers.
 pub struct AuthMiddleware {
     pool: Pool<ConnectionManager<PgConnection>>,
 }
@@ -115,7 +124,9 @@ where
     /// Processes the incoming request, extracting and validating the session token.
     ///
     /// Retrieves the `session_token` from the request's cookies and queries the database to verify
-    /// the session. If valid and active, the associated `user_id` is inserted into the request's
+    /// the session. Sessions of disabled users are rejected like unknown ones. If valid and
+    /// active, the associated `user_id`, the user's preferences (the defaults if they have not
+    /// chosen any) and the active organization, if any, are inserted into the request's
     /// extensions for use in downstream handlers.
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let service = self.service.clone();
@@ -128,8 +139,11 @@ where
                 .value()
                 .to_string();
 
-            let session =
-                queries::get_active_session_by_token(&pool, &token, Utc::now().naive_utc())
+            // Session lookup is synchronous Diesel I/O, so run it on the blocking pool
+            let (session, preferences) =
+                web::block(move || queries::get_active_session_by_token(&pool, &token, Utc::now()))
+                    .await
+                    .map_err(|_| ErrorInternalServerError("Blocking task failed"))?
                     .map_err(AuthError::DbError)?
                     .ok_or_else(|| AuthError::SessionNotFound)?;
 
@@ -138,11 +152,223 @@ where
                 .ok_or_else(|| ErrorUnauthorized("User ID missing in session"))?;
 
             req.extensions_mut().insert(uid);
+            req.extensions_mut()
+                .insert(preferences.unwrap_or_else(|| UserPreferences::defaults(uid)));
+            if let Some(organization_id) = session.active_organization_id {
+                req.extensions_mut()
+                    .insert(ActiveOrganization(organization_id));
+            }
             service.call(req).await
         })
     }
 }
 
+/// Name of the header carrying the request ID, both inbound and outbound.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest inbound request ID that is propagated; longer values are replaced.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// Request ID of the request whose future is currently being polled.
+    static REQUEST_ID: String;
+}
+
+/// Returns the ID of the request currently being handled, if any.
+///
+/// Work moved onto the blocking thread pool runs outside the request task, so log lines
+/// emitted from there carry no request ID.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Middleware struct assigning every request an ID and writing the access log.
+///
+/// An inbound `X-Request-Id` header is propagated when it looks sane, otherwise a new UUID is
+/// generated. The ID is echoed in the `X-Request-Id` response header (error responses included),
+/// attached to every log line emitted while the request is handled, and written to the access
+/// log together with the authenticated `user_id`.
+pub struct RequestIdMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    /// Creates the transformed `RequestIdMiddlewareService` to handle requests.
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddlewareService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// Service struct resolving the request ID and logging each request once it completes.
+pub struct RequestIdMiddlewareService<S> {
+    service: Rc<S>,
+}
+
+/// Returns the inbound request ID if it is short and made of safe characters.
+fn inbound_request_id(req: &ServiceRequest) -> Option<String> {
+    let id = req.headers().get(REQUEST_ID_HEADER)?.to_str().ok()?;
+    let is_safe = !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'));
+    is_safe.then(|| id.to_string())
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    /// Resolves the request ID, runs the request inside its logging scope and writes the access log.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let started = Instant::now();
+
+        let request_id = inbound_request_id(&req).unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let peer = req
+            .connection_info()
+            .realip_remote_addr()
+            .unwrap_or("-")
+            .to_string();
+
+        Box::pin(REQUEST_ID.scope(request_id.clone(), async move {
+            let header_value = HeaderValue::from_str(&request_id)
+                .expect("request IDs only contain header-safe characters");
+
+            let (status, user_id, result) = match service.call(req).await {
+                Ok(mut res) => {
+                    let user_id = res.request().authenticated_user_id();
+                    res.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+                    (res.status(), user_id, Ok(res))
+                }
+                Err(e) => {
+                    // Errors raised by inner middleware never became a response here, so render
+                    // it now to be able to attach the request ID header.
+                    let mut res = e.error_response();
+                    res.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+                    let status = res.status();
+                    (
+                        status,
+                        None,
+                        Err(InternalError::from_response(e, res).into()),
+                    )
+                }
+            };
+
+            let duration_ms = (started.elapsed().as_secs_f64() * 1_000_000.0).round() / 1000.0;
+            let user_id = user_id.map(|id| id.to_string());
+            log::info!(
+                target: "access",
+                method = method.as_str(),
+                path = path.as_str(),
+                status = status.as_u16(),
+                duration_ms = duration_ms,
+                user_id = user_id.as_deref().unwrap_or("-"),
+                peer = peer.as_str();
+                "request completed"
+            );
+
+            result
+        }))
+    }
+}
+
+/// Middleware struct recording Prometheus request counts and latencies.
+///
+/// Requests are labelled by method, matched route pattern (e.g. `/api/protected/vehicles/{vehicle_id}`)
+/// and response status code.
+pub struct MetricsMiddleware;
+
+impl<S, B> Transform<S, ServiceRequest> for MetricsMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = MetricsMiddlewareService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    /// Creates the transformed `MetricsMiddlewareService` to handle requests.
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MetricsMiddlewareService {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+/// Service struct timing each request and recording it once the response is produced.
+pub struct MetricsMiddlewareService<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for MetricsMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    /// Times the request and records it under its matched route pattern.
+    ///
+    /// Requests that match no route are grouped under `unmatched` to keep label cardinality bounded.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let started = Instant::now();
+        let method = req.method().to_string();
+        // The pattern is resolved from the app's resource map, so it is known before routing
+        let route = req
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".to_string());
+
+        Box::pin(async move {
+            let result = service.call(req).await;
+
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(e) => e.as_response_error().status_code(),
+            };
+            metrics::observe_request(
+                &method,
+                &route,
+                status.as_u16(),
+                started.elapsed().as_secs_f64(),
+            );
+
+            result
+        })
+    }
+}
+
 /// Trait providing an extension for `ServiceRequest` and `HttpRequest` to access the authenticated user ID.
 ///
 /// This trait allows for easy retrieval of the authenticated `user_id` (if present) from request
@@ -150,16 +376,48 @@ where
 pub trait AuthenticatedRequest {
     /// Returns the authenticated `user_id`, if available.
     fn authenticated_user_id(&self) -> Option<Uuid>;
+
+    /// Returns the organization the session is working with, if one is active.
+    fn active_organization_id(&self) -> Option<Uuid>;
+
+    /// Returns the authenticated user's preferences, or the defaults on unauthenticated requests.
+    fn user_preferences(&self) -> UserPreferences;
 }
 
 impl AuthenticatedRequest for ServiceRequest {
     fn authenticated_user_id(&self) -> Option<Uuid> {
         self.extensions().get::<Uuid>().copied()
     }
+
+    fn active_organization_id(&self) -> Option<Uuid> {
+        self.extensions()
+            .get::<ActiveOrganization>()
+            .map(|organization| organization.0)
+    }
+
+    fn user_preferences(&self) -> UserPreferences {
+        self.extensions()
+            .get::<UserPreferences>()
+            .cloned()
+            .unwrap_or_else(|| UserPreferences::defaults(Uuid::nil()))
+    }
 }
 
 impl AuthenticatedRequest for HttpRequest {
     fn authenticated_user_id(&self) -> Option<Uuid> {
         self.extensions().get::<Uuid>().copied()
     }
+
+    fn active_organization_id(&self) -> Option<Uuid> {
+        self.extensions()
+            .get::<ActiveOrganization>()
+            .map(|organization| organization.0)
+    }
+
+    fn user_preferences(&self) -> UserPreferences {
+        self.extensions()
+            .get::<UserPreferences>()
+            .cloned()
+            .unwrap_or_else(|| UserPreferences::defaults(Uuid::nil()))
+    }
 }
@@ -1,10 +1,128 @@
 use crate::schema::*;
-use chrono::NaiveDateTime;
-use diesel::sql_types::Float4;
-use diesel::{prelude::Identifiable, Insertable, Queryable, QueryableByName, Selectable};
+use crate::units::Units;
+use chrono::{DateTime, Utc};
+use diesel::sql_types::{BigInt, Numeric};
+use diesel::{
+    prelude::Identifiable, AsChangeset, Insertable, Queryable, QueryableByName, Selectable,
+};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// Deserializers for timestamps in requests and backups.
+///
+/// Timestamps are RFC 3339 strings with an offset, e.g. `2025-03-01T23:30:00+07:00`, and are
+/// stored in UTC. Requests must carry the offset: a timestamp without one is ambiguous, so it is
+/// rejected (`400 Bad Request`) rather than guessed. Backups written by earlier versions hold
+/// timestamps without an offset, which were always in UTC, so the `backup_` variants still
+/// accept those.
+pub mod timestamp {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer};
+
+    fn invalid(value: &str) -> String {
+        format!(
+            "invalid timestamp {:?}, expected RFC 3339 with an offset such as \
+             2025-03-01T23:30:00+07:00",
+            value
+        )
+    }
+
+    fn parse(value: &str) -> Result<DateTime<Utc>, String> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+            .map_err(|_| invalid(value))
+    }
+
+    fn parse_backup(value: &str) -> Result<DateTime<Utc>, String> {
+        parse(value).or_else(|_| {
+            value
+                .parse::<NaiveDateTime>()
+                .map(|timestamp| timestamp.and_utc())
+                .map_err(|_| invalid(value))
+        })
+    }
+
+    pub fn required<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        parse(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub fn optional<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| parse(&value))
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+
+    pub fn backup_required<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        parse_backup(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+
+    pub fn backup_optional<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| parse_backup(&value))
+            .transpose()
+            .map_err(D::Error::custom)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parses_offsets_and_only_takes_naive_backup_timestamps_as_utc() {
+            let cases: &[(&str, Option<&str>, Option<&str>)] = &[
+                (
+                    "2025-03-01T23:30:00+07:00",
+                    Some("2025-03-01T16:30:00Z"),
+                    Some("2025-03-01T16:30:00Z"),
+                ),
+                (
+                    "2025-03-01T16:30:00Z",
+                    Some("2025-03-01T16:30:00Z"),
+                    Some("2025-03-01T16:30:00Z"),
+                ),
+                ("2025-03-01T16:30:00", None, Some("2025-03-01T16:30:00Z")),
+                ("2025-03-01", None, None),
+                ("yesterday", None, None),
+            ];
+            for (value, request, backup) in cases {
+                let expected =
+                    |at: &Option<&str>| at.map(|at| at.parse::<DateTime<Utc>>().unwrap());
+                assert_eq!(parse(value).ok(), expected(request), "request {:?}", value);
+                assert_eq!(
+                    parse_backup(value).ok(),
+                    expected(backup),
+                    "backup {:?}",
+                    value
+                );
+            }
+        }
+    }
+}
+
+/// Deserializer for request fields that can be left out, cleared or set.
+///
+/// Used with `#[serde(default)]`: a missing field is `None`, `null` is `Some(None)` and a value
+/// is `Some(Some(value))`.
+pub mod nullable {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Some)
+    }
+}
+
 /// Represents a user in the system with essential identification and profile information.
 ///
 /// This struct maps to the `users` table and contains fields for user-specific data, including:
@@ -13,6 +131,9 @@ use uuid::Uuid;
 /// - `hashed_password`: The user's hashed password for authentication.
 /// - `full_name`: The user's full name for display purposes.
 /// - `created_at` and `updated_at`: Optional timestamps for record tracking.
+/// - `is_admin`: Whether the user may administer other users.
+/// - `is_active`: Whether the user is allowed to log in.
+/// - `email`: Optional address for email notifications.
 #[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize)]
 #[diesel(table_name = users)]
 pub struct User {
@@ -26,9 +147,15 @@ pub struct User {
     /// Full name of the user, used for display purposes.
     pub full_name: String,
     /// Timestamp for when the user record was created.
-    pub created_at: Option<NaiveDateTime>,
+    pub created_at: Option<DateTime<Utc>>,
     /// Timestamp for the last update made to the user record.
-    pub updated_at: Option<NaiveDateTime>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Whether the user has administrative privileges.
+    pub is_admin: bool,
+    /// Whether the user account is enabled; inactive users cannot log in.
+    pub is_active: bool,
+    /// Address for email notifications, if the user has one.
+    pub email: Option<String>,
 }
 
 /// Represents a new user to be inserted into the database.
@@ -47,9 +174,64 @@ pub struct NewUser {
     /// Full name of the new user.
     pub full_name: String,
     /// Creation timestamp for the new user record, optional as it's managed by the database.
-    pub created_at: Option<NaiveDateTime>,
+    pub created_at: Option<DateTime<Utc>>,
     /// Update timestamp for the new user record, optional as it's managed by the database.
-    pub updated_at: Option<NaiveDateTime>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Whether the new user has administrative privileges.
+    pub is_admin: bool,
+    /// Optional address for email notifications.
+    pub email: Option<String>,
+}
+
+/// Represents an entry of the audit log recording a data-changing action.
+///
+/// This struct maps to the `audit_log` table and contains:
+/// - `user_id`: The actor who performed the action, if known.
+/// - `action`: What was done (`create`, `update`, `delete`, `login`, `logout`, ...).
+/// - `entity_type` and `entity_id`: The record that was changed.
+/// - `before` and `after`: JSON snapshots of the record around the change.
+/// - `request_id`: ID of the HTTP request that caused the change.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLog {
+    /// Unique identifier for the audit entry.
+    pub id: Uuid,
+    /// ID of the user who performed the action.
+    pub user_id: Option<Uuid>,
+    /// Action performed on the entity.
+    pub action: String,
+    /// Type of the changed entity.
+    pub entity_type: String,
+    /// ID of the changed entity.
+    pub entity_id: Option<Uuid>,
+    /// Snapshot of the entity before the change.
+    pub before: Option<serde_json::Value>,
+    /// Snapshot of the entity after the change.
+    pub after: Option<serde_json::Value>,
+    /// ID of the request that caused the change.
+    pub request_id: Option<String>,
+    /// Timestamp of the change.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Represents a new audit log entry to be inserted into the database.
+#[derive(Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLog {
+    /// ID of the user who performed the action.
+    pub user_id: Option<Uuid>,
+    /// Action performed on the entity.
+    pub action: String,
+    /// Type of the changed entity.
+    pub entity_type: String,
+    /// ID of the changed entity.
+    pub entity_id: Option<Uuid>,
+    /// Snapshot of the entity before the change.
+    pub before: Option<serde_json::Value>,
+    /// Snapshot of the entity after the change.
+    pub after: Option<serde_json::Value>,
+    /// ID of the request that caused the change.
+    pub request_id: Option<String>,
 }
 
 /// Represents a session for user authentication and authorization.
@@ -72,9 +254,11 @@ pub struct Session {
     /// Secure token associated with the session for identification.
     pub session_token: String,
     /// Expiration timestamp indicating when the session becomes invalid.
-    pub expires_at: NaiveDateTime,
+    pub expires_at: DateTime<Utc>,
     /// Timestamp for when the session record was created.
-    pub created_at: Option<NaiveDateTime>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Organization whose vehicles the session is currently working with.
+    pub active_organization_id: Option<Uuid>,
 }
 
 /// Represents a new session to be inserted into the database.
@@ -91,9 +275,76 @@ pub struct NewSession {
     /// Unique session token generated at session initialization.
     pub session_token: String,
     /// Expiration timestamp for session validity.
-    pub expires_at: NaiveDateTime,
+    pub expires_at: DateTime<Utc>,
     /// Timestamp for when the session is created, useful for tracking.
-    pub created_at: Option<NaiveDateTime>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Organization the session starts out working with.
+    pub active_organization_id: Option<Uuid>,
+}
+
+/// A user's choice of units and formats.
+///
+/// This struct maps to the `user_preferences` table. Users without a row use the defaults of
+/// `UserPreferences::defaults`:
+/// - `distance_unit`: `km` or `mi` (see `OdometerUnit`).
+/// - `volume_unit`: `l`, `us_gal` or `imp_gal` (see `VolumeUnit`).
+/// - `currency`: ISO 4217 code shown next to costs; costs are not converted.
+/// - `date_format`: `iso`, `dmy` or `mdy` (see `DateFormat`).
+/// - `timezone`: IANA name of the timezone timestamps are shown and days are evaluated in.
+#[derive(
+    Selectable,
+    Queryable,
+    Identifiable,
+    Insertable,
+    AsChangeset,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+)]
+#[diesel(table_name = user_preferences, primary_key(user_id))]
+pub struct UserPreferences {
+    pub user_id: Uuid,
+    pub distance_unit: String,
+    pub volume_unit: String,
+    pub currency: String,
+    pub date_format: String,
+    pub timezone: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl UserPreferences {
+    /// Returns the preferences of a user who has not chosen any.
+    pub fn defaults(user_id: Uuid) -> Self {
+        UserPreferences {
+            user_id,
+            distance_unit: OdometerUnit::Kilometers.as_str().to_string(),
+            volume_unit: VolumeUnit::Liters.as_str().to_string(),
+            currency: "USD".to_string(),
+            date_format: DateFormat::Iso.as_str().to_string(),
+            timezone: "UTC".to_string(),
+            updated_at: None,
+        }
+    }
+
+    /// Returns the units values are entered and shown in.
+    pub fn units(&self) -> Units {
+        Units {
+            distance: OdometerUnit::parse(&self.distance_unit).unwrap_or(OdometerUnit::Kilometers),
+            volume: VolumeUnit::parse(&self.volume_unit).unwrap_or(VolumeUnit::Liters),
+            odometer: OdometerUnit::parse(&self.distance_unit).unwrap_or(OdometerUnit::Kilometers),
+        }
+    }
+
+    /// Returns the date format.
+    pub fn date_format(&self) -> DateFormat {
+        DateFormat::parse(&self.date_format).unwrap_or(DateFormat::Iso)
+    }
+
+    /// Returns the timezone days and local times are evaluated in.
+    pub fn timezone(&self) -> chrono_tz::Tz {
+        self.timezone.parse().unwrap_or(chrono_tz::Tz::UTC)
+    }
 }
 
 #[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug)]
@@ -106,8 +357,39 @@ pub struct Vehicle {
     pub registration: String,
     pub registration_expiry_date: chrono::NaiveDate,
     pub user_id: Uuid,
-    pub created_at: Option<NaiveDateTime>,
-    pub updated_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// When the vehicle was archived; archived vehicles keep their history but are hidden by default.
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Organization (fleet) the vehicle belongs to.
+    pub organization_id: Uuid,
+    /// Vehicle identification number.
+    #[serde(default)]
+    pub vin: Option<String>,
+    /// Model year.
+    #[serde(default)]
+    pub model_year: Option<i32>,
+    /// Colour of the body.
+    #[serde(default)]
+    pub colour: Option<String>,
+    /// What the vehicle runs on (see `FuelType`).
+    #[serde(default)]
+    pub fuel_type: Option<String>,
+    /// Capacity of the tank in liters, or of the battery in kWh for electric vehicles.
+    #[serde(default)]
+    pub tank_capacity: Option<Decimal>,
+    /// Unit the odometer counts in, and readings are entered and shown in (see `OdometerUnit`).
+    #[serde(default = "OdometerUnit::default_name")]
+    pub odometer_unit: String,
+    /// When the vehicle was bought.
+    #[serde(default)]
+    pub purchase_date: Option<chrono::NaiveDate>,
+    /// What the vehicle cost.
+    #[serde(default)]
+    pub purchase_price: Option<Decimal>,
 }
 
 #[derive(Insertable)]
@@ -119,6 +401,788 @@ pub struct NewVehicle {
     pub registration: String,
     pub registration_expiry_date: chrono::NaiveDate,
     pub user_id: Uuid,
+    pub organization_id: Uuid,
+    pub vin: Option<String>,
+    pub model_year: Option<i32>,
+    pub colour: Option<String>,
+    pub fuel_type: Option<String>,
+    pub tank_capacity: Option<Decimal>,
+    pub odometer_unit: String,
+    pub purchase_date: Option<chrono::NaiveDate>,
+    pub purchase_price: Option<Decimal>,
+}
+
+/// The changes to a vehicle's details; fields left as `None` are kept, and optional details set
+/// to `Some(None)` are cleared.
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = vehicles)]
+pub struct VehicleChanges {
+    pub brand: Option<String>,
+    pub model: Option<String>,
+    pub registration: Option<String>,
+    pub registration_expiry_date: Option<chrono::NaiveDate>,
+    pub vin: Option<Option<String>>,
+    pub model_year: Option<Option<i32>>,
+    pub colour: Option<Option<String>>,
+    pub fuel_type: Option<Option<String>>,
+    pub tank_capacity: Option<Option<Decimal>>,
+    pub odometer_unit: Option<String>,
+    pub purchase_date: Option<Option<chrono::NaiveDate>>,
+    pub purchase_price: Option<Option<Decimal>>,
+}
+
+/// What a vehicle runs on.
+///
+/// - `Petrol`, `Diesel` and `Lpg`: Combustion engines, refueled in liters.
+/// - `Ev`: Battery electric, charged in kWh.
+/// - `Hybrid`: Hybrid electric, refueled in liters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuelType {
+    Petrol,
+    Diesel,
+    Ev,
+    Hybrid,
+    Lpg,
+}
+
+impl FuelType {
+    /// Returns the name stored in `vehicles.fuel_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FuelType::Petrol => "petrol",
+            FuelType::Diesel => "diesel",
+            FuelType::Ev => "ev",
+            FuelType::Hybrid => "hybrid",
+            FuelType::Lpg => "lpg",
+        }
+    }
+
+    /// Parses a fuel type name as stored in `vehicles.fuel_type`.
+    pub fn parse(fuel_type: &str) -> Option<Self> {
+        match fuel_type {
+            "petrol" => Some(FuelType::Petrol),
+            "diesel" => Some(FuelType::Diesel),
+            "ev" => Some(FuelType::Ev),
+            "hybrid" => Some(FuelType::Hybrid),
+            "lpg" => Some(FuelType::Lpg),
+            _ => None,
+        }
+    }
+}
+
+/// Number of kilometers in a mile.
+pub const KILOMETERS_PER_MILE: f64 = 1.609_344;
+
+/// Unit of a distance: of a vehicle's odometer, or the one a user enters and reads distances in.
+///
+/// Readings are stored in kilometers whatever the unit of the odometer they were read from.
+///
+/// - `Kilometers`: Kilometers (the default).
+/// - `Miles`: Miles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OdometerUnit {
+    Kilometers,
+    Miles,
+}
+
+impl OdometerUnit {
+    /// Returns the name stored in `vehicles.odometer_unit` or `user_preferences.distance_unit`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OdometerUnit::Kilometers => "km",
+            OdometerUnit::Miles => "mi",
+        }
+    }
+
+    /// Parses a unit name as stored in `vehicles.odometer_unit` or
+    /// `user_preferences.distance_unit`.
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit {
+            "km" => Some(OdometerUnit::Kilometers),
+            "mi" => Some(OdometerUnit::Miles),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the default unit, for vehicles backed up before units were recorded.
+    pub fn default_name() -> String {
+        OdometerUnit::Kilometers.as_str().to_string()
+    }
+
+    /// Returns the number of kilometers in one unit.
+    pub fn kilometers(self) -> f64 {
+        match self {
+            OdometerUnit::Kilometers => 1.0,
+            OdometerUnit::Miles => KILOMETERS_PER_MILE,
+        }
+    }
+}
+
+/// Number of liters in a US gallon.
+pub const LITERS_PER_US_GALLON: f64 = 3.785_411_784;
+
+/// Number of liters in an imperial gallon.
+pub const LITERS_PER_IMPERIAL_GALLON: f64 = 4.546_09;
+
+/// Unit fuel quantities are entered and shown in.
+///
+/// - `Liters`: Liters (the default, and the unit quantities are stored in).
+/// - `UsGallons`: US gallons.
+/// - `ImperialGallons`: Imperial gallons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeUnit {
+    Liters,
+    UsGallons,
+    ImperialGallons,
+}
+
+impl VolumeUnit {
+    /// Returns the name stored in `user_preferences.volume_unit`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VolumeUnit::Liters => "l",
+            VolumeUnit::UsGallons => "us_gal",
+            VolumeUnit::ImperialGallons => "imp_gal",
+        }
+    }
+
+    /// Parses a unit name as stored in `user_preferences.volume_unit`.
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit {
+            "l" => Some(VolumeUnit::Liters),
+            "us_gal" => Some(VolumeUnit::UsGallons),
+            "imp_gal" => Some(VolumeUnit::ImperialGallons),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of liters in one unit.
+    pub fn liters(self) -> f64 {
+        match self {
+            VolumeUnit::Liters => 1.0,
+            VolumeUnit::UsGallons => LITERS_PER_US_GALLON,
+            VolumeUnit::ImperialGallons => LITERS_PER_IMPERIAL_GALLON,
+        }
+    }
+
+    /// Returns the abbreviation shown next to quantities.
+    pub fn label(self) -> &'static str {
+        match self {
+            VolumeUnit::Liters => "L",
+            VolumeUnit::UsGallons => "US gal",
+            VolumeUnit::ImperialGallons => "imp gal",
+        }
+    }
+}
+
+/// How dates are shown on rendered pages.
+///
+/// - `Iso`: `2025-01-31` (the default).
+/// - `Dmy`: `31/01/2025`.
+/// - `Mdy`: `01/31/2025`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    Iso,
+    Dmy,
+    Mdy,
+}
+
+impl DateFormat {
+    /// Returns the name stored in `user_preferences.date_format`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateFormat::Iso => "iso",
+            DateFormat::Dmy => "dmy",
+            DateFormat::Mdy => "mdy",
+        }
+    }
+
+    /// Parses a format name as stored in `user_preferences.date_format`.
+    pub fn parse(format: &str) -> Option<Self> {
+        match format {
+            "iso" => Some(DateFormat::Iso),
+            "dmy" => Some(DateFormat::Dmy),
+            "mdy" => Some(DateFormat::Mdy),
+            _ => None,
+        }
+    }
+
+    /// Returns the `strftime` pattern of the format.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            DateFormat::Iso => "%Y-%m-%d",
+            DateFormat::Dmy => "%d/%m/%Y",
+            DateFormat::Mdy => "%m/%d/%Y",
+        }
+    }
+}
+
+/// Represents an organization (fleet) that owns vehicles and has members.
+///
+/// This struct maps to the `organizations` table:
+/// - `id`: Unique identifier for the organization.
+/// - `name`: Display name of the organization.
+/// - `created_at` and `updated_at`: Optional timestamps for record tracking.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = organizations)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = organizations)]
+pub struct NewOrganization {
+    pub id: Uuid,
+    pub name: String,
+}
+
+/// Represents a user's membership of an organization.
+///
+/// This struct maps to the `organization_members` table:
+/// - `organization_id` and `user_id`: The organization and its member.
+/// - `role`: Either `admin` or `member` (see `OrganizationRole`).
+/// - `created_at`: When the membership (or invitation) was created.
+/// - `invited_by`: The user who sent the invitation, if any.
+/// - `accepted_at`: When the invitation was accepted; `None` while it is pending.
+#[derive(Selectable, Queryable, Insertable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Organization))]
+#[diesel(table_name = organization_members)]
+pub struct OrganizationMember {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+    pub invited_by: Option<Uuid>,
+    pub accepted_at: Option<DateTime<Utc>>,
+}
+
+/// Role of a user within an organization.
+///
+/// - `Member`: Sees the organization's vehicles that are shared with them.
+/// - `Admin`: Has owner access to every vehicle of the organization and manages its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrganizationRole {
+    Member,
+    Admin,
+}
+
+impl OrganizationRole {
+    /// Returns the name stored in `organization_members.role`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrganizationRole::Member => "member",
+            OrganizationRole::Admin => "admin",
+        }
+    }
+
+    /// Parses a role name as stored in `organization_members.role`.
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "member" => Some(OrganizationRole::Member),
+            "admin" => Some(OrganizationRole::Admin),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a user's access to a vehicle.
+///
+/// This struct maps to the `vehicle_members` table:
+/// - `vehicle_id` and `user_id`: The vehicle and the user it is shared with.
+/// - `role`: One of `owner`, `editor` or `viewer` (see `VehicleRole`).
+/// - `invited_by`: The user who sent the invitation, if any.
+/// - `accepted_at`: When the invitation was accepted; `None` while it is pending.
+/// - `created_at`: When the membership (or invitation) was created.
+#[derive(Selectable, Queryable, Insertable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Vehicle))]
+#[diesel(table_name = vehicle_members)]
+pub struct VehicleMember {
+    pub vehicle_id: Uuid,
+    pub user_id: Uuid,
+    pub role: String,
+    pub invited_by: Option<Uuid>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Level of access a member has to a vehicle, ordered from least to most privileged.
+///
+/// - `Viewer`: May read the vehicle and its history.
+/// - `Editor`: May also edit the vehicle and record odometer readings and refuels.
+/// - `Owner`: May also archive the vehicle and manage its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VehicleRole {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+impl VehicleRole {
+    /// Returns the name stored in `vehicle_members.role`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VehicleRole::Viewer => "viewer",
+            VehicleRole::Editor => "editor",
+            VehicleRole::Owner => "owner",
+        }
+    }
+
+    /// Parses a role name as stored in `vehicle_members.role`.
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "viewer" => Some(VehicleRole::Viewer),
+            "editor" => Some(VehicleRole::Editor),
+            "owner" => Some(VehicleRole::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a notification sent to a user, shown in their inbox.
+///
+/// This struct maps to the `notifications` table:
+/// - `id`: Unique identifier for the notification.
+/// - `user_id`: The recipient.
+/// - `vehicle_id`: The vehicle the notification is about, if any.
+/// - `kind`: What the notification is about (see `NotificationKind`).
+/// - `title` and `body`: The message.
+/// - `dedup_key`: Identifies the event notified about, so it is only notified once per user.
+/// - `read_at`: When the recipient read the notification, if they did.
+/// - `created_at`: When the notification was created.
+/// - `dismissed_at`: When the recipient dismissed the notification from their inbox, if they did.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = notifications)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub vehicle_id: Option<Uuid>,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub dedup_key: String,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub dismissed_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a new notification to be inserted into the database.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = notifications)]
+pub struct NewNotification {
+    pub user_id: Uuid,
+    pub vehicle_id: Option<Uuid>,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub dedup_key: String,
+}
+
+/// What a notification is about.
+///
+/// - `RegistrationExpiry`: A vehicle's registration expires soon or has expired.
+/// - `FuelEconomyDrop`: A vehicle's latest fuel economy is well below its recent average.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    RegistrationExpiry,
+    FuelEconomyDrop,
+}
+
+impl NotificationKind {
+    /// Returns the name stored in `notifications.kind`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationKind::RegistrationExpiry => "registration_expiry",
+            NotificationKind::FuelEconomyDrop => "fuel_economy_drop",
+        }
+    }
+}
+
+/// Represents the delivery of a notification through one channel.
+///
+/// This struct maps to the `notification_deliveries` table:
+/// - `id`: Unique identifier for the delivery.
+/// - `notification_id`: The notification being delivered.
+/// - `channel`: The channel delivering it (`inbox`, `email`, `webhook`).
+/// - `status`: See `DeliveryStatus`.
+/// - `attempts`: How many times delivery was attempted.
+/// - `last_error`: Why the last attempt failed, if it did.
+/// - `delivered_at`: When the notification was delivered.
+/// - `created_at`: When the delivery was queued.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Debug, Clone)]
+#[diesel(belongs_to(Notification))]
+#[diesel(table_name = notification_deliveries)]
+pub struct NotificationDelivery {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    pub channel: String,
+    pub status: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A pending delivery with the notification to deliver and its recipient.
+pub struct PendingDelivery {
+    pub delivery: NotificationDelivery,
+    pub notification: Notification,
+    pub recipient: User,
+}
+
+/// State of a notification delivery.
+///
+/// - `Pending`: Not delivered yet; attempted again on the next run.
+/// - `Sent`: Delivered.
+/// - `Skipped`: Not delivered because the channel cannot reach the recipient.
+/// - `Failed`: Given up after too many failed attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    Pending,
+    Sent,
+    Skipped,
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// Returns the name stored in `notification_deliveries.status`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Sent => "sent",
+            DeliveryStatus::Skipped => "skipped",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+
+    /// Parses a status name as stored in `notification_deliveries.status` and
+    /// `webhook_deliveries.status`.
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(DeliveryStatus::Pending),
+            "sent" => Some(DeliveryStatus::Sent),
+            "skipped" => Some(DeliveryStatus::Skipped),
+            "failed" => Some(DeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a file attached to a vehicle, and optionally to one of its refuels.
+///
+/// This struct maps to the `documents` table:
+/// - `id`: Unique identifier for the document.
+/// - `vehicle_id`: The vehicle the document belongs to.
+/// - `refuel_id`: The refuel the document belongs to, e.g. for a fuel receipt.
+/// - `kind`: What the document is (see `DocumentKind`).
+/// - `filename`: The name of the uploaded file.
+/// - `content_type`: The media type detected from the file's contents.
+/// - `size_bytes`: The size of the file.
+/// - `storage_key` and `thumbnail_key`: Where the file and its thumbnail (for images) are stored,
+///   relative to the documents directory; never serialized.
+/// - `user_id`: The user who uploaded the document.
+/// - `created_at`: When the document was uploaded.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Debug, Clone)]
+#[diesel(belongs_to(Vehicle))]
+#[diesel(table_name = documents)]
+pub struct Document {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub refuel_id: Option<Uuid>,
+    pub kind: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    #[serde(skip_serializing)]
+    pub thumbnail_key: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a new document to be inserted into the database.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = documents)]
+pub struct NewDocument {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub refuel_id: Option<Uuid>,
+    pub kind: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub storage_key: String,
+    pub thumbnail_key: Option<String>,
+    pub user_id: Option<Uuid>,
+}
+
+/// What a document is.
+///
+/// - `Insurance`: An insurance policy or certificate.
+/// - `Registration`: A registration certificate.
+/// - `Receipt`: A receipt, e.g. of a refuel.
+/// - `Other`: Anything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Insurance,
+    Registration,
+    Receipt,
+    Other,
+}
+
+impl DocumentKind {
+    /// Returns the name stored in `documents.kind`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentKind::Insurance => "insurance",
+            DocumentKind::Registration => "registration",
+            DocumentKind::Receipt => "receipt",
+            DocumentKind::Other => "other",
+        }
+    }
+
+    /// Parses a kind name as stored in `documents.kind`.
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "insurance" => Some(DocumentKind::Insurance),
+            "registration" => Some(DocumentKind::Registration),
+            "receipt" => Some(DocumentKind::Receipt),
+            "other" => Some(DocumentKind::Other),
+            _ => None,
+        }
+    }
+}
+
+/// A stored file to send to the client: a document or its thumbnail.
+///
+/// - `path`: Where the file is stored.
+/// - `content_type`: The media type of the file.
+/// - `filename`: The name to offer the file under.
+pub struct DocumentFile {
+    pub path: std::path::PathBuf,
+    pub content_type: String,
+    pub filename: String,
+}
+
+/// Represents an insurance policy of a vehicle.
+///
+/// This struct maps to the `insurance_policies` table:
+/// - `provider` and `policy_number`: Who insures the vehicle, and under which policy.
+/// - `coverage_type`: What the policy covers (see `CoverageType`).
+/// - `premium`: What the policy costs for its whole term.
+/// - `start_date` and `end_date`: The first and last day covered.
+/// - `document_id`: The policy document, uploaded as a vehicle document.
+/// - `user_id`: The user who recorded the policy.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Vehicle))]
+#[diesel(table_name = insurance_policies)]
+pub struct InsurancePolicy {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub provider: String,
+    pub policy_number: String,
+    pub coverage_type: String,
+    pub premium: Decimal,
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub document_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a new insurance policy to be inserted into the database, or the updated details
+/// of an existing one.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = insurance_policies)]
+pub struct NewInsurancePolicy {
+    pub vehicle_id: Uuid,
+    pub provider: String,
+    pub policy_number: String,
+    pub coverage_type: String,
+    pub premium: Decimal,
+    pub start_date: chrono::NaiveDate,
+    pub end_date: chrono::NaiveDate,
+    pub document_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+}
+
+/// What an insurance policy covers.
+///
+/// - `Comprehensive`: Damage to the vehicle itself, whatever the cause.
+/// - `ThirdParty`: Liability towards others only.
+/// - `TotalLossOnly`: Theft or damage beyond repair only.
+/// - `Other`: Anything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageType {
+    Comprehensive,
+    ThirdParty,
+    TotalLossOnly,
+    Other,
+}
+
+impl CoverageType {
+    /// Returns the name stored in `insurance_policies.coverage_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CoverageType::Comprehensive => "comprehensive",
+            CoverageType::ThirdParty => "third_party",
+            CoverageType::TotalLossOnly => "total_loss_only",
+            CoverageType::Other => "other",
+        }
+    }
+
+    /// Parses a coverage type name as stored in `insurance_policies.coverage_type`.
+    pub fn parse(coverage_type: &str) -> Option<Self> {
+        match coverage_type {
+            "comprehensive" => Some(CoverageType::Comprehensive),
+            "third_party" => Some(CoverageType::ThirdParty),
+            "total_loss_only" => Some(CoverageType::TotalLossOnly),
+            "other" => Some(CoverageType::Other),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a user's outgoing webhook endpoint.
+///
+/// This struct maps to the `webhooks` table:
+/// - `id`: Unique identifier for the webhook.
+/// - `user_id`: The user who configured the webhook.
+/// - `organization_id`: The organization whose vehicle events it receives.
+/// - `url`: Where the events are posted.
+/// - `secret`: Key of the HMAC signature of each payload; never serialized.
+/// - `events`: The subscribed events (see `WebhookEvent`).
+/// - `is_active`: Whether events are delivered; inactive webhooks keep their queued deliveries.
+/// - `created_at` and `updated_at`: Optional timestamps for record tracking.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Debug, Clone)]
+#[diesel(belongs_to(User))]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub organization_id: Uuid,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub is_active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a new webhook to be inserted into the database.
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = webhooks)]
+pub struct NewWebhook {
+    pub user_id: Uuid,
+    pub organization_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+/// An event that can be delivered to webhooks.
+///
+/// - `VehicleCreated`, `VehicleUpdated`, `VehicleDeleted`: A vehicle was created, changed or
+///   deleted (archived, or purged without being archived first).
+/// - `OdometerCreated`: An odometer reading was recorded.
+/// - `RefuelCreated`: A refuel was recorded or imported.
+/// - `ChargingSessionCreated`: A charging session was recorded.
+/// - `Ping`: A test event, sent on request to a single webhook regardless of its subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    VehicleCreated,
+    VehicleUpdated,
+    VehicleDeleted,
+    OdometerCreated,
+    RefuelCreated,
+    ChargingSessionCreated,
+    Ping,
+}
+
+impl WebhookEvent {
+    /// The events webhooks can subscribe to.
+    pub const SUBSCRIBABLE: [WebhookEvent; 6] = [
+        WebhookEvent::VehicleCreated,
+        WebhookEvent::VehicleUpdated,
+        WebhookEvent::VehicleDeleted,
+        WebhookEvent::OdometerCreated,
+        WebhookEvent::RefuelCreated,
+        WebhookEvent::ChargingSessionCreated,
+    ];
+
+    /// Returns the event name, as stored in `webhooks.events` and sent to the webhook.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::VehicleCreated => "vehicle.created",
+            WebhookEvent::VehicleUpdated => "vehicle.updated",
+            WebhookEvent::VehicleDeleted => "vehicle.deleted",
+            WebhookEvent::OdometerCreated => "odometer.created",
+            WebhookEvent::RefuelCreated => "refuel.created",
+            WebhookEvent::ChargingSessionCreated => "charging_session.created",
+            WebhookEvent::Ping => "ping",
+        }
+    }
+
+    /// Parses the name of an event webhooks can subscribe to.
+    pub fn parse(event: &str) -> Option<Self> {
+        WebhookEvent::SUBSCRIBABLE
+            .into_iter()
+            .find(|subscribable| subscribable.as_str() == event)
+    }
+}
+
+/// Represents a queued or attempted delivery of an event to a webhook.
+///
+/// This struct maps to the `webhook_deliveries` table:
+/// - `id`: Unique identifier for the delivery, sent in the `X-Vimana-Delivery` header.
+/// - `webhook_id`: The receiving webhook.
+/// - `event`: The event name.
+/// - `payload`: The JSON document posted to the webhook.
+/// - `status`: `pending`, `sent` or `failed` (see `DeliveryStatus`).
+/// - `attempts`: How many times delivery was attempted.
+/// - `next_attempt_at`: When a pending delivery is attempted next.
+/// - `response_status`: The HTTP status of the last response, if one was received.
+/// - `last_error`: Why the last attempt failed, if it did.
+/// - `delivered_at`: When the webhook accepted the event.
+/// - `created_at`: When the event was queued.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Debug, Clone)]
+#[diesel(belongs_to(Webhook))]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A webhook delivery that is due, with the webhook to deliver it to.
+pub struct DueWebhookDelivery {
+    pub delivery: WebhookDelivery,
+    pub webhook: Webhook,
+}
+
+/// Outcome of a webhook delivery attempt.
+///
+/// - `response_status`: The HTTP status of the response, if one was received.
+/// - `error`: Why the attempt failed, or `None` if the webhook accepted the event.
+/// - `retry_at`: When to attempt a failed delivery again, or `None` to give up.
+pub struct WebhookAttempt {
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub retry_at: Option<DateTime<Utc>>,
 }
 
 /// Represents an odometer reading associated with a vehicle.
@@ -129,6 +1193,8 @@ pub struct NewVehicle {
 /// - `timestamp`: The time the odometer reading was recorded.
 /// - `odometer_value`: The recorded odometer value in kilometers or miles.
 /// - `created_at` and `updated_at`: Optional timestamps for record creation and updates.
+/// - `user_id`: The user who submitted the reading, if known.
+/// - `driver_id`: The user who was driving, if known.
 #[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug)]
 #[diesel(belongs_to(Vehicle))]
 #[diesel(table_name = odometer)]
@@ -138,13 +1204,20 @@ pub struct Odometer {
     /// Vehicle ID associated with this odometer reading.
     pub vehicle_id: Uuid,
     /// Timestamp of the odometer reading.
-    pub timestamp: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub timestamp: Option<DateTime<Utc>>,
     /// The odometer value recorded.
-    pub odometer_value: f32,
+    pub odometer_value: Decimal,
     /// Timestamp for when the odometer record was created.
-    pub created_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub created_at: Option<DateTime<Utc>>,
     /// Timestamp for the last update made to the odometer record.
-    pub updated_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// User who submitted the odometer reading.
+    pub user_id: Option<Uuid>,
+    /// User who was driving when the odometer reading was taken.
+    pub driver_id: Option<Uuid>,
 }
 
 /// Represents a new odometer entry to be inserted into the database.
@@ -157,7 +1230,11 @@ pub struct NewOdometer {
     /// Vehicle ID associated with this new odometer reading.
     pub vehicle_id: Uuid,
     /// The odometer value recorded.
-    pub odometer_value: f32,
+    pub odometer_value: Decimal,
+    /// User who submitted the odometer reading.
+    pub user_id: Option<Uuid>,
+    /// User who was driving when the odometer reading was taken.
+    pub driver_id: Option<Uuid>,
 }
 
 /// Represents a refueling event associated with an odometer reading.
@@ -168,6 +1245,7 @@ pub struct NewOdometer {
 /// - `odometer_id`: The associated odometer reading for this refueling.
 /// - `timestamp`: The time the refueling occurred.
 /// - `refuel_quantity`: The amount of fuel added in liters or gallons.
+/// - `cost`: What the refueling cost, if known.
 /// - `created_at` and `updated_at`: Optional timestamps for record creation and updates.
 #[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug)]
 #[diesel(belongs_to(Odometer))]
@@ -180,13 +1258,22 @@ pub struct Refuel {
     /// Odometer ID linked to this refueling.
     pub odometer_id: Uuid,
     /// Timestamp of the refueling event.
-    pub timestamp: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub timestamp: Option<DateTime<Utc>>,
     /// Quantity of fuel added.
-    pub refuel_quantity: f32,
+    pub refuel_quantity: Decimal,
     /// Timestamp for when the refueling record was created.
-    pub created_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub created_at: Option<DateTime<Utc>>,
     /// Timestamp for the last update made to the refueling record.
-    pub updated_at: Option<NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// User who submitted the refueling.
+    pub user_id: Option<Uuid>,
+    /// User who was driving when the vehicle was refueled.
+    pub driver_id: Option<Uuid>,
+    /// What the refueling cost, if known.
+    pub cost: Option<Decimal>,
 }
 
 /// Represents a new refueling entry to be inserted into the database.
@@ -201,7 +1288,13 @@ pub struct NewRefuel {
     /// Odometer ID linked to this refueling.
     pub odometer_id: Uuid,
     /// Quantity of fuel added.
-    pub refuel_quantity: f32,
+    pub refuel_quantity: Decimal,
+    /// User who submitted the refueling.
+    pub user_id: Option<Uuid>,
+    /// User who was driving when the vehicle was refueled.
+    pub driver_id: Option<Uuid>,
+    /// What the refueling cost, if known.
+    pub cost: Option<Decimal>,
 }
 
 /// Represents a detailed refueling event, including associated odometer information.
@@ -213,17 +1306,274 @@ pub struct RefuelWithOdometer {
     pub id: Uuid,
     pub vehicle_id: Uuid,
     pub odometer_id: Uuid,
-    pub refuel_quantity: f32,
-    pub odometer_value: f32,
-    pub timestamp: Option<NaiveDateTime>,
+    pub refuel_quantity: Decimal,
+    pub odometer_value: Decimal,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub user_id: Option<Uuid>,
+    pub driver_id: Option<Uuid>,
+    pub cost: Option<Decimal>,
+}
+
+/// Represents a charging session of an electric or plug-in hybrid vehicle.
+///
+/// This struct maps to the `charging_sessions` table. Like a refuel, the reading taken when the
+/// session started is stored as an `odometer` row, linked through `odometer_id`:
+/// - `started_at`: When the session started.
+/// - `energy_kwh`: The energy delivered, in kWh.
+/// - `charger_type`: `ac` or `dc` (see `ChargerType`).
+/// - `start_soc` and `end_soc`: The battery's state of charge, in percent, before and after.
+/// - `duration_minutes`: How long the vehicle was charging.
+/// - `cost` and `location`: What the session cost and where it took place, if known.
+/// - `user_id`: The user who recorded the session; `driver_id`: the user who drove, if known.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Odometer))]
+#[diesel(table_name = charging_sessions)]
+pub struct ChargingSession {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub odometer_id: Uuid,
+    #[serde(deserialize_with = "timestamp::backup_required")]
+    pub started_at: DateTime<Utc>,
+    pub energy_kwh: f32,
+    pub charger_type: String,
+    pub start_soc: Option<f32>,
+    pub end_soc: Option<f32>,
+    pub duration_minutes: Option<i32>,
+    pub cost: Option<Decimal>,
+    pub location: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub driver_id: Option<Uuid>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// The details of a charging session as entered, without the odometer row it is stored with.
+#[derive(Debug, Clone)]
+pub struct ChargingSessionDetails {
+    pub odometer_value: Decimal,
+    pub started_at: Option<DateTime<Utc>>,
+    pub energy_kwh: f32,
+    pub charger_type: String,
+    pub start_soc: Option<f32>,
+    pub end_soc: Option<f32>,
+    pub duration_minutes: Option<i32>,
+    pub cost: Option<Decimal>,
+    pub location: Option<String>,
+    pub driver_id: Option<Uuid>,
+}
+
+/// The kind of charger a charging session used.
+///
+/// - `Ac`: Alternating current, e.g. a wall box or a public post.
+/// - `Dc`: Direct current fast charging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargerType {
+    Ac,
+    Dc,
+}
+
+impl ChargerType {
+    /// Returns the name stored in `charging_sessions.charger_type`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChargerType::Ac => "ac",
+            ChargerType::Dc => "dc",
+        }
+    }
+
+    /// Parses a charger type name as stored in `charging_sessions.charger_type`.
+    pub fn parse(charger_type: &str) -> Option<Self> {
+        match charger_type {
+            "ac" => Some(ChargerType::Ac),
+            "dc" => Some(ChargerType::Dc),
+            _ => None,
+        }
+    }
+}
+
+/// Represents a period during which a user is the assigned driver of a vehicle.
+///
+/// This struct maps to the `driver_assignments` table:
+/// - `id`: Unique identifier for the assignment.
+/// - `vehicle_id` and `user_id`: The vehicle and its driver.
+/// - `assigned_by`: The user who made the assignment, if known.
+/// - `starts_at` and `ends_at`: The assignment period; `ends_at` is `None` while it is open-ended.
+/// - `created_at`: When the assignment was recorded.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Vehicle))]
+#[diesel(table_name = driver_assignments)]
+pub struct DriverAssignment {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Represents a new driver assignment to be inserted into the database.
+#[derive(Insertable)]
+#[diesel(table_name = driver_assignments)]
+pub struct NewDriverAssignment {
+    pub vehicle_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a logbook trip of a vehicle.
+///
+/// This struct maps to the `trips` table. The start and end readings are stored as `odometer`
+/// rows, linked through `start_odometer_id` and `end_odometer_id`:
+/// - `started_at` and `ended_at`: When the trip started and ended.
+/// - `origin`, `destination` and `purpose`: Free-text logbook details.
+/// - `category`: `business` or `private` (see `TripCategory`).
+/// - `user_id`: The user who recorded the trip; `driver_id`: the user who drove, if known.
+#[derive(Selectable, Queryable, Identifiable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(belongs_to(Vehicle))]
+#[diesel(table_name = trips)]
+pub struct Trip {
+    pub id: Uuid,
+    pub vehicle_id: Uuid,
+    pub start_odometer_id: Uuid,
+    pub end_odometer_id: Uuid,
+    #[serde(deserialize_with = "timestamp::backup_required")]
+    pub started_at: DateTime<Utc>,
+    #[serde(deserialize_with = "timestamp::backup_required")]
+    pub ended_at: DateTime<Utc>,
+    pub origin: String,
+    pub destination: String,
+    pub purpose: String,
+    pub category: String,
+    pub user_id: Option<Uuid>,
+    pub driver_id: Option<Uuid>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "timestamp::backup_optional")]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// The details of a trip as entered in the logbook, without the odometer rows it is stored with.
+#[derive(Debug, Clone)]
+pub struct TripDetails {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub start_odometer_value: Decimal,
+    pub end_odometer_value: Decimal,
+    pub origin: String,
+    pub destination: String,
+    pub purpose: String,
+    pub category: String,
+    pub driver_id: Option<Uuid>,
+}
+
+/// The details of a refuel as entered, without the odometer row it is stored with.
+#[derive(Debug, Clone)]
+pub struct RefuelDetails {
+    pub refuel_quantity: Decimal,
+    pub odometer_value: Decimal,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub driver_id: Option<Uuid>,
+    pub cost: Option<Decimal>,
+}
+
+/// A backup of a user's vehicles and their history, as a versioned JSON document.
+///
+/// - `format`: Always `vimana-backup`.
+/// - `version`: Version of the document layout; version 2 holds vehicles with their odometer
+///   readings (in kilometers), refuels (in liters), trips, charging sessions and insurance
+///   policies, each list optional past the refuels. Version 1, which kept the readings of mile
+///   odometers in miles, is still restored.
+/// - `exported_at`: When the backup was made.
+/// - `user_id`: The user who made the backup.
+/// - `vehicles`: The vehicles and their history.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Backup {
+    pub format: String,
+    pub version: u32,
+    #[serde(deserialize_with = "timestamp::backup_required")]
+    pub exported_at: DateTime<Utc>,
+    pub user_id: Uuid,
+    pub vehicles: Vec<VehicleHistory>,
+}
+
+/// A vehicle with its whole history, as stored in a backup.
+///
+/// Odometer readings are listed once; refuels, trips and charging sessions refer to them by ID.
+/// Documents are not part of a backup, so insurance policies lose their policy document.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VehicleHistory {
+    pub vehicle: Vehicle,
+    pub odometer: Vec<Odometer>,
+    pub refuels: Vec<Refuel>,
+    #[serde(default)]
+    pub trips: Vec<Trip>,
+    #[serde(default)]
+    pub charging_sessions: Vec<ChargingSession>,
+    #[serde(default)]
+    pub insurance_policies: Vec<InsurancePolicy>,
+}
+
+/// The odometer readings of a vehicle around a time range.
+///
+/// - `before`: The last reading before the range.
+/// - `within`: The readings within the range, oldest first.
+/// - `after`: The first reading after the range.
+#[derive(Debug)]
+pub struct SurroundingOdometer {
+    pub before: Option<Odometer>,
+    pub within: Vec<Odometer>,
+    pub after: Option<Odometer>,
+}
+
+/// Whether a trip counts towards business or private mileage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TripCategory {
+    Business,
+    Private,
+}
+
+impl TripCategory {
+    /// Parses a category name as stored in `trips.category`.
+    pub fn parse(category: &str) -> Option<Self> {
+        match category {
+            "business" => Some(TripCategory::Business),
+            "private" => Some(TripCategory::Private),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, QueryableByName, Serialize)]
 pub struct TraveledDistance {
-    #[diesel(sql_type = Float4)]
-    start_value: f32,
-    #[diesel(sql_type = Float4)]
-    end_value: f32,
-    #[diesel(sql_type = Float4)]
-    traveled_distance: f32,
+    #[diesel(sql_type = Numeric)]
+    pub start_value: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub end_value: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub traveled_distance: Decimal,
+}
+
+/// Row counts used to refresh the domain gauges exposed on `/metrics`.
+#[derive(Debug, QueryableByName)]
+pub struct DomainCounts {
+    /// Number of sessions that have not expired.
+    #[diesel(sql_type = BigInt)]
+    pub active_sessions: i64,
+    /// Number of registered users.
+    #[diesel(sql_type = BigInt)]
+    pub users: i64,
+    /// Number of vehicles.
+    #[diesel(sql_type = BigInt)]
+    pub vehicles: i64,
+    /// Number of odometer readings.
+    #[diesel(sql_type = BigInt)]
+    pub odometer_readings: i64,
+    /// Number of refuels.
+    #[diesel(sql_type = BigInt)]
+    pub refuels: i64,
 }
@@ -0,0 +1,367 @@
+//! Background notification scheduler and delivery channels.
+//!
+//! `spawn_scheduler` runs inside the server and, every `NOTIFICATION_INTERVAL_SECS` seconds
+//! (default one hour), creates the registration expiry reminders and fuel economy alerts that are
+//! due and delivers the pending notifications through the configured channels:
+//! - `inbox`: Always enabled; the notification is shown in the recipient's in-app inbox.
+//! - `email`: Enabled by `SMTP_HOST` (with `SMTP_PORT`, default 25, and `SMTP_FROM`); sends a
+//!   plain-text mail to recipients that have an email address.
+//! - `webhook`: Enabled by `NOTIFICATION_WEBHOOK_URL`; posts the notification as JSON. The URL
+//!   must resolve to public addresses unless `WEBHOOK_ALLOW_PRIVATE=true`.
+//!
+//! Reminders are sent `REGISTRATION_REMINDER_DAYS` (default `30,7,1`) days before expiry and once
+//! after expiry. A refuel is alerted about when its fuel economy is `FUEL_ECONOMY_DROP_PERCENT`
+//! (default 25) percent or more below the vehicle's recent average. Failed deliveries are retried
+//! on the following runs, up to `MAX_DELIVERY_ATTEMPTS` attempts.
+use chrono::Utc;
+use futures::future::LocalBoxFuture;
+use lettre::{message::Mailbox, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde_json::json;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    models::{DeliveryStatus, Notification, PendingDelivery, User},
+    queries, services, webhooks, DbPool,
+};
+
+/// Number of failed attempts after which a delivery is given up.
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+
+/// Number of pending deliveries attempted per run.
+const DELIVERY_BATCH_SIZE: i64 = 100;
+
+/// How far back refuels are checked for a fuel economy drop, so that refuels recorded while the
+/// scheduler was not running are still checked.
+const FUEL_ECONOMY_LOOKBACK_DAYS: i64 = 7;
+
+/// How long a webhook request may take before it counts as failed.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A way of delivering notifications to their recipients.
+pub trait NotificationChannel {
+    /// The name of the channel, as stored in `notification_deliveries.channel`.
+    fn name(&self) -> &'static str;
+
+    /// Delivers a notification to its recipient.
+    ///
+    /// # Returns
+    /// - `Ok(DeliveryStatus::Sent)`: If the notification was delivered.
+    /// - `Ok(DeliveryStatus::Skipped)`: If the channel cannot reach the recipient.
+    /// - `Err(String)`: Why delivery failed; it is retried on the next run.
+    fn deliver<'a>(
+        &'a self,
+        notification: &'a Notification,
+        recipient: &'a User,
+    ) -> LocalBoxFuture<'a, Result<DeliveryStatus, String>>;
+}
+
+/// Delivers notifications to the in-app inbox, where they are stored already.
+pub struct InboxChannel;
+
+impl NotificationChannel for InboxChannel {
+    fn name(&self) -> &'static str {
+        "inbox"
+    }
+
+    fn deliver<'a>(
+        &'a self,
+        _notification: &'a Notification,
+        _recipient: &'a User,
+    ) -> LocalBoxFuture<'a, Result<DeliveryStatus, String>> {
+        Box::pin(async { Ok(DeliveryStatus::Sent) })
+    }
+}
+
+/// Delivers notifications as plain-text mails through an SMTP server.
+pub struct EmailChannel {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailChannel {
+    /// Creates a channel sending through an SMTP server without TLS or authentication, such as a
+    /// local mail relay or sink.
+    pub fn new(host: &str, port: u16, from: Mailbox) -> Self {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+            .port(port)
+            .build();
+        EmailChannel { transport, from }
+    }
+}
+
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn deliver<'a>(
+        &'a self,
+        notification: &'a Notification,
+        recipient: &'a User,
+    ) -> LocalBoxFuture<'a, Result<DeliveryStatus, String>> {
+        Box::pin(async move {
+            let Some(email) = recipient.email.as_deref() else {
+                return Ok(DeliveryStatus::Skipped);
+            };
+            let to = email
+                .parse::<Mailbox>()
+                .map_err(|e| format!("Invalid recipient address: {}", e))?;
+            let message = Message::builder()
+                .from(self.from.clone())
+                .to(to)
+                .subject(notification.title.as_str())
+                .body(notification.body.clone())
+                .map_err(|e| format!("Failed to build mail: {}", e))?;
+
+            self.transport
+                .send(message)
+                .await
+                .map_err(|e| format!("Failed to send mail: {}", e))?;
+            Ok(DeliveryStatus::Sent)
+        })
+    }
+}
+
+/// Delivers notifications by posting them as JSON to a URL.
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookChannel {
+    /// Creates a channel posting to the given URL. Like webhook subscriptions, the channel does
+    /// not follow redirects and only connects to public addresses.
+    ///
+    /// # Panics
+    /// If the HTTP client cannot be built, e.g. because no TLS backend is available.
+    pub fn new(url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(webhooks::PublicResolver))
+            .build()
+            .expect("Failed to build the notification webhook HTTP client");
+        WebhookChannel { client, url }
+    }
+}
+
+impl NotificationChannel for WebhookChannel {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn deliver<'a>(
+        &'a self,
+        notification: &'a Notification,
+        recipient: &'a User,
+    ) -> LocalBoxFuture<'a, Result<DeliveryStatus, String>> {
+        Box::pin(async move {
+            let payload = json!({
+                "id": notification.id,
+                "kind": notification.kind,
+                "title": notification.title,
+                "body": notification.body,
+                "vehicle_id": notification.vehicle_id,
+                "user_id": recipient.id,
+                "username": recipient.username,
+                "created_at": notification.created_at,
+            });
+
+            // The host may resolve differently than at startup
+            webhooks::check_target(&self.url).await?;
+            self.client
+                .post(&self.url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Webhook request failed: {}", e))?;
+            Ok(DeliveryStatus::Sent)
+        })
+    }
+}
+
+/// Scheduler configuration, read from the environment.
+///
+/// - `interval`: Time between runs.
+/// - `reminder_days`: The registration reminder windows, in days before expiry.
+/// - `fuel_economy_drop_percent`: How far below average fuel economy has to drop to be alerted.
+/// - `channels`: The enabled delivery channels.
+pub struct NotificationSettings {
+    pub interval: Duration,
+    pub reminder_days: Vec<i64>,
+    pub fuel_economy_drop_percent: f64,
+    pub channels: Vec<Box<dyn NotificationChannel>>,
+}
+
+impl NotificationSettings {
+    /// Reads the settings from the environment, falling back to the defaults (with a warning)
+    /// for values that cannot be parsed.
+    pub fn from_env() -> Self {
+        let interval = env_or("NOTIFICATION_INTERVAL_SECS", 3600, |value| {
+            value.parse::<u64>().ok().filter(|&secs| secs > 0)
+        });
+
+        let reminder_days = env_or("REGISTRATION_REMINDER_DAYS", vec![30, 7, 1], |value| {
+            value
+                .split(',')
+                .map(|day| day.trim().parse::<i64>().ok().filter(|&day| day >= 0))
+                .collect::<Option<Vec<_>>>()
+                .filter(|days| !days.is_empty())
+        });
+
+        let fuel_economy_drop_percent = env_or("FUEL_ECONOMY_DROP_PERCENT", 25.0, |value| {
+            value
+                .parse::<f64>()
+                .ok()
+                .filter(|percent| *percent > 0.0 && *percent < 100.0)
+        });
+
+        let mut channels: Vec<Box<dyn NotificationChannel>> = vec![Box::new(InboxChannel)];
+        if let Ok(host) = env::var("SMTP_HOST") {
+            let port = env_or("SMTP_PORT", 25, |value| value.parse::<u16>().ok());
+            let from = env_or(
+                "SMTP_FROM",
+                "Vimana <vimana@localhost>"
+                    .parse::<Mailbox>()
+                    .expect("Valid default sender"),
+                |value| value.parse::<Mailbox>().ok(),
+            );
+            channels.push(Box::new(EmailChannel::new(&host, port, from)));
+        }
+        if let Ok(url) = env::var("NOTIFICATION_WEBHOOK_URL") {
+            channels.push(Box::new(WebhookChannel::new(url)));
+        }
+
+        NotificationSettings {
+            interval: Duration::from_secs(interval),
+            reminder_days,
+            fuel_economy_drop_percent,
+            channels,
+        }
+    }
+
+    /// Returns the names of the enabled channels.
+    fn channel_names(&self) -> Vec<&'static str> {
+        self.channels.iter().map(|channel| channel.name()).collect()
+    }
+}
+
+/// Reads and parses an environment variable, falling back to a default if it is unset or invalid.
+fn env_or<T>(name: &str, default: T, parse: impl FnOnce(&str) -> Option<T>) -> T {
+    match env::var(name) {
+        Ok(value) => parse(&value).unwrap_or_else(|| {
+            log::warn!("Ignoring invalid {}={:?}, using the default", name, value);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Starts the notification scheduler on the current runtime.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+pub fn spawn_scheduler(pool: DbPool) {
+    let settings = NotificationSettings::from_env();
+    log::info!(
+        "Notification scheduler running every {}s with channels {:?}",
+        settings.interval.as_secs(),
+        settings.channel_names()
+    );
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(settings.interval);
+        loop {
+            interval.tick().await;
+            run(&pool, &settings).await;
+        }
+    });
+}
+
+/// Creates the reminders and alerts that are due and delivers the pending notifications.
+async fn run(pool: &DbPool, settings: &NotificationSettings) {
+    match services::create_registration_reminders(
+        pool,
+        Utc::now().date_naive(),
+        settings.reminder_days.clone(),
+        settings.channel_names(),
+    )
+    .await
+    {
+        Ok(created) if !created.is_empty() => {
+            log::info!("Created {} registration reminders", created.len())
+        }
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to create registration reminders: {}", e),
+    }
+
+    match services::create_fuel_economy_alerts(
+        pool,
+        Utc::now() - chrono::Duration::days(FUEL_ECONOMY_LOOKBACK_DAYS),
+        settings.fuel_economy_drop_percent,
+        settings.channel_names(),
+    )
+    .await
+    {
+        Ok(created) if !created.is_empty() => {
+            log::info!("Created {} fuel economy alerts", created.len())
+        }
+        Ok(_) => {}
+        Err(e) => log::error!("Failed to create fuel economy alerts: {}", e),
+    }
+
+    if let Err(e) = deliver_pending(pool, &settings.channels).await {
+        log::error!("Failed to deliver notifications: {}", e);
+    }
+}
+
+/// Attempts the pending deliveries and records their outcome.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `channels`: The enabled delivery channels.
+///
+/// # Returns
+/// - `Ok(())`: If the deliveries were attempted; individual failures are recorded, not returned.
+/// - `Err(ServiceError)`: If the pending deliveries could not be loaded or updated.
+async fn deliver_pending(
+    pool: &DbPool,
+    channels: &[Box<dyn NotificationChannel>],
+) -> Result<(), services::ServiceError> {
+    let pending = services::run_blocking(pool, |pool| {
+        queries::get_pending_deliveries(pool, DELIVERY_BATCH_SIZE)
+    })
+    .await?;
+
+    for PendingDelivery {
+        delivery,
+        notification,
+        recipient,
+    } in pending
+    {
+        let outcome = match channels
+            .iter()
+            .find(|channel| channel.name() == delivery.channel)
+        {
+            Some(channel) => channel.deliver(&notification, &recipient).await,
+            None => Err(format!("Channel {} is not configured", delivery.channel)),
+        };
+        if let Err(e) = &outcome {
+            log::warn!(
+                "Delivery of notification {} through {} failed: {}",
+                notification.id,
+                delivery.channel,
+                e
+            );
+        }
+
+        services::run_blocking(pool, move |pool| {
+            queries::record_delivery_attempt(pool, delivery.id, outcome, MAX_DELIVERY_ATTEMPTS)
+        })
+        .await?;
+    }
+    Ok(())
+}
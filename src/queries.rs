@@ -1,13 +1,19 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, Utc};
 use diesel::r2d2::PoolError as R2D2Error;
 use diesel::result::Error as DieselError;
 use diesel::sql_types::{Timestamptz, Uuid as DieselUuid};
 use diesel::{ExpressionMethods, QueryDsl};
-use diesel::{OptionalExtension, RunQueryDsl};
+use diesel::{OptionalExtension, PgConnection, RunQueryDsl};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use rust_decimal::Decimal;
 use std::fmt;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
-use crate::{models, schema, DbPool};
+use crate::{models, requests::AuditLogQuery, schema, DbPool};
+
+/// Migrations embedded at compile time, used to report pending migrations.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 
 /// Custom error type for database interactions.
 ///
@@ -15,6 +21,7 @@ use crate::{models, schema, DbPool};
 /// - `ConnectionError`: Issues with the connection pool.
 /// - `QueryError`: Errors returned by Diesel during query execution.
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum DbError {
     ConnectionError(R2D2Error),
     QueryError(DieselError),
@@ -45,6 +52,69 @@ impl From<DieselError> for DbError {
     }
 }
 
+/// Measures a database round trip by running `SELECT 1`.
+///
+/// The time spent waiting for a pooled connection is excluded from the measurement.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+///
+/// # Returns
+/// - `Ok(Duration)`: The round-trip latency of the query.
+/// - `Err(DbError)`: If a connection cannot be obtained or the query fails.
+pub fn ping(pool: &DbPool) -> Result<Duration, DbError> {
+    let mut conn = pool.get()?;
+
+    let started = Instant::now();
+    diesel::sql_query("SELECT 1").execute(&mut conn)?;
+    Ok(started.elapsed())
+}
+
+/// Lists the embedded migrations that have not been applied to the database.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+///
+/// # Returns
+/// - `Ok(Vec<String>)`: Names of the pending migrations, empty when up to date.
+/// - `Err(DbError)`: If the migration table cannot be read.
+pub fn pending_migrations(pool: &DbPool) -> Result<Vec<String>, DbError> {
+    let mut conn = pool.get()?;
+
+    let pending = conn
+        .pending_migrations(MIGRATIONS)
+        .map_err(|e| DbError::StdError(e.to_string()))?;
+
+    Ok(pending
+        .iter()
+        .map(|migration| migration.name().to_string())
+        .collect())
+}
+
+/// Counts active sessions and domain records for the metrics endpoint.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+///
+/// # Returns
+/// - `Ok(DomainCounts)`: The current row counts.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn get_domain_counts(pool: &DbPool) -> Result<models::DomainCounts, DbError> {
+    let mut conn = pool.get()?;
+
+    let sql = r#"
+        SELECT
+            (SELECT COUNT(*) FROM public.sessions WHERE expires_at > NOW()) AS active_sessions,
+            (SELECT COUNT(*) FROM public.users) AS users,
+            (SELECT COUNT(*) FROM public.vehicles) AS vehicles,
+            (SELECT COUNT(*) FROM public.odometer) AS odometer_readings,
+            (SELECT COUNT(*) FROM public.refuel) AS refuels
+        ;
+    "#;
+
+    Ok(diesel::sql_query(sql).get_result::<models::DomainCounts>(&mut conn)?)
+}
+
 /// Fetches a user by username.
 ///
 /// # Arguments
@@ -69,27 +139,198 @@ pub fn get_user_by_username(
         .optional()?)
 }
 
-/// Creates a new session in the database.
+/// Fetches a user by ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the user to query.
+///
+/// # Returns
+/// - `Ok(Some(User))`: If the user exists.
+/// - `Ok(None)`: If no user matches the given ID.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn get_user_by_id(pool: &DbPool, user_id: Uuid) -> Result<Option<models::User>, DbError> {
+    let mut conn = pool.get()?;
+
+    Ok(schema::users::table
+        .filter(schema::users::id.eq(user_id))
+        .first::<models::User>(&mut conn)
+        .optional()?)
+}
+
+/// Sets or clears a user's email address.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `user_id`: The ID of the user.
+/// - `email`: The new address, or `None` to clear it.
+///
+/// # Returns
+/// - `Ok(Some(User))`: The updated user.
+/// - `Ok(None)`: If the user does not exist.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn update_user_email(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    email: Option<String>,
+) -> Result<Option<models::User>, DbError> {
+    use crate::schema::users::dsl;
+
+    Ok(diesel::update(dsl::users.find(user_id))
+        .set((dsl::email.eq(email), dsl::updated_at.eq(chrono::Utc::now())))
+        .get_result::<models::User>(conn)
+        .optional()?)
+}
+
+/// Retrieves a user's unit and format preferences.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the user.
+///
+/// # Returns
+/// - `Ok(Some(UserPreferences))`: If the user has chosen preferences.
+/// - `Ok(None)`: If the user uses the defaults.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn get_user_preferences(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<Option<models::UserPreferences>, DbError> {
+    use crate::schema::user_preferences::dsl;
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::user_preferences
+        .find(user_id)
+        .select(models::UserPreferences::as_select())
+        .first(&mut conn)
+        .optional()?)
+}
+
+/// Stores a user's unit and format preferences, replacing any earlier choice.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `preferences`: The preferences to store.
+///
+/// # Returns
+/// - `Ok(UserPreferences)`: The stored preferences.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn upsert_user_preferences(
+    conn: &mut PgConnection,
+    preferences: &models::UserPreferences,
+) -> Result<models::UserPreferences, DbError> {
+    use crate::schema::user_preferences::dsl;
+    use diesel::SelectableHelper;
+
+    Ok(diesel::insert_into(dsl::user_preferences)
+        .values(preferences)
+        .on_conflict(dsl::user_id)
+        .do_update()
+        .set(preferences)
+        .returning(models::UserPreferences::as_returning())
+        .get_result(conn)?)
+}
+
+/// Counts the users that are both administrators and active.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+///
+/// # Returns
+/// - `Ok(i64)`: The number of active administrators.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn count_active_admins(pool: &DbPool) -> Result<i64, DbError> {
+    use crate::schema::users::dsl::*;
+
+    let mut conn = pool.get()?;
+
+    Ok(users
+        .filter(is_admin.eq(true))
+        .filter(is_active.eq(true))
+        .count()
+        .get_result(&mut conn)?)
+}
+
+/// Counts all users, active or not.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+///
+/// # Returns
+/// - `Ok(i64)`: The number of users.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn count_users(pool: &DbPool) -> Result<i64, DbError> {
+    let mut conn = pool.get()?;
+
+    Ok(schema::users::table.count().get_result(&mut conn)?)
+}
+
+/// Creates the initial administrator, provided no active administrator exists yet.
+///
+/// The check and the insert run in one transaction holding a lock on `users`, so two
+/// concurrent setup attempts cannot both succeed. The administrator also gets a personal
+/// organization to keep their vehicles in.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `user_data`: The administrator details to insert.
+///
+/// # Returns
+/// - `Ok(Some(User))`: The created administrator.
+/// - `Ok(None)`: If an active administrator already exists.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn create_initial_admin(
+    conn: &mut PgConnection,
+    user_data: &models::NewUser,
+) -> Result<Option<models::User>, DbError> {
+    use crate::schema::users::dsl::*;
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        diesel::sql_query("LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE").execute(conn)?;
+
+        let admins: i64 = users
+            .filter(is_admin.eq(true))
+            .filter(is_active.eq(true))
+            .count()
+            .get_result(conn)?;
+
+        if admins > 0 {
+            return Ok(None);
+        }
+
+        let admin = diesel::insert_into(users)
+            .values(user_data)
+            .get_result::<models::User>(conn)?;
+
+        insert_organization(conn, &format!("{}'s vehicles", admin.full_name), admin.id)?;
+
+        Ok::<Option<models::User>, DbError>(Some(admin))
+    })
+}
+
+/// Creates a new session in the database.
+///
+/// # Arguments
+/// - `conn`: Database connection.
 /// - `session_data`: The session details to insert.
 ///
 /// # Returns
 /// - `Ok(usize)`: The number of rows inserted (typically 1).
 /// - `Err(DbError)`: If there is a database-related error.
 pub fn create_new_session(
-    pool: &DbPool,
+    conn: &mut PgConnection,
     session_data: &models::NewSession,
 ) -> Result<usize, DbError> {
-    let mut conn = pool.get()?;
-
     Ok(diesel::insert_into(schema::sessions::table)
         .values(session_data)
-        .execute(&mut conn)?)
+        .execute(conn)?)
 }
 
-/// Retrieves an active session by token and checks its expiry.
+/// Retrieves an active session by token and checks its expiry, together with the preferences of
+/// its user. Sessions of disabled users are not returned.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
@@ -97,448 +338,3067 @@ pub fn create_new_session(
 /// - `current_time`: The current time for expiry validation.
 ///
 /// # Returns
-/// - `Ok(Some(Session))`: If the session is valid and active.
-/// - `Ok(None)`: If no session matches the given token or if it is expired.
+/// - `Ok(Some((Session, Option<UserPreferences>)))`: If the session is valid and active; the
+///   preferences are `None` if the user has not chosen any.
+/// - `Ok(None)`: If no session matches the given token, if it is expired or if its user is
+///   disabled.
 /// - `Err(DbError)`: If there is a database-related error.
 pub fn get_active_session_by_token(
     pool: &DbPool,
     token: &str,
-    current_time: NaiveDateTime,
-) -> Result<Option<models::Session>, DbError> {
-    use crate::schema::sessions::dsl::*;
+    current_time: DateTime<Utc>,
+) -> Result<Option<(models::Session, Option<models::UserPreferences>)>, DbError> {
+    use crate::schema::{sessions, user_preferences, users};
+    use diesel::{JoinOnDsl, NullableExpressionMethods, SelectableHelper};
 
     let mut conn = pool.get()?;
 
-    Ok(sessions
-        .filter(session_token.eq(token))
-        .filter(expires_at.gt(current_time))
-        .first::<models::Session>(&mut conn)
+    Ok(sessions::table
+        .inner_join(users::table)
+        .left_join(
+            user_preferences::table.on(user_preferences::user_id.nullable().eq(sessions::user_id)),
+        )
+        .filter(sessions::session_token.eq(token))
+        .filter(sessions::expires_at.gt(current_time))
+        .filter(users::is_active.eq(true))
+        .select((
+            models::Session::as_select(),
+            Option::<models::UserPreferences>::as_select(),
+        ))
+        .first(&mut conn)
         .optional()?)
 }
 
 /// Deletes a session by user ID and session token.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
+/// - `conn`: Database connection.
 /// - `user_id`: The user ID associated with the session.
 /// - `token`: The session token to delete.
 ///
 /// # Returns
-/// - `Ok(usize)`: The number of rows deleted (typically 1).
+/// - `Ok(Vec<Uuid>)`: The IDs of the deleted sessions (typically one).
 /// - `Err(DbError)`: If there is a database-related error.
-pub fn delete_session(pool: &DbPool, user_id: Uuid, token: &str) -> Result<usize, DbError> {
-    let mut conn = pool.get()?;
-
+pub fn delete_session(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    token: &str,
+) -> Result<Vec<Uuid>, DbError> {
     Ok(diesel::delete(
         schema::sessions::table
             .filter(schema::sessions::user_id.eq(user_id))
             .filter(schema::sessions::session_token.eq(token)),
     )
-    .execute(&mut conn)?)
+    .returning(schema::sessions::id)
+    .get_results::<Uuid>(conn)?)
+}
+
+/// Applies a change and appends its audit log entries in the same transaction.
+///
+/// Either the change and its audit entries are committed together, or neither is.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `change`: The change to apply on the transaction's connection.
+/// - `entries`: Builds the audit entries from the result of the change.
+///
+/// # Returns
+/// - `Ok(T)`: The result of the change.
+/// - `Err(DbError)`: If the change or the audit insert fails; nothing is committed.
+pub fn audited<T, I>(
+    pool: &DbPool,
+    change: impl FnOnce(&mut PgConnection) -> Result<T, DbError>,
+    entries: impl FnOnce(&T) -> I,
+) -> Result<T, DbError>
+where
+    I: IntoIterator<Item = models::NewAuditLog>,
+{
+    use diesel::Connection;
+
+    let mut conn = pool.get()?;
+
+    conn.transaction(|conn| {
+        let value = change(conn)?;
+        let entries = entries(&value).into_iter().collect::<Vec<_>>();
+        if !entries.is_empty() {
+            diesel::insert_into(schema::audit_log::table)
+                .values(&entries)
+                .execute(conn)?;
+        }
+
+        Ok(value)
+    })
 }
 
-/// Creates a new user in the database.
+/// Retrieves audit log entries matching the given filters, newest first.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+/// - `filter`: Optional actor, entity and time range filters.
+/// - `limit`: Maximum number of entries to return.
+///
+/// # Returns
+/// - `Ok(Vec<AuditLog>)`: The matching audit entries.
+/// - `Err(DbError)`: If there is a database-related error.
+pub fn get_audit_logs(
+    pool: &DbPool,
+    filter: &AuditLogQuery,
+    limit: i64,
+) -> Result<Vec<models::AuditLog>, DbError> {
+    use crate::schema::audit_log::dsl;
+
+    let mut conn = pool.get()?;
+
+    let mut query = dsl::audit_log.into_boxed();
+    if let Some(user_id) = filter.user_id {
+        query = query.filter(dsl::user_id.eq(user_id));
+    }
+    if let Some(entity_type) = &filter.entity_type {
+        query = query.filter(dsl::entity_type.eq(entity_type.clone()));
+    }
+    if let Some(entity_id) = filter.entity_id {
+        query = query.filter(dsl::entity_id.eq(entity_id));
+    }
+    if let Some(start) = filter.start {
+        query = query.filter(dsl::created_at.ge(start));
+    }
+    if let Some(end) = filter.end {
+        query = query.filter(dsl::created_at.le(end));
+    }
+
+    Ok(query
+        .order(dsl::created_at.desc())
+        .limit(limit)
+        .load::<models::AuditLog>(&mut conn)?)
+}
+
+/// Creates a new user in the database as a member of an organization.
+///
+/// # Arguments
+/// - `conn`: Database connection.
 /// - `user_data`: The user details to insert.
+/// - `organization_id`: The organization the user joins.
+/// - `role`: The user's role in that organization.
 ///
 /// # Returns
 /// - `Ok(User)`: The created user record.
 /// - `Err(DbError)`: If there is a database-related error.
 pub fn create_new_user(
-    pool: &DbPool,
+    conn: &mut PgConnection,
     user_data: &models::NewUser,
+    organization_id: Uuid,
+    role: models::OrganizationRole,
 ) -> Result<models::User, DbError> {
-    let mut conn = pool.get()?;
+    use crate::schema::{organization_members, users};
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        let user = diesel::insert_into(users::table)
+            .values(user_data)
+            .get_result::<models::User>(conn)?;
+
+        diesel::insert_into(organization_members::table)
+            .values((
+                organization_members::organization_id.eq(organization_id),
+                organization_members::user_id.eq(user.id),
+                organization_members::role.eq(role.as_str()),
+                organization_members::accepted_at.eq(Utc::now()),
+            ))
+            .execute(conn)?;
+
+        Ok::<models::User, DbError>(user)
+    })
+}
+
+/// Inserts an organization and makes `admin_id` its administrator.
+///
+/// Takes a connection rather than the pool so callers can run it inside their transaction.
+fn insert_organization(
+    conn: &mut PgConnection,
+    name: &str,
+    admin_id: Uuid,
+) -> Result<models::Organization, DbError> {
+    use crate::schema::{organization_members, organizations};
+
+    let organization = diesel::insert_into(organizations::table)
+        .values(&models::NewOrganization {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+        })
+        .get_result::<models::Organization>(conn)?;
+
+    diesel::insert_into(organization_members::table)
+        .values((
+            organization_members::organization_id.eq(organization.id),
+            organization_members::user_id.eq(admin_id),
+            organization_members::role.eq(models::OrganizationRole::Admin.as_str()),
+            organization_members::accepted_at.eq(Utc::now()),
+        ))
+        .execute(conn)?;
 
-    Ok(diesel::insert_into(schema::users::table)
-        .values(user_data)
-        .get_result::<models::User>(&mut conn)?)
+    Ok(organization)
 }
 
-/// Creates a new vehicle record in the database.
+/// Creates a new organization with its creator as administrator.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `new_vehicle`: The vehicle data to insert.
+/// - `conn`: Database connection.
+/// - `name`: The name of the organization.
+/// - `admin_id`: The ID of the creating user.
 ///
 /// # Returns
-/// - `Ok(Vehicle)`: The newly created vehicle.
+/// - `Ok(Organization)`: The newly created organization.
 /// - `Err(DbError)`: If the query fails.
-pub fn create_vehicle(
-    pool: &DbPool,
-    new_vehicle: &models::NewVehicle,
-) -> Result<models::Vehicle, DbError> {
-    let mut conn = pool.get()?;
-    Ok(diesel::insert_into(schema::vehicles::table)
-        .values(new_vehicle)
-        .get_result::<models::Vehicle>(&mut conn)?)
+pub fn create_organization(
+    conn: &mut PgConnection,
+    name: &str,
+    admin_id: Uuid,
+) -> Result<models::Organization, DbError> {
+    use diesel::Connection;
+
+    conn.transaction(|conn| insert_organization(conn, name, admin_id))
 }
 
-/// Retrieves all vehicles owned by a specific user.
+/// Retrieves the organizations a user belongs to, along with the user's role in each.
+///
+/// Organizations the user is only invited to are left out.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `user_id`: The ID of the user.
 ///
 /// # Returns
-/// - `Ok(Vec<Vehicle>)`: A list of vehicles owned by the user.
+/// - `Ok(Vec<(Organization, String)>)`: The organizations, in the order the user joined them.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_vehicles_by_user_id(
+pub fn get_organizations_by_user_id(
     pool: &DbPool,
-    user_id_value: Uuid,
-) -> Result<Vec<models::Vehicle>, DbError> {
-    use crate::schema::vehicles::dsl;
+    user_id: Uuid,
+) -> Result<Vec<(models::Organization, String)>, DbError> {
+    use crate::schema::{organization_members, organizations};
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
 
-    Ok(schema::vehicles::table
-        .filter(dsl::user_id.eq(user_id_value))
-        .order(dsl::created_at.asc())
-        .load::<models::Vehicle>(&mut conn)?)
+    Ok(organizations::table
+        .inner_join(organization_members::table)
+        .filter(organization_members::user_id.eq(user_id))
+        .filter(organization_members::accepted_at.is_not_null())
+        .order(organization_members::created_at.asc())
+        .select((
+            models::Organization::as_select(),
+            organization_members::role,
+        ))
+        .load::<(models::Organization, String)>(&mut conn)?)
 }
 
-/// Retrieves a vehicle by its ID.
+/// Retrieves a user's membership of (or invitation to) an organization.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
+/// - `organization_id`: The ID of the organization.
+/// - `user_id`: The ID of the user.
 ///
 /// # Returns
-/// - `Ok(Some(Vehicle))`: The vehicle data, if found.
-/// - `Ok(None)`: If no vehicle matches the given ID.
+/// - `Ok(Some(OrganizationMember))`: The membership or pending invitation, if any.
+/// - `Ok(None)`: If the user is neither a member of nor invited to the organization.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_vehicle_by_id(
+pub fn get_organization_member(
     pool: &DbPool,
-    vehicle_id_value: Uuid,
-) -> Result<Option<models::Vehicle>, DbError> {
-    use crate::schema::vehicles::dsl::*;
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::OrganizationMember>, DbError> {
+    use crate::schema::organization_members::dsl;
 
     let mut conn = pool.get()?;
 
-    Ok(schema::vehicles::table
-        .filter(id.eq(vehicle_id_value))
-        .first::<models::Vehicle>(&mut conn)
+    Ok(dsl::organization_members
+        .find((organization_id, user_id))
+        .first::<models::OrganizationMember>(&mut conn)
         .optional()?)
 }
 
-/// Updates a vehicle by its ID.
+/// Retrieves the members of an organization with their user details.
+///
+/// Pending invitations are left out, so they don't reveal which usernames exist.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle to update.
-/// - `new_brand`: Updated brand (optional).
-/// - `new_model`: Updated model (optional).
-/// - `new_registration`: Updated registration (optional).
-/// - `new_registration_expiry_date`: Updated registration expiry date (optional).
+/// - `organization_id`: The ID of the organization.
 ///
 /// # Returns
-/// - `Ok(Vehicle)`: The updated vehicle data.
+/// - `Ok(Vec<(OrganizationMember, String, String)>)`: Each membership with the member's username and full name.
 /// - `Err(DbError)`: If the query fails.
-pub fn update_vehicle_by_id(
+pub fn get_organization_members(
     pool: &DbPool,
-    vehicle_id_value: Uuid,
-    new_brand: Option<String>,
-    new_model: Option<String>,
-    new_registration: Option<String>,
-    new_registration_expiry_date: Option<NaiveDate>,
-) -> Result<models::Vehicle, DbError> {
-    use crate::schema::vehicles::dsl::*;
+    organization_id: Uuid,
+) -> Result<Vec<(models::OrganizationMember, String, String)>, DbError> {
+    use crate::schema::{organization_members, users};
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
 
-    diesel::update(vehicles.filter(id.eq(vehicle_id_value)))
-        .set((
-            new_brand.map(|val| brand.eq(val)),
-            new_model.map(|val| model.eq(val)),
-            new_registration.map(|val| registration.eq(val)),
-            new_registration_expiry_date.map(|val| registration_expiry_date.eq(val)),
-            updated_at.eq(chrono::Utc::now().naive_utc()),
+    Ok(organization_members::table
+        .inner_join(users::table)
+        .filter(organization_members::organization_id.eq(organization_id))
+        .filter(organization_members::accepted_at.is_not_null())
+        .order(organization_members::created_at.asc())
+        .select((
+            models::OrganizationMember::as_select(),
+            users::username,
+            users::full_name,
         ))
-        .execute(&mut conn)?;
-
-    get_vehicle_by_id(pool, vehicle_id_value)?
-        .ok_or(DbError::QueryError(diesel::result::Error::NotFound))
+        .load::<(models::OrganizationMember, String, String)>(&mut conn)?)
 }
 
-/// Deletes a vehicle by its ID.
+/// Creates an organization membership (an invitation, when `accepted_at` is `None`).
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle to delete.
+/// - `conn`: Database connection.
+/// - `member`: The membership to insert.
 ///
 /// # Returns
-/// - `Ok(usize)`: Number of rows deleted.
+/// - `Ok(Some(OrganizationMember))`: The newly created membership.
+/// - `Ok(None)`: If the user is already a member or has a pending invitation.
 /// - `Err(DbError)`: If the query fails.
-pub fn delete_vehicle_by_id(pool: &DbPool, vehicle_id_value: Uuid) -> Result<usize, DbError> {
-    use crate::schema::vehicles::dsl::*;
-
-    let mut conn = pool.get()?;
-
-    Ok(diesel::delete(vehicles.filter(id.eq(vehicle_id_value))).execute(&mut conn)?)
+pub fn create_organization_member(
+    conn: &mut PgConnection,
+    member: &models::OrganizationMember,
+) -> Result<Option<models::OrganizationMember>, DbError> {
+    Ok(diesel::insert_into(schema::organization_members::table)
+        .values(member)
+        .on_conflict_do_nothing()
+        .get_result::<models::OrganizationMember>(conn)
+        .optional()?)
 }
 
-/// Creates a new odometer entry for a specific vehicle.
+/// Retrieves the pending organization invitations addressed to a user.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
-/// - `odometer_value`: The odometer reading.
-/// - `timestamp`: Optional timestamp for the odometer entry.
+/// - `user_id`: The ID of the invited user.
 ///
 /// # Returns
-/// - `Ok(Odometer)`: The newly created odometer entry.
+/// - `Ok(Vec<(OrganizationMember, Organization)>)`: Each pending invitation with its organization.
 /// - `Err(DbError)`: If the query fails.
-pub fn create_new_odometer(
+pub fn get_pending_organization_invitations(
     pool: &DbPool,
-    vehicle_id: Uuid,
-    odometer_value: f32,
-    timestamp: Option<NaiveDateTime>,
-) -> Result<models::Odometer, DbError> {
-    use crate::schema::odometer;
+    user_id: Uuid,
+) -> Result<Vec<(models::OrganizationMember, models::Organization)>, DbError> {
+    use crate::schema::{organization_members, organizations};
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
-    let new_odometer = models::NewOdometer {
-        vehicle_id,
-        odometer_value,
-    };
 
-    diesel::insert_into(odometer::table)
-        .values((
-            &new_odometer,
-            timestamp.map(|ts| odometer::timestamp.eq(ts)),
+    Ok(organization_members::table
+        .inner_join(organizations::table)
+        .filter(organization_members::user_id.eq(user_id))
+        .filter(organization_members::accepted_at.is_null())
+        .order(organization_members::created_at.asc())
+        .select((
+            models::OrganizationMember::as_select(),
+            models::Organization::as_select(),
         ))
-        .get_result::<models::Odometer>(&mut conn)
-        .map_err(DbError::from)
+        .load::<(models::OrganizationMember, models::Organization)>(&mut conn)?)
 }
 
-/// Retrieves the latest odometer entry for a specific vehicle.
+/// Marks a pending organization invitation as accepted.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `organization_id`: The ID of the organization.
+/// - `user_id`: The ID of the invited user.
+///
+/// # Returns
+/// - `Ok(Some(OrganizationMember))`: The accepted membership.
+/// - `Ok(None)`: If there is no pending invitation.
+/// - `Err(DbError)`: If the query fails.
+pub fn accept_organization_member(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::OrganizationMember>, DbError> {
+    use crate::schema::organization_members::dsl;
+
+    Ok(diesel::update(
+        dsl::organization_members
+            .find((organization_id, user_id))
+            .filter(dsl::accepted_at.is_null()),
+    )
+    .set(dsl::accepted_at.eq(Utc::now()))
+    .get_result::<models::OrganizationMember>(conn)
+    .optional()?)
+}
+
+/// Removes a user from an organization, along with their access to its vehicles, or withdraws
+/// their invitation.
+///
+/// The last accepted administrator cannot be removed. Sessions that had the organization active are
+/// left without an active organization.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `organization_id`: The ID of the organization.
+/// - `user_id`: The ID of the member to remove.
+///
+/// # Returns
+/// - `Ok(Some(OrganizationMember))`: The removed membership.
+/// - `Ok(None)`: If the user is not a member, or is the organization's last administrator.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_organization_member(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::OrganizationMember>, DbError> {
+    use crate::schema::{organization_members, sessions, vehicle_members, vehicles};
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        let members = organization_members::table
+            .filter(organization_members::organization_id.eq(organization_id))
+            .for_update()
+            .load::<models::OrganizationMember>(conn)?;
+
+        let Some(member) = members.iter().find(|m| m.user_id == user_id) else {
+            return Ok(None);
+        };
+        let is_admin = |m: &models::OrganizationMember| {
+            m.accepted_at.is_some() && m.role == models::OrganizationRole::Admin.as_str()
+        };
+        if is_admin(member) && members.iter().filter(|m| is_admin(m)).count() == 1 {
+            return Ok(None);
+        }
+
+        diesel::delete(organization_members::table.find((organization_id, user_id)))
+            .execute(conn)?;
+
+        let organization_vehicles = vehicles::table
+            .filter(vehicles::organization_id.eq(organization_id))
+            .select(vehicles::id);
+        diesel::delete(
+            vehicle_members::table
+                .filter(vehicle_members::user_id.eq(user_id))
+                .filter(vehicle_members::vehicle_id.eq_any(organization_vehicles)),
+        )
+        .execute(conn)?;
+
+        diesel::update(
+            sessions::table
+                .filter(sessions::user_id.eq(user_id))
+                .filter(sessions::active_organization_id.eq(organization_id)),
+        )
+        .set(sessions::active_organization_id.eq(None::<Uuid>))
+        .execute(conn)?;
+
+        Ok::<Option<models::OrganizationMember>, DbError>(Some(member.clone()))
+    })
+}
+
+/// Switches the organization a session is working with.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The user ID associated with the session.
+/// - `token`: The session token.
+/// - `organization_id`: The organization to make active.
+///
+/// # Returns
+/// - `Ok(usize)`: Number of sessions updated (typically 1).
+/// - `Err(DbError)`: If the query fails.
+pub fn set_active_organization(
+    pool: &DbPool,
+    user_id: Uuid,
+    token: &str,
+    organization_id: Uuid,
+) -> Result<usize, DbError> {
+    use crate::schema::sessions::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(diesel::update(
+        dsl::sessions
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::session_token.eq(token)),
+    )
+    .set(dsl::active_organization_id.eq(organization_id))
+    .execute(&mut conn)?)
+}
+
+/// Creates a new vehicle record in the database, with its creator as owner.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `new_vehicle`: The vehicle data to insert.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The newly created vehicle.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_vehicle(
+    conn: &mut PgConnection,
+    new_vehicle: &models::NewVehicle,
+) -> Result<models::Vehicle, DbError> {
+    use crate::schema::{vehicle_members, vehicles};
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        let vehicle = diesel::insert_into(vehicles::table)
+            .values(new_vehicle)
+            .get_result::<models::Vehicle>(conn)?;
+
+        diesel::insert_into(vehicle_members::table)
+            .values((
+                vehicle_members::vehicle_id.eq(vehicle.id),
+                vehicle_members::user_id.eq(vehicle.user_id),
+                vehicle_members::role.eq(models::VehicleRole::Owner.as_str()),
+                vehicle_members::accepted_at.eq(vehicle.created_at),
+            ))
+            .execute(conn)?;
+
+        Ok::<models::Vehicle, DbError>(vehicle)
+    })
+}
+
+/// Retrieves the vehicles of an organization that a user is an accepted member of, along with
+/// the user's role.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the user.
+/// - `organization_id`: The organization the vehicles belong to.
+/// - `include_archived`: Whether archived vehicles are returned too.
+///
+/// # Returns
+/// - `Ok(Vec<(Vehicle, String)>)`: The vehicles shared with the user and the user's role on each.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicles_by_user_id(
+    pool: &DbPool,
+    user_id_value: Uuid,
+    organization_id: Uuid,
+    include_archived: bool,
+) -> Result<Vec<(models::Vehicle, String)>, DbError> {
+    use crate::schema::{vehicle_members, vehicles};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    let mut query = vehicles::table
+        .inner_join(vehicle_members::table)
+        .filter(vehicle_members::user_id.eq(user_id_value))
+        .filter(vehicle_members::accepted_at.is_not_null())
+        .filter(vehicles::organization_id.eq(organization_id))
+        .into_boxed();
+    if !include_archived {
+        query = query.filter(vehicles::archived_at.is_null());
+    }
+
+    Ok(query
+        .order(vehicles::created_at.asc())
+        .select((models::Vehicle::as_select(), vehicle_members::role))
+        .load::<(models::Vehicle, String)>(&mut conn)?)
+}
+
+/// Retrieves all vehicles of an organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `organization_id`: The ID of the organization.
+/// - `include_archived`: Whether archived vehicles are returned too.
+///
+/// # Returns
+/// - `Ok(Vec<Vehicle>)`: The organization's vehicles, oldest first.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicles_by_organization_id(
+    pool: &DbPool,
+    organization_id: Uuid,
+    include_archived: bool,
+) -> Result<Vec<models::Vehicle>, DbError> {
+    use crate::schema::vehicles::dsl;
+
+    let mut conn = pool.get()?;
+
+    let mut query = dsl::vehicles
+        .filter(dsl::organization_id.eq(organization_id))
+        .into_boxed();
+    if !include_archived {
+        query = query.filter(dsl::archived_at.is_null());
+    }
+
+    Ok(query
+        .order(dsl::created_at.asc())
+        .load::<models::Vehicle>(&mut conn)?)
+}
+
+/// Retrieves a vehicle by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Some(Vehicle))`: The vehicle data, if found.
+/// - `Ok(None)`: If no vehicle matches the given ID.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicle_by_id(
+    pool: &DbPool,
+    vehicle_id_value: Uuid,
+) -> Result<Option<models::Vehicle>, DbError> {
+    use crate::schema::vehicles::dsl::*;
+
+    let mut conn = pool.get()?;
+
+    Ok(schema::vehicles::table
+        .filter(id.eq(vehicle_id_value))
+        .first::<models::Vehicle>(&mut conn)
+        .optional()?)
+}
+
+/// Updates a vehicle by its ID.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle to update.
+/// - `changes`: The updated details; fields left as `None` are kept.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The updated vehicle data.
+/// - `Err(DbError)`: If the query fails.
+pub fn update_vehicle_by_id(
+    conn: &mut PgConnection,
+    vehicle_id_value: Uuid,
+    changes: models::VehicleChanges,
+) -> Result<models::Vehicle, DbError> {
+    use crate::schema::vehicles::dsl::*;
+
+    Ok(diesel::update(vehicles.filter(id.eq(vehicle_id_value)))
+        .set((&changes, updated_at.eq(chrono::Utc::now())))
+        .get_result::<models::Vehicle>(conn)?)
+}
+
+/// Archives a vehicle by its ID, keeping its odometer and refuel history.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle to archive.
+///
+/// # Returns
+/// - `Ok(Some(Vehicle))`: The archived vehicle.
+/// - `Ok(None)`: If no active vehicle matches the given ID.
+/// - `Err(DbError)`: If the query fails.
+pub fn archive_vehicle_by_id(
+    conn: &mut PgConnection,
+    vehicle_id_value: Uuid,
+) -> Result<Option<models::Vehicle>, DbError> {
+    use crate::schema::vehicles::dsl::*;
+
+    let now = chrono::Utc::now();
+
+    Ok(diesel::update(
+        vehicles
+            .filter(id.eq(vehicle_id_value))
+            .filter(archived_at.is_null()),
+    )
+    .set((archived_at.eq(now), updated_at.eq(now)))
+    .get_result::<models::Vehicle>(conn)
+    .optional()?)
+}
+
+/// Restores an archived vehicle by its ID.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle to restore.
+///
+/// # Returns
+/// - `Ok(Some(Vehicle))`: The restored vehicle.
+/// - `Ok(None)`: If no archived vehicle matches the given ID.
+/// - `Err(DbError)`: If the query fails.
+pub fn restore_vehicle_by_id(
+    conn: &mut PgConnection,
+    vehicle_id_value: Uuid,
+) -> Result<Option<models::Vehicle>, DbError> {
+    use crate::schema::vehicles::dsl::*;
+
+    Ok(diesel::update(
+        vehicles
+            .filter(id.eq(vehicle_id_value))
+            .filter(archived_at.is_not_null()),
+    )
+    .set((
+        archived_at.eq(None::<chrono::DateTime<chrono::Utc>>),
+        updated_at.eq(chrono::Utc::now()),
+    ))
+    .get_result::<models::Vehicle>(conn)
+    .optional()?)
+}
+
+/// Permanently deletes a vehicle by its ID, along with its odometer and refuel history.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle to delete.
+///
+/// # Returns
+/// - `Ok(usize)`: Number of rows deleted.
+/// - `Err(DbError)`: If the query fails.
+pub fn purge_vehicle_by_id(
+    conn: &mut PgConnection,
+    vehicle_id_value: Uuid,
+) -> Result<usize, DbError> {
+    use crate::schema::vehicles::dsl::*;
+
+    Ok(diesel::delete(vehicles.filter(id.eq(vehicle_id_value))).execute(conn)?)
+}
+
+/// Retrieves a user's membership of a vehicle, accepted or pending.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user.
+///
+/// # Returns
+/// - `Ok(Some(VehicleMember))`: The membership, if any.
+/// - `Ok(None)`: If the vehicle is not shared with the user.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicle_member(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::VehicleMember>, DbError> {
+    use crate::schema::vehicle_members::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::vehicle_members
+        .find((vehicle_id, user_id))
+        .first::<models::VehicleMember>(&mut conn)
+        .optional()?)
+}
+
+/// Retrieves the members of a vehicle, including pending invitations, with their user details.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Vec<(VehicleMember, String, String)>)`: Each membership with the member's username and full name.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicle_members(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+) -> Result<Vec<(models::VehicleMember, String, String)>, DbError> {
+    use crate::schema::{users, vehicle_members};
+    use diesel::{JoinOnDsl, SelectableHelper};
+
+    let mut conn = pool.get()?;
+
+    Ok(vehicle_members::table
+        .inner_join(users::table.on(users::id.eq(vehicle_members::user_id)))
+        .filter(vehicle_members::vehicle_id.eq(vehicle_id))
+        .order(vehicle_members::created_at.asc())
+        .select((
+            models::VehicleMember::as_select(),
+            users::username,
+            users::full_name,
+        ))
+        .load::<(models::VehicleMember, String, String)>(&mut conn)?)
+}
+
+/// Retrieves the pending vehicle invitations addressed to a user.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the invited user.
+///
+/// # Returns
+/// - `Ok(Vec<(VehicleMember, Vehicle)>)`: Each pending invitation with its vehicle.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_pending_invitations(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<Vec<(models::VehicleMember, models::Vehicle)>, DbError> {
+    use crate::schema::{vehicle_members, vehicles};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(vehicle_members::table
+        .inner_join(vehicles::table)
+        .filter(vehicle_members::user_id.eq(user_id))
+        .filter(vehicle_members::accepted_at.is_null())
+        .filter(vehicles::archived_at.is_null())
+        .order(vehicle_members::created_at.asc())
+        .select((
+            models::VehicleMember::as_select(),
+            models::Vehicle::as_select(),
+        ))
+        .load::<(models::VehicleMember, models::Vehicle)>(&mut conn)?)
+}
+
+/// Creates a vehicle membership (an invitation, when `accepted_at` is `None`).
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `member`: The membership to insert.
+///
+/// # Returns
+/// - `Ok(VehicleMember)`: The newly created membership.
+/// - `Err(DbError)`: If the query fails, e.g. when the user is already a member.
+pub fn create_vehicle_member(
+    conn: &mut PgConnection,
+    member: &models::VehicleMember,
+) -> Result<models::VehicleMember, DbError> {
+    Ok(diesel::insert_into(schema::vehicle_members::table)
+        .values(member)
+        .get_result::<models::VehicleMember>(conn)?)
+}
+
+/// Marks a pending vehicle invitation as accepted.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the invited user.
+///
+/// # Returns
+/// - `Ok(Some(VehicleMember))`: The accepted membership.
+/// - `Ok(None)`: If there is no pending invitation.
+/// - `Err(DbError)`: If the query fails.
+pub fn accept_vehicle_member(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::VehicleMember>, DbError> {
+    use crate::schema::vehicle_members::dsl;
+
+    Ok(diesel::update(
+        dsl::vehicle_members
+            .find((vehicle_id, user_id))
+            .filter(dsl::accepted_at.is_null()),
+    )
+    .set(dsl::accepted_at.eq(chrono::Utc::now()))
+    .get_result::<models::VehicleMember>(conn)
+    .optional()?)
+}
+
+/// Removes a user's membership of (or invitation to) a vehicle.
+///
+/// The last accepted owner cannot be removed, so every vehicle stays manageable. The owner
+/// count is checked inside the transaction while holding a lock on the vehicle's memberships.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the member to remove.
+///
+/// # Returns
+/// - `Ok(Some(VehicleMember))`: The removed membership.
+/// - `Ok(None)`: If the user is not a member, or is the vehicle's last owner.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_vehicle_member(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<models::VehicleMember>, DbError> {
+    use crate::schema::vehicle_members::dsl;
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        let members = dsl::vehicle_members
+            .filter(dsl::vehicle_id.eq(vehicle_id))
+            .for_update()
+            .load::<models::VehicleMember>(conn)?;
+
+        let Some(member) = members.iter().find(|m| m.user_id == user_id) else {
+            return Ok(None);
+        };
+        let is_owner = |m: &models::VehicleMember| {
+            m.accepted_at.is_some() && m.role == models::VehicleRole::Owner.as_str()
+        };
+        if is_owner(member) && members.iter().filter(|m| is_owner(m)).count() == 1 {
+            return Ok(None);
+        }
+
+        diesel::delete(dsl::vehicle_members.find((vehicle_id, user_id))).execute(conn)?;
+
+        Ok::<Option<models::VehicleMember>, DbError>(Some(member.clone()))
+    })
+}
+
+/// Creates a new odometer entry for a specific vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `odometer_value`: The odometer reading.
+/// - `timestamp`: Optional timestamp for the odometer entry.
+/// - `user_id`: The ID of the user submitting the entry.
+/// - `driver_id`: The ID of the user who was driving, if known.
+///
+/// # Returns
+/// - `Ok(Odometer)`: The newly created odometer entry.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_new_odometer(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    odometer_value: Decimal,
+    timestamp: Option<DateTime<Utc>>,
+    user_id: Uuid,
+    driver_id: Option<Uuid>,
+) -> Result<models::Odometer, DbError> {
+    use crate::schema::odometer;
+
+    let new_odometer = models::NewOdometer {
+        vehicle_id,
+        odometer_value,
+        user_id: Some(user_id),
+        driver_id,
+    };
+
+    diesel::insert_into(odometer::table)
+        .values((
+            &new_odometer,
+            timestamp.map(|ts| odometer::timestamp.eq(ts)),
+        ))
+        .get_result::<models::Odometer>(conn)
+        .map_err(DbError::from)
+}
+
+/// Retrieves the latest odometer entry for a specific vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Some(Odometer))`: The latest odometer entry.
+/// - `Ok(None)`: If no odometer entry exists.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_latest_odometer(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+) -> Result<Option<models::Odometer>, DbError> {
+    use crate::schema::odometer::dsl;
+
+    let mut conn = pool.get()?;
+    Ok(dsl::odometer
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .order(dsl::timestamp.desc())
+        .first::<models::Odometer>(&mut conn)
+        .optional()?)
+}
+
+/// Retrieves time-series odometer data for a specific vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start date/time of the range.
+/// - `end`: The end date/time of the range.
+///
+/// # Returns
+/// - `Ok(Vec<Odometer>)`: A list of odometer entries.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_odometer_timeseries(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<models::Odometer>, DbError> {
+    use crate::schema::odometer::dsl;
+
+    let mut conn = pool.get()?;
+    Ok(dsl::odometer
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .filter(dsl::timestamp.between(start, end))
+        .order(dsl::timestamp.asc())
+        .load::<models::Odometer>(&mut conn)?)
+}
+
+/// Creates a new refueling entry for a specific vehicle, along with an associated odometer entry.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user submitting the entry.
+/// - `details`: The fuel quantity, odometer reading, optional timestamp, driver and cost.
+///
+/// # Returns
+/// - `Ok(RefuelWithOdometer)`: The newly created refueling entry with odometer details.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_new_refuel(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+    details: models::RefuelDetails,
+) -> Result<models::RefuelWithOdometer, DbError> {
+    use diesel::Connection;
+
+    conn.transaction(|conn| insert_refuel(conn, vehicle_id, user_id, &details))
+}
+
+/// Creates many refueling entries for a vehicle, each with its odometer entry, in one
+/// transaction: either all of them are created or none is.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user submitting the entries.
+/// - `refuels`: The refuels to create.
+///
+/// # Returns
+/// - `Ok(Vec<RefuelWithOdometer>)`: The newly created refueling entries, in the given order.
+/// - `Err(DbError)`: If any insert fails.
+pub fn import_refuels(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+    refuels: Vec<models::RefuelDetails>,
+) -> Result<Vec<models::RefuelWithOdometer>, DbError> {
+    use diesel::Connection;
+
+    conn.transaction(|conn| {
+        refuels
+            .iter()
+            .map(|details| insert_refuel(conn, vehicle_id, user_id, details))
+            .collect()
+    })
+}
+
+/// Inserts a refueling entry and its odometer entry on an open connection.
+fn insert_refuel(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+    details: &models::RefuelDetails,
+) -> Result<models::RefuelWithOdometer, DbError> {
+    use crate::schema::{odometer, refuel};
+
+    let new_odometer = diesel::insert_into(odometer::table)
+        .values((
+            odometer::vehicle_id.eq(vehicle_id),
+            odometer::odometer_value.eq(details.odometer_value),
+            details.timestamp.map(|ts| odometer::timestamp.eq(ts)),
+            odometer::user_id.eq(user_id),
+            odometer::driver_id.eq(details.driver_id),
+        ))
+        .get_result::<models::Odometer>(conn)?;
+
+    let new_refuel = diesel::insert_into(refuel::table)
+        .values((
+            refuel::vehicle_id.eq(vehicle_id),
+            refuel::odometer_id.eq(new_odometer.id),
+            refuel::refuel_quantity.eq(details.refuel_quantity),
+            details.timestamp.map(|ts| refuel::timestamp.eq(ts)),
+            refuel::user_id.eq(user_id),
+            refuel::driver_id.eq(details.driver_id),
+            refuel::cost.eq(details.cost),
+        ))
+        .get_result::<models::Refuel>(conn)?;
+
+    Ok(models::RefuelWithOdometer {
+        id: new_refuel.id,
+        vehicle_id: new_refuel.vehicle_id,
+        odometer_id: new_refuel.odometer_id,
+        refuel_quantity: new_refuel.refuel_quantity,
+        odometer_value: new_odometer.odometer_value,
+        timestamp: new_refuel.timestamp,
+        user_id: new_refuel.user_id,
+        driver_id: new_refuel.driver_id,
+        cost: new_refuel.cost,
+    })
+}
+
+/// Retrieves the whole history of a vehicle: its odometer readings, refuels, trips and charging
+/// sessions.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle`: The vehicle.
+///
+/// # Returns
+/// - `Ok(VehicleHistory)`: The vehicle with its history, oldest entries first.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_vehicle_history(
+    pool: &DbPool,
+    vehicle: models::Vehicle,
+) -> Result<models::VehicleHistory, DbError> {
+    use crate::schema::{charging_sessions, insurance_policies, odometer, refuel, trips};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    let odometer = odometer::table
+        .filter(odometer::vehicle_id.eq(vehicle.id))
+        .order((odometer::timestamp.asc(), odometer::created_at.asc()))
+        .load::<models::Odometer>(&mut conn)?;
+    let refuels = refuel::table
+        .filter(refuel::vehicle_id.eq(vehicle.id))
+        .order((refuel::timestamp.asc(), refuel::created_at.asc()))
+        .select(models::Refuel::as_select())
+        .load(&mut conn)?;
+    let trips = trips::table
+        .filter(trips::vehicle_id.eq(vehicle.id))
+        .order(trips::started_at.asc())
+        .select(models::Trip::as_select())
+        .load(&mut conn)?;
+    let charging_sessions = charging_sessions::table
+        .filter(charging_sessions::vehicle_id.eq(vehicle.id))
+        .order(charging_sessions::started_at.asc())
+        .select(models::ChargingSession::as_select())
+        .load(&mut conn)?;
+    let insurance_policies = insurance_policies::table
+        .filter(insurance_policies::vehicle_id.eq(vehicle.id))
+        .order(insurance_policies::start_date.asc())
+        .select(models::InsurancePolicy::as_select())
+        .load(&mut conn)?;
+
+    Ok(models::VehicleHistory {
+        vehicle,
+        odometer,
+        refuels,
+        trips,
+        charging_sessions,
+        insurance_policies,
+    })
+}
+
+/// Restores vehicles and their history from a backup, in one transaction.
+///
+/// Every record gets a new ID, and references between records are remapped to the new IDs.
+/// The vehicles are owned by `user_id` and belong to `organization_id`. Records attributed to
+/// `original_user_id` (the user who made the backup) are attributed to `user_id`; other user
+/// references are dropped, as those users may not exist here. Insurance policies lose their
+/// policy document, which is not part of the backup.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `user_id`: The ID of the restoring user.
+/// - `organization_id`: The organization receiving the vehicles.
+/// - `original_user_id`: The ID of the user who made the backup.
+/// - `histories`: The vehicles and their history.
+///
+/// # Returns
+/// - `Ok(Vec<Vehicle>)`: The restored vehicles.
+/// - `Err(DbError)`: If any insert fails, in which case nothing is restored.
+pub fn restore_vehicle_histories(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    organization_id: Uuid,
+    original_user_id: Uuid,
+    histories: Vec<models::VehicleHistory>,
+) -> Result<Vec<models::Vehicle>, DbError> {
+    use crate::schema::{
+        charging_sessions, insurance_policies, odometer, refuel, trips, vehicle_members, vehicles,
+    };
+    use diesel::Connection;
+    use std::collections::HashMap;
+
+    let remap_user = |id: Option<Uuid>| id.filter(|id| *id == original_user_id).map(|_| user_id);
+    conn.transaction(|conn| {
+        let mut restored = Vec::with_capacity(histories.len());
+        for history in histories {
+            let vehicle = diesel::insert_into(vehicles::table)
+                .values((
+                    vehicles::id.eq(Uuid::new_v4()),
+                    vehicles::brand.eq(&history.vehicle.brand),
+                    vehicles::model.eq(&history.vehicle.model),
+                    vehicles::registration.eq(&history.vehicle.registration),
+                    vehicles::registration_expiry_date.eq(history.vehicle.registration_expiry_date),
+                    vehicles::user_id.eq(user_id),
+                    vehicles::organization_id.eq(organization_id),
+                    vehicles::archived_at.eq(history.vehicle.archived_at),
+                    vehicles::vin.eq(&history.vehicle.vin),
+                    vehicles::model_year.eq(history.vehicle.model_year),
+                    vehicles::colour.eq(&history.vehicle.colour),
+                    vehicles::fuel_type.eq(&history.vehicle.fuel_type),
+                    vehicles::tank_capacity.eq(history.vehicle.tank_capacity),
+                    vehicles::odometer_unit.eq(&history.vehicle.odometer_unit),
+                    vehicles::purchase_date.eq(history.vehicle.purchase_date),
+                    vehicles::purchase_price.eq(history.vehicle.purchase_price),
+                ))
+                .get_result::<models::Vehicle>(conn)?;
+
+            diesel::insert_into(vehicle_members::table)
+                .values((
+                    vehicle_members::vehicle_id.eq(vehicle.id),
+                    vehicle_members::user_id.eq(user_id),
+                    vehicle_members::role.eq(models::VehicleRole::Owner.as_str()),
+                    vehicle_members::accepted_at.eq(vehicle.created_at),
+                ))
+                .execute(conn)?;
+
+            let mut odometer_ids = HashMap::with_capacity(history.odometer.len());
+            for reading in &history.odometer {
+                let id = diesel::insert_into(odometer::table)
+                    .values((
+                        odometer::vehicle_id.eq(vehicle.id),
+                        odometer::odometer_value.eq(reading.odometer_value),
+                        odometer::timestamp.eq(reading.timestamp),
+                        odometer::user_id.eq(remap_user(reading.user_id)),
+                        odometer::driver_id.eq(remap_user(reading.driver_id)),
+                    ))
+                    .returning(odometer::id)
+                    .get_result::<Uuid>(conn)?;
+                odometer_ids.insert(reading.id, id);
+            }
+            let odometer_id =
+                |id: Uuid| odometer_ids.get(&id).copied().ok_or(DieselError::NotFound);
+
+            for entry in &history.refuels {
+                diesel::insert_into(refuel::table)
+                    .values((
+                        refuel::vehicle_id.eq(vehicle.id),
+                        refuel::odometer_id.eq(odometer_id(entry.odometer_id)?),
+                        refuel::refuel_quantity.eq(entry.refuel_quantity),
+                        refuel::timestamp.eq(entry.timestamp),
+                        refuel::user_id.eq(remap_user(entry.user_id)),
+                        refuel::driver_id.eq(remap_user(entry.driver_id)),
+                        refuel::cost.eq(entry.cost),
+                    ))
+                    .execute(conn)?;
+            }
+
+            for trip in &history.trips {
+                diesel::insert_into(trips::table)
+                    .values((
+                        trips::vehicle_id.eq(vehicle.id),
+                        trips::start_odometer_id.eq(odometer_id(trip.start_odometer_id)?),
+                        trips::end_odometer_id.eq(odometer_id(trip.end_odometer_id)?),
+                        trips::started_at.eq(trip.started_at),
+                        trips::ended_at.eq(trip.ended_at),
+                        trips::origin.eq(&trip.origin),
+                        trips::destination.eq(&trip.destination),
+                        trips::purpose.eq(&trip.purpose),
+                        trips::category.eq(&trip.category),
+                        trips::user_id.eq(remap_user(trip.user_id)),
+                        trips::driver_id.eq(remap_user(trip.driver_id)),
+                    ))
+                    .execute(conn)?;
+            }
+
+            for session in &history.charging_sessions {
+                diesel::insert_into(charging_sessions::table)
+                    .values((
+                        charging_sessions::vehicle_id.eq(vehicle.id),
+                        charging_sessions::odometer_id.eq(odometer_id(session.odometer_id)?),
+                        charging_sessions::started_at.eq(session.started_at),
+                        charging_sessions::energy_kwh.eq(session.energy_kwh),
+                        charging_sessions::charger_type.eq(&session.charger_type),
+                        charging_sessions::start_soc.eq(session.start_soc),
+                        charging_sessions::end_soc.eq(session.end_soc),
+                        charging_sessions::duration_minutes.eq(session.duration_minutes),
+                        charging_sessions::cost.eq(session.cost),
+                        charging_sessions::location.eq(&session.location),
+                        charging_sessions::user_id.eq(remap_user(session.user_id)),
+                        charging_sessions::driver_id.eq(remap_user(session.driver_id)),
+                    ))
+                    .execute(conn)?;
+            }
+
+            for policy in &history.insurance_policies {
+                diesel::insert_into(insurance_policies::table)
+                    .values(models::NewInsurancePolicy {
+                        vehicle_id: vehicle.id,
+                        provider: policy.provider.clone(),
+                        policy_number: policy.policy_number.clone(),
+                        coverage_type: policy.coverage_type.clone(),
+                        premium: policy.premium,
+                        start_date: policy.start_date,
+                        end_date: policy.end_date,
+                        document_id: None,
+                        user_id: remap_user(policy.user_id),
+                    })
+                    .execute(conn)?;
+            }
+
+            restored.push(vehicle);
+        }
+
+        Ok::<_, DbError>(restored)
+    })
+}
+
+/// Retrieves the latest refueling entry for a specific vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Some(RefuelWithOdometer))`: The latest refueling entry.
+/// - `Ok(None)`: If no refueling entry exists.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_latest_refuel(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+) -> Result<Option<models::RefuelWithOdometer>, DbError> {
+    use crate::schema::{odometer, refuel};
+
+    let mut conn = pool.get()?;
+    Ok(refuel::table
+        .inner_join(odometer::table)
+        .filter(refuel::vehicle_id.eq(vehicle_id))
+        .order(refuel::timestamp.desc())
+        .select((
+            refuel::id,
+            refuel::vehicle_id,
+            refuel::odometer_id,
+            refuel::refuel_quantity,
+            odometer::odometer_value,
+            refuel::timestamp,
+            refuel::user_id,
+            refuel::driver_id,
+            refuel::cost,
+        ))
+        .first::<models::RefuelWithOdometer>(&mut conn)
+        .optional()?)
+}
+
+/// Retrieves time-series refueling data for a specific vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start date/time of the range.
+/// - `end`: The end date/time of the range.
+///
+/// # Returns
+/// - `Ok(Vec<RefuelWithOdometer>)`: A list of refueling entries with odometer details.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_refuel_timeseries(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<models::RefuelWithOdometer>, DbError> {
+    use crate::schema::{odometer, refuel};
+
+    let mut conn = pool.get()?;
+    Ok(refuel::table
+        .inner_join(odometer::table)
+        .filter(refuel::vehicle_id.eq(vehicle_id))
+        .filter(refuel::timestamp.between(start, end))
+        .order(refuel::timestamp.asc())
+        .select((
+            refuel::id,
+            refuel::vehicle_id,
+            refuel::odometer_id,
+            refuel::refuel_quantity,
+            odometer::odometer_value,
+            refuel::timestamp,
+            refuel::user_id,
+            refuel::driver_id,
+            refuel::cost,
+        ))
+        .load::<models::RefuelWithOdometer>(&mut conn)?)
+}
+
+/// Creates a charging session along with the odometer reading taken when it started.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user recording the session.
+/// - `details`: The session details.
+///
+/// # Returns
+/// - `Ok((ChargingSession, Decimal))`: The new session with its odometer value.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_charging_session(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+    details: &models::ChargingSessionDetails,
+) -> Result<(models::ChargingSession, Decimal), DbError> {
+    use crate::schema::{charging_sessions, odometer};
+    use diesel::{Connection, SelectableHelper};
+
+    let started_at = details.started_at.unwrap_or_else(chrono::Utc::now);
+
+    conn.transaction(|conn| {
+        let reading = diesel::insert_into(odometer::table)
+            .values((
+                odometer::vehicle_id.eq(vehicle_id),
+                odometer::odometer_value.eq(details.odometer_value),
+                odometer::timestamp.eq(started_at),
+                odometer::user_id.eq(user_id),
+                odometer::driver_id.eq(details.driver_id),
+            ))
+            .get_result::<models::Odometer>(conn)?;
+
+        let session = diesel::insert_into(charging_sessions::table)
+            .values((
+                charging_sessions::vehicle_id.eq(vehicle_id),
+                charging_sessions::odometer_id.eq(reading.id),
+                charging_sessions::started_at.eq(started_at),
+                charging_sessions::energy_kwh.eq(details.energy_kwh),
+                charging_sessions::charger_type.eq(&details.charger_type),
+                charging_sessions::start_soc.eq(details.start_soc),
+                charging_sessions::end_soc.eq(details.end_soc),
+                charging_sessions::duration_minutes.eq(details.duration_minutes),
+                charging_sessions::cost.eq(details.cost),
+                charging_sessions::location.eq(&details.location),
+                charging_sessions::user_id.eq(user_id),
+                charging_sessions::driver_id.eq(details.driver_id),
+            ))
+            .returning(models::ChargingSession::as_returning())
+            .get_result::<models::ChargingSession>(conn)?;
+
+        Ok::<_, DbError>((session, reading.odometer_value))
+    })
+}
+
+/// Retrieves the charging sessions of a vehicle that started within a time range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start of the range.
+/// - `end`: The end of the range.
+///
+/// # Returns
+/// - `Ok(Vec<(ChargingSession, Decimal)>)`: Each session with its odometer value, oldest first.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_charging_sessions(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(models::ChargingSession, Decimal)>, DbError> {
+    use crate::schema::{charging_sessions, odometer};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(charging_sessions::table
+        .inner_join(odometer::table)
+        .filter(charging_sessions::vehicle_id.eq(vehicle_id))
+        .filter(charging_sessions::started_at.between(start, end))
+        .order(charging_sessions::started_at.asc())
+        .select((
+            models::ChargingSession::as_select(),
+            odometer::odometer_value,
+        ))
+        .load::<(models::ChargingSession, Decimal)>(&mut conn)?)
+}
+
+/// Retrieves the last charging session of a vehicle that started before a given time.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `before`: The time the session must have started before.
+///
+/// # Returns
+/// - `Ok(Some((ChargingSession, Decimal)))`: The session with its odometer value.
+/// - `Ok(None)`: If the vehicle has no earlier session.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_previous_charging_session(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    before: DateTime<Utc>,
+) -> Result<Option<(models::ChargingSession, Decimal)>, DbError> {
+    use crate::schema::{charging_sessions, odometer};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(charging_sessions::table
+        .inner_join(odometer::table)
+        .filter(charging_sessions::vehicle_id.eq(vehicle_id))
+        .filter(charging_sessions::started_at.lt(before))
+        .order(charging_sessions::started_at.desc())
+        .select((
+            models::ChargingSession::as_select(),
+            odometer::odometer_value,
+        ))
+        .first::<(models::ChargingSession, Decimal)>(&mut conn)
+        .optional()?)
+}
+
+/// Deletes a charging session of a vehicle along with its odometer reading.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `session_id`: The ID of the session.
+///
+/// # Returns
+/// - `Ok(Some(ChargingSession))`: The deleted session.
+/// - `Ok(None)`: If the vehicle has no such session.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_charging_session(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    session_id: Uuid,
+) -> Result<Option<models::ChargingSession>, DbError> {
+    use crate::schema::{charging_sessions, odometer};
+    use diesel::{Connection, SelectableHelper};
+
+    conn.transaction(|conn| {
+        let session = diesel::delete(
+            charging_sessions::table
+                .filter(charging_sessions::id.eq(session_id))
+                .filter(charging_sessions::vehicle_id.eq(vehicle_id)),
+        )
+        .returning(models::ChargingSession::as_returning())
+        .get_result::<models::ChargingSession>(conn)
+        .optional()?;
+
+        if let Some(session) = &session {
+            diesel::delete(odometer::table.find(session.odometer_id)).execute(conn)?;
+        }
+
+        Ok::<_, DbError>(session)
+    })
+}
+
+/// Retrieves the driver assignments of a vehicle, newest first, with each driver's name.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Vec<(DriverAssignment, String, String)>)`: Each assignment with the driver's username and full name.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_driver_assignments(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+) -> Result<Vec<(models::DriverAssignment, String, String)>, DbError> {
+    use crate::schema::{driver_assignments, users};
+    use diesel::{JoinOnDsl, SelectableHelper};
+
+    let mut conn = pool.get()?;
+
+    Ok(driver_assignments::table
+        .inner_join(users::table.on(users::id.eq(driver_assignments::user_id)))
+        .filter(driver_assignments::vehicle_id.eq(vehicle_id))
+        .order(driver_assignments::starts_at.desc())
+        .select((
+            models::DriverAssignment::as_select(),
+            users::username,
+            users::full_name,
+        ))
+        .load::<(models::DriverAssignment, String, String)>(&mut conn)?)
+}
+
+/// Retrieves the driver assigned to a vehicle at a given time.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `at`: The point in time to look up.
+///
+/// # Returns
+/// - `Ok(Some(Uuid))`: The ID of the assigned driver.
+/// - `Ok(None)`: If no driver was assigned at that time.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_assigned_driver(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    at: DateTime<Utc>,
+) -> Result<Option<Uuid>, DbError> {
+    use crate::schema::driver_assignments::dsl;
+    use diesel::BoolExpressionMethods;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::driver_assignments
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .filter(dsl::starts_at.le(at))
+        .filter(dsl::ends_at.is_null().or(dsl::ends_at.gt(at)))
+        .order(dsl::starts_at.desc())
+        .select(dsl::user_id)
+        .first::<Uuid>(&mut conn)
+        .optional()?)
+}
+
+/// Creates a driver assignment, handing the vehicle over from an open-ended assignment.
+///
+/// An open-ended assignment that started before the new one is ended when the new one starts.
+/// Any other overlap with an existing assignment is rejected.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `assignment`: The assignment to create.
+///
+/// # Returns
+/// - `Ok(Some((DriverAssignment, Option<DriverAssignment>)))`: The new assignment, and the
+///   assignment it ended, if any.
+/// - `Ok(None)`: If the assignment overlaps an existing one; nothing is changed.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_driver_assignment(
+    conn: &mut PgConnection,
+    assignment: &models::NewDriverAssignment,
+) -> Result<Option<(models::DriverAssignment, Option<models::DriverAssignment>)>, DbError> {
+    use crate::schema::{driver_assignments::dsl, vehicles};
+    use diesel::{BoolExpressionMethods, Connection, SelectableHelper};
+
+    conn.transaction(|conn| {
+        // Serialize assignments per vehicle so concurrent requests can't both pass the overlap check
+        vehicles::table
+            .find(assignment.vehicle_id)
+            .select(vehicles::id)
+            .for_update()
+            .first::<Uuid>(conn)?;
+
+        let open = dsl::driver_assignments
+            .filter(dsl::vehicle_id.eq(assignment.vehicle_id))
+            .filter(dsl::ends_at.is_null())
+            .filter(dsl::starts_at.lt(assignment.starts_at))
+            .select(models::DriverAssignment::as_select())
+            .first::<models::DriverAssignment>(conn)
+            .optional()?;
+
+        let mut overlapping = dsl::driver_assignments
+            .filter(dsl::vehicle_id.eq(assignment.vehicle_id))
+            .filter(
+                dsl::ends_at
+                    .is_null()
+                    .or(dsl::ends_at.gt(assignment.starts_at)),
+            )
+            .into_boxed();
+        if let Some(ends_at) = assignment.ends_at {
+            overlapping = overlapping.filter(dsl::starts_at.lt(ends_at));
+        }
+        if let Some(open) = &open {
+            overlapping = overlapping.filter(dsl::id.ne(open.id));
+        }
+        if overlapping.count().get_result::<i64>(conn)? > 0 {
+            return Ok(None);
+        }
+
+        let ended = match open {
+            Some(open) => Some(
+                diesel::update(dsl::driver_assignments.find(open.id))
+                    .set(dsl::ends_at.eq(assignment.starts_at))
+                    .returning(models::DriverAssignment::as_returning())
+                    .get_result::<models::DriverAssignment>(conn)?,
+            ),
+            None => None,
+        };
+
+        let created = diesel::insert_into(dsl::driver_assignments)
+            .values(assignment)
+            .returning(models::DriverAssignment::as_returning())
+            .get_result::<models::DriverAssignment>(conn)?;
+
+        Ok::<_, DbError>(Some((created, ended)))
+    })
+}
+
+/// Deletes a driver assignment of a vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `assignment_id`: The ID of the assignment.
+///
+/// # Returns
+/// - `Ok(Some(DriverAssignment))`: The deleted assignment.
+/// - `Ok(None)`: If the vehicle has no such assignment.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_driver_assignment(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    assignment_id: Uuid,
+) -> Result<Option<models::DriverAssignment>, DbError> {
+    use crate::schema::driver_assignments::dsl;
+    use diesel::SelectableHelper;
+
+    Ok(diesel::delete(
+        dsl::driver_assignments
+            .filter(dsl::id.eq(assignment_id))
+            .filter(dsl::vehicle_id.eq(vehicle_id)),
+    )
+    .returning(models::DriverAssignment::as_returning())
+    .get_result::<models::DriverAssignment>(conn)
+    .optional()?)
+}
+
+/// Retrieves users by their IDs.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_ids`: The IDs of the users.
+///
+/// # Returns
+/// - `Ok(Vec<User>)`: The users that exist, in no particular order.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_users_by_ids(pool: &DbPool, user_ids: Vec<Uuid>) -> Result<Vec<models::User>, DbError> {
+    use crate::schema::users::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::users
+        .filter(dsl::id.eq_any(user_ids))
+        .load::<models::User>(&mut conn)?)
+}
+
+/// Retrieves the trips of a vehicle that started within a date range, with their odometer values.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start date/time of the range.
+/// - `end`: The end date/time of the range.
+///
+/// # Returns
+/// - `Ok(Vec<(Trip, Decimal, Decimal)>)`: Each trip with its start and end odometer values, oldest first.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_trips(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<(models::Trip, Decimal, Decimal)>, DbError> {
+    use crate::schema::{odometer, trips};
+    use diesel::{JoinOnDsl, SelectableHelper};
+
+    let mut conn = pool.get()?;
+    let (start_odometer, end_odometer) =
+        diesel::alias!(odometer as start_odometer, odometer as end_odometer);
+
+    Ok(trips::table
+        .inner_join(
+            start_odometer.on(trips::start_odometer_id.eq(start_odometer.field(odometer::id))),
+        )
+        .inner_join(end_odometer.on(trips::end_odometer_id.eq(end_odometer.field(odometer::id))))
+        .filter(trips::vehicle_id.eq(vehicle_id))
+        .filter(trips::started_at.between(start, end))
+        .order(trips::started_at.asc())
+        .select((
+            models::Trip::as_select(),
+            start_odometer.field(odometer::odometer_value),
+            end_odometer.field(odometer::odometer_value),
+        ))
+        .load::<(models::Trip, Decimal, Decimal)>(&mut conn)?)
+}
+
+/// Retrieves a trip of a vehicle, with its odometer values.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `trip_id`: The ID of the trip.
+///
+/// # Returns
+/// - `Ok(Some((Trip, Decimal, Decimal)))`: The trip with its start and end odometer values.
+/// - `Ok(None)`: If the vehicle has no such trip.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_trip(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    trip_id: Uuid,
+) -> Result<Option<(models::Trip, Decimal, Decimal)>, DbError> {
+    use crate::schema::{odometer, trips};
+    use diesel::{JoinOnDsl, SelectableHelper};
+
+    let mut conn = pool.get()?;
+    let (start_odometer, end_odometer) =
+        diesel::alias!(odometer as start_odometer, odometer as end_odometer);
+
+    Ok(trips::table
+        .inner_join(
+            start_odometer.on(trips::start_odometer_id.eq(start_odometer.field(odometer::id))),
+        )
+        .inner_join(end_odometer.on(trips::end_odometer_id.eq(end_odometer.field(odometer::id))))
+        .filter(trips::vehicle_id.eq(vehicle_id))
+        .filter(trips::id.eq(trip_id))
+        .select((
+            models::Trip::as_select(),
+            start_odometer.field(odometer::odometer_value),
+            end_odometer.field(odometer::odometer_value),
+        ))
+        .first::<(models::Trip, Decimal, Decimal)>(&mut conn)
+        .optional()?)
+}
+
+/// Counts the trips of a vehicle that overlap a time range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start of the range.
+/// - `end`: The end of the range.
+/// - `exclude_trip_id`: A trip to leave out, e.g. the one being updated.
+///
+/// # Returns
+/// - `Ok(i64)`: The number of overlapping trips.
+/// - `Err(DbError)`: If the query fails.
+pub fn count_overlapping_trips(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude_trip_id: Option<Uuid>,
+) -> Result<i64, DbError> {
+    use crate::schema::trips::dsl;
+
+    let mut conn = pool.get()?;
+
+    let mut query = dsl::trips
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .filter(dsl::started_at.lt(end))
+        .filter(dsl::ended_at.gt(start))
+        .into_boxed();
+    if let Some(exclude_trip_id) = exclude_trip_id {
+        query = query.filter(dsl::id.ne(exclude_trip_id));
+    }
+
+    Ok(query.count().get_result(&mut conn)?)
+}
+
+/// Retrieves the odometer readings of a vehicle around a time range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start`: The start of the range.
+/// - `end`: The end of the range.
+/// - `exclude`: Readings to leave out, e.g. those of the trip being updated.
+///
+/// # Returns
+/// - `Ok(SurroundingOdometer)`: The last reading before the range, the readings within it, and
+///   the first reading after it.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_odometer_around(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    exclude: Vec<Uuid>,
+) -> Result<models::SurroundingOdometer, DbError> {
+    use crate::schema::odometer::dsl;
+
+    let mut conn = pool.get()?;
+    let readings = || {
+        dsl::odometer
+            .filter(dsl::vehicle_id.eq(vehicle_id))
+            .filter(dsl::id.ne_all(exclude.clone()))
+            .into_boxed()
+    };
+
+    let before = readings()
+        .filter(dsl::timestamp.lt(start))
+        .order(dsl::timestamp.desc())
+        .first::<models::Odometer>(&mut conn)
+        .optional()?;
+    let within = readings()
+        .filter(dsl::timestamp.between(start, end))
+        .order(dsl::timestamp.asc())
+        .load::<models::Odometer>(&mut conn)?;
+    let after = readings()
+        .filter(dsl::timestamp.gt(end))
+        .order(dsl::timestamp.asc())
+        .first::<models::Odometer>(&mut conn)
+        .optional()?;
+
+    Ok(models::SurroundingOdometer {
+        before,
+        within,
+        after,
+    })
+}
+
+/// Creates a trip along with its start and end odometer readings.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user recording the trip.
+/// - `details`: The trip details.
+///
+/// # Returns
+/// - `Ok((Trip, Decimal, Decimal))`: The new trip with its start and end odometer values.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_trip(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    user_id: Uuid,
+    details: &models::TripDetails,
+) -> Result<(models::Trip, Decimal, Decimal), DbError> {
+    use crate::schema::{odometer, trips};
+    use diesel::{Connection, SelectableHelper};
+
+    conn.transaction(|conn| {
+        let mut reading = |value: Decimal, at: DateTime<Utc>| {
+            diesel::insert_into(odometer::table)
+                .values((
+                    odometer::vehicle_id.eq(vehicle_id),
+                    odometer::odometer_value.eq(value),
+                    odometer::timestamp.eq(at),
+                    odometer::user_id.eq(user_id),
+                    odometer::driver_id.eq(details.driver_id),
+                ))
+                .get_result::<models::Odometer>(conn)
+        };
+        let start_odometer = reading(details.start_odometer_value, details.started_at)?;
+        let end_odometer = reading(details.end_odometer_value, details.ended_at)?;
+
+        let trip = diesel::insert_into(trips::table)
+            .values((
+                trips::vehicle_id.eq(vehicle_id),
+                trips::start_odometer_id.eq(start_odometer.id),
+                trips::end_odometer_id.eq(end_odometer.id),
+                trips::started_at.eq(details.started_at),
+                trips::ended_at.eq(details.ended_at),
+                trips::origin.eq(&details.origin),
+                trips::destination.eq(&details.destination),
+                trips::purpose.eq(&details.purpose),
+                trips::category.eq(&details.category),
+                trips::user_id.eq(user_id),
+                trips::driver_id.eq(details.driver_id),
+            ))
+            .returning(models::Trip::as_returning())
+            .get_result::<models::Trip>(conn)?;
+
+        Ok::<_, DbError>((
+            trip,
+            start_odometer.odometer_value,
+            end_odometer.odometer_value,
+        ))
+    })
+}
+
+/// Updates a trip along with its start and end odometer readings.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `trip`: The trip to update.
+/// - `details`: The new trip details.
+///
+/// # Returns
+/// - `Ok((Trip, Decimal, Decimal))`: The updated trip with its start and end odometer values.
+/// - `Err(DbError)`: If the query fails.
+pub fn update_trip(
+    conn: &mut PgConnection,
+    trip: &models::Trip,
+    details: &models::TripDetails,
+) -> Result<(models::Trip, Decimal, Decimal), DbError> {
+    use crate::schema::{odometer, trips};
+    use diesel::{Connection, SelectableHelper};
+
+    let now = chrono::Utc::now();
+
+    conn.transaction(|conn| {
+        let mut reading = |id: Uuid, value: Decimal, at: DateTime<Utc>| {
+            diesel::update(odometer::table.find(id))
+                .set((
+                    odometer::odometer_value.eq(value),
+                    odometer::timestamp.eq(at),
+                    odometer::driver_id.eq(details.driver_id),
+                    odometer::updated_at.eq(now),
+                ))
+                .execute(conn)
+        };
+        reading(
+            trip.start_odometer_id,
+            details.start_odometer_value,
+            details.started_at,
+        )?;
+        reading(
+            trip.end_odometer_id,
+            details.end_odometer_value,
+            details.ended_at,
+        )?;
+
+        let updated = diesel::update(trips::table.find(trip.id))
+            .set((
+                trips::started_at.eq(details.started_at),
+                trips::ended_at.eq(details.ended_at),
+                trips::origin.eq(&details.origin),
+                trips::destination.eq(&details.destination),
+                trips::purpose.eq(&details.purpose),
+                trips::category.eq(&details.category),
+                trips::driver_id.eq(details.driver_id),
+                trips::updated_at.eq(now),
+            ))
+            .returning(models::Trip::as_returning())
+            .get_result::<models::Trip>(conn)?;
+
+        Ok::<_, DbError>((
+            updated,
+            details.start_odometer_value,
+            details.end_odometer_value,
+        ))
+    })
+}
+
+/// Deletes a trip of a vehicle along with its start and end odometer readings.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `trip_id`: The ID of the trip.
+///
+/// # Returns
+/// - `Ok(Some(Trip))`: The deleted trip.
+/// - `Ok(None)`: If the vehicle has no such trip.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_trip(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    trip_id: Uuid,
+) -> Result<Option<models::Trip>, DbError> {
+    use crate::schema::{odometer, trips};
+    use diesel::{Connection, SelectableHelper};
+
+    conn.transaction(|conn| {
+        let trip = diesel::delete(
+            trips::table
+                .filter(trips::id.eq(trip_id))
+                .filter(trips::vehicle_id.eq(vehicle_id)),
+        )
+        .returning(models::Trip::as_returning())
+        .get_result::<models::Trip>(conn)
+        .optional()?;
+
+        if let Some(trip) = &trip {
+            diesel::delete(
+                odometer::table
+                    .filter(odometer::id.eq_any([trip.start_odometer_id, trip.end_odometer_id])),
+            )
+            .execute(conn)?;
+        }
+
+        Ok::<_, DbError>(trip)
+    })
+}
+
+pub fn get_traveled_distance(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Option<models::TraveledDistance>, DbError> {
+    use diesel::sql_query;
+
+    let mut conn = pool.get()?;
+
+    let sql = r#"
+        WITH first_reading AS (
+            SELECT odometer_value
+            FROM public.odometer
+            WHERE vehicle_id = $1
+            AND timestamp BETWEEN $2 AND $3
+            ORDER BY timestamp ASC
+            LIMIT 1
+        ),
+        last_reading AS (
+            SELECT odometer_value
+            FROM public.odometer
+            WHERE vehicle_id = $1
+            AND timestamp BETWEEN $2 AND $3
+            ORDER BY timestamp DESC
+            LIMIT 1
+        )
+        SELECT
+            first_reading.odometer_value AS start_value,
+            last_reading.odometer_value AS end_value,
+            last_reading.odometer_value - first_reading.odometer_value AS traveled_distance
+        FROM
+            first_reading,
+            last_reading
+        ;
+    "#;
+
+    Ok(sql_query(sql)
+        .bind::<DieselUuid, _>(vehicle_id)
+        .bind::<Timestamptz, _>(start)
+        .bind::<Timestamptz, _>(end)
+        .get_result::<models::TraveledDistance>(&mut conn)
+        .optional()?)
+}
+
+/// Retrieves the active vehicles whose registration expires on or before a date, with the IDs of
+/// their owners.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `until`: The last expiry date to include; earlier (already expired) dates are included too.
+///
+/// # Returns
+/// - `Ok(Vec<(Vehicle, Uuid)>)`: One entry per vehicle and accepted owner.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_expiring_registrations(
+    pool: &DbPool,
+    until: NaiveDate,
+) -> Result<Vec<(models::Vehicle, Uuid)>, DbError> {
+    use crate::schema::{vehicle_members, vehicles};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(vehicles::table
+        .inner_join(vehicle_members::table)
+        .filter(vehicles::archived_at.is_null())
+        .filter(vehicles::registration_expiry_date.le(until))
+        .filter(vehicle_members::role.eq(models::VehicleRole::Owner.as_str()))
+        .filter(vehicle_members::accepted_at.is_not_null())
+        .order(vehicles::registration_expiry_date.asc())
+        .select((models::Vehicle::as_select(), vehicle_members::user_id))
+        .load(&mut conn)?)
+}
+
+/// Retrieves the active vehicles refueled since a given time, with the IDs of their owners.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `since`: The earliest refuel time to consider.
+///
+/// # Returns
+/// - `Ok(Vec<(Vehicle, Uuid)>)`: One entry per vehicle and accepted owner.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_recently_refueled_vehicles(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+) -> Result<Vec<(models::Vehicle, Uuid)>, DbError> {
+    use crate::schema::{refuel, vehicle_members, vehicles};
+    use diesel::dsl::exists;
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    Ok(vehicles::table
+        .inner_join(vehicle_members::table)
+        .filter(vehicles::archived_at.is_null())
+        .filter(exists(
+            refuel::table
+                .filter(refuel::vehicle_id.eq(vehicles::id))
+                .filter(refuel::timestamp.ge(since)),
+        ))
+        .filter(vehicle_members::role.eq(models::VehicleRole::Owner.as_str()))
+        .filter(vehicle_members::accepted_at.is_not_null())
+        .select((models::Vehicle::as_select(), vehicle_members::user_id))
+        .load(&mut conn)?)
+}
+
+/// Creates a notification and queues its delivery through the given channels, unless the
+/// recipient was already notified with the same deduplication key.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `notification`: The notification to create.
+/// - `channels`: The names of the channels to deliver it through.
+///
+/// # Returns
+/// - `Ok(Some(Notification))`: The created notification.
+/// - `Ok(None)`: If the recipient was already notified.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_notification(
+    pool: &DbPool,
+    notification: &models::NewNotification,
+    channels: &[&str],
+) -> Result<Option<models::Notification>, DbError> {
+    use crate::schema::{notification_deliveries, notifications};
+    use diesel::Connection;
+
+    let mut conn = pool.get()?;
+
+    conn.transaction(|conn| {
+        let created = diesel::insert_into(notifications::table)
+            .values(notification)
+            .on_conflict((notifications::user_id, notifications::dedup_key))
+            .do_nothing()
+            .get_result::<models::Notification>(conn)
+            .optional()?;
+
+        if let Some(created) = &created {
+            let deliveries: Vec<_> = channels
+                .iter()
+                .map(|channel| {
+                    (
+                        notification_deliveries::notification_id.eq(created.id),
+                        notification_deliveries::channel.eq(*channel),
+                    )
+                })
+                .collect();
+            diesel::insert_into(notification_deliveries::table)
+                .values(&deliveries)
+                .execute(conn)?;
+        }
+
+        Ok::<_, DbError>(created)
+    })
+}
+
+/// Retrieves a user's notifications that have not been dismissed, newest first.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the recipient.
+/// - `unread_only`: Whether to skip notifications that were read.
+/// - `limit`: The maximum number of notifications to return.
+///
+/// # Returns
+/// - `Ok(Vec<Notification>)`: The notifications.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_notifications(
+    pool: &DbPool,
+    user_id: Uuid,
+    unread_only: bool,
+    limit: i64,
+) -> Result<Vec<models::Notification>, DbError> {
+    use crate::schema::notifications::dsl;
+
+    let mut conn = pool.get()?;
+
+    let mut query = dsl::notifications
+        .filter(dsl::user_id.eq(user_id))
+        .filter(dsl::dismissed_at.is_null())
+        .into_boxed();
+    if unread_only {
+        query = query.filter(dsl::read_at.is_null());
+    }
+
+    Ok(query
+        .order(dsl::created_at.desc())
+        .limit(limit)
+        .load::<models::Notification>(&mut conn)?)
+}
+
+/// Counts a user's unread notifications that have not been dismissed.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the recipient.
+///
+/// # Returns
+/// - `Ok(i64)`: The number of unread notifications.
+/// - `Err(DbError)`: If the query fails.
+pub fn count_unread_notifications(pool: &DbPool, user_id: Uuid) -> Result<i64, DbError> {
+    use crate::schema::notifications::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::notifications
+        .filter(dsl::user_id.eq(user_id))
+        .filter(dsl::dismissed_at.is_null())
+        .filter(dsl::read_at.is_null())
+        .count()
+        .get_result(&mut conn)?)
+}
+
+/// Marks one of a user's notifications as read, keeping the time it was first read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the recipient.
+/// - `notification_id`: The ID of the notification.
+///
+/// # Returns
+/// - `Ok(Some(Notification))`: The read notification.
+/// - `Ok(None)`: If the user has no such notification, or dismissed it.
+/// - `Err(DbError)`: If the query fails.
+pub fn mark_notification_read(
+    pool: &DbPool,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> Result<Option<models::Notification>, DbError> {
+    use crate::schema::notifications::dsl;
+    use diesel::Connection;
+
+    let mut conn = pool.get()?;
+
+    conn.transaction(|conn| {
+        let target = dsl::notifications
+            .filter(dsl::id.eq(notification_id))
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::dismissed_at.is_null());
+
+        diesel::update(target.filter(dsl::read_at.is_null()))
+            .set(dsl::read_at.eq(chrono::Utc::now()))
+            .execute(conn)?;
+
+        Ok::<_, DbError>(target.first::<models::Notification>(conn).optional()?)
+    })
+}
+
+/// Marks all of a user's unread notifications as read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the recipient.
+///
+/// # Returns
+/// - `Ok(usize)`: The number of notifications marked as read.
+/// - `Err(DbError)`: If the query fails.
+pub fn mark_all_notifications_read(pool: &DbPool, user_id: Uuid) -> Result<usize, DbError> {
+    use crate::schema::notifications::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(diesel::update(
+        dsl::notifications
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::dismissed_at.is_null())
+            .filter(dsl::read_at.is_null()),
+    )
+    .set(dsl::read_at.eq(chrono::Utc::now()))
+    .execute(&mut conn)?)
+}
+
+/// Dismisses one of a user's notifications from their inbox.
+///
+/// The notification is kept, so the event it is about is not notified again.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the recipient.
+/// - `notification_id`: The ID of the notification.
+///
+/// # Returns
+/// - `Ok(true)`: If the notification was dismissed.
+/// - `Ok(false)`: If the user has no such notification, or already dismissed it.
+/// - `Err(DbError)`: If the query fails.
+pub fn dismiss_notification(
+    pool: &DbPool,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> Result<bool, DbError> {
+    use crate::schema::notifications::dsl;
+
+    let mut conn = pool.get()?;
+
+    let dismissed = diesel::update(
+        dsl::notifications
+            .filter(dsl::id.eq(notification_id))
+            .filter(dsl::user_id.eq(user_id))
+            .filter(dsl::dismissed_at.is_null()),
+    )
+    .set(dsl::dismissed_at.eq(chrono::Utc::now()))
+    .execute(&mut conn)?;
+
+    Ok(dismissed > 0)
+}
+
+/// Retrieves pending notification deliveries, oldest first, with their notification and
+/// recipient.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `limit`: The maximum number of deliveries to return.
+///
+/// # Returns
+/// - `Ok(Vec<PendingDelivery>)`: The pending deliveries.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_pending_deliveries(
+    pool: &DbPool,
+    limit: i64,
+) -> Result<Vec<models::PendingDelivery>, DbError> {
+    use crate::schema::{notification_deliveries, notifications, users};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    let rows = notification_deliveries::table
+        .inner_join(notifications::table.inner_join(users::table))
+        .filter(notification_deliveries::status.eq(models::DeliveryStatus::Pending.as_str()))
+        .order(notification_deliveries::created_at.asc())
+        .limit(limit)
+        .select((
+            models::NotificationDelivery::as_select(),
+            models::Notification::as_select(),
+            models::User::as_select(),
+        ))
+        .load::<(
+            models::NotificationDelivery,
+            models::Notification,
+            models::User,
+        )>(&mut conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(delivery, notification, recipient)| models::PendingDelivery {
+                delivery,
+                notification,
+                recipient,
+            },
+        )
+        .collect())
+}
+
+/// Records the outcome of a delivery attempt.
+///
+/// A failed attempt leaves the delivery pending, to be retried, until `max_attempts` attempts
+/// have failed.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `delivery_id`: The ID of the delivery.
+/// - `outcome`: `Ok(Sent)` if the notification was delivered, `Ok(Skipped)` if the channel cannot
+///   reach the recipient, or why delivery failed.
+/// - `max_attempts`: The number of failed attempts after which delivery is given up.
+///
+/// # Returns
+/// - `Ok(NotificationDelivery)`: The updated delivery.
+/// - `Err(DbError)`: If the query fails.
+pub fn record_delivery_attempt(
+    pool: &DbPool,
+    delivery_id: Uuid,
+    outcome: Result<models::DeliveryStatus, String>,
+    max_attempts: i32,
+) -> Result<models::NotificationDelivery, DbError> {
+    use crate::schema::notification_deliveries::dsl;
+    use diesel::Connection;
+
+    let mut conn = pool.get()?;
+
+    conn.transaction(|conn| {
+        let attempts = dsl::notification_deliveries
+            .find(delivery_id)
+            .select(dsl::attempts)
+            .for_update()
+            .first::<i32>(conn)?
+            + 1;
+
+        let (status, last_error, delivered_at) = match outcome {
+            Ok(models::DeliveryStatus::Sent) => {
+                (models::DeliveryStatus::Sent, None, Some(chrono::Utc::now()))
+            }
+            Ok(status) => (status, None, None),
+            Err(error) if attempts >= max_attempts => {
+                (models::DeliveryStatus::Failed, Some(error), None)
+            }
+            Err(error) => (models::DeliveryStatus::Pending, Some(error), None),
+        };
+
+        Ok(
+            diesel::update(dsl::notification_deliveries.find(delivery_id))
+                .set((
+                    dsl::attempts.eq(attempts),
+                    dsl::status.eq(status.as_str()),
+                    dsl::last_error.eq(last_error),
+                    dsl::delivered_at.eq(delivered_at),
+                ))
+                .get_result::<models::NotificationDelivery>(conn)?,
+        )
+    })
+}
+
+/// Inserts a new webhook.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `webhook`: The webhook to insert.
+///
+/// # Returns
+/// - `Ok(Webhook)`: The inserted webhook.
+/// - `Err(DbError)`: If the query fails.
+pub fn create_webhook(
+    conn: &mut PgConnection,
+    webhook: &models::NewWebhook,
+) -> Result<models::Webhook, DbError> {
+    use crate::schema::webhooks::dsl;
+
+    Ok(diesel::insert_into(dsl::webhooks)
+        .values(webhook)
+        .get_result::<models::Webhook>(conn)?)
+}
+
+/// Retrieves the webhooks a user configured within an organization, oldest first.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the user.
+/// - `organization_id`: The ID of the organization.
+///
+/// # Returns
+/// - `Ok(Vec<Webhook>)`: The webhooks.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_webhooks_by_user(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<Vec<models::Webhook>, DbError> {
+    use crate::schema::webhooks::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::webhooks
+        .filter(dsl::user_id.eq(user_id))
+        .filter(dsl::organization_id.eq(organization_id))
+        .order(dsl::created_at.asc())
+        .load::<models::Webhook>(&mut conn)?)
+}
+
+/// Retrieves a webhook by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+///
+/// # Returns
+/// - `Ok(Some(Webhook))`: The webhook, if it exists.
+/// - `Ok(None)`: If no webhook matches the given ID.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_webhook_by_id(
+    pool: &DbPool,
+    webhook_id: Uuid,
+) -> Result<Option<models::Webhook>, DbError> {
+    use crate::schema::webhooks::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(dsl::webhooks
+        .find(webhook_id)
+        .first::<models::Webhook>(&mut conn)
+        .optional()?)
+}
+
+/// Updates a webhook; fields passed as `None` are kept.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `webhook_id`: The ID of the webhook.
+/// - `url`: The new URL.
+/// - `events`: The new subscribed events.
+/// - `is_active`: Whether events are delivered.
+///
+/// # Returns
+/// - `Ok(Webhook)`: The updated webhook.
+/// - `Err(DbError)`: If the query fails.
+pub fn update_webhook(
+    conn: &mut PgConnection,
+    webhook_id: Uuid,
+    url: Option<String>,
+    events: Option<Vec<String>>,
+    is_active: Option<bool>,
+) -> Result<models::Webhook, DbError> {
+    use crate::schema::webhooks::dsl;
+
+    Ok(diesel::update(dsl::webhooks.find(webhook_id))
+        .set((
+            url.map(|val| dsl::url.eq(val)),
+            events.map(|val| dsl::events.eq(val)),
+            is_active.map(|val| dsl::is_active.eq(val)),
+            dsl::updated_at.eq(chrono::Utc::now()),
+        ))
+        .get_result::<models::Webhook>(conn)?)
+}
+
+/// Deletes a webhook along with its delivery log.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `webhook_id`: The ID of the webhook.
+///
+/// # Returns
+/// - `Ok(usize)`: The number of deleted webhooks.
+/// - `Err(DbError)`: If the query fails.
+pub fn delete_webhook(conn: &mut PgConnection, webhook_id: Uuid) -> Result<usize, DbError> {
+    use crate::schema::webhooks::dsl;
+
+    Ok(diesel::delete(dsl::webhooks.find(webhook_id)).execute(conn)?)
+}
+
+/// Retrieves the delivery log of a webhook, newest first.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `status`: Only return deliveries in this state, if given.
+/// - `limit`: The maximum number of deliveries to return.
+///
+/// # Returns
+/// - `Ok(Vec<WebhookDelivery>)`: The deliveries.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_webhook_deliveries(
+    pool: &DbPool,
+    webhook_id: Uuid,
+    status: Option<models::DeliveryStatus>,
+    limit: i64,
+) -> Result<Vec<models::WebhookDelivery>, DbError> {
+    use crate::schema::webhook_deliveries::dsl;
+
+    let mut conn = pool.get()?;
+
+    let mut query = dsl::webhook_deliveries
+        .filter(dsl::webhook_id.eq(webhook_id))
+        .into_boxed();
+    if let Some(status) = status {
+        query = query.filter(dsl::status.eq(status.as_str()));
+    }
+
+    Ok(query
+        .order(dsl::created_at.desc())
+        .limit(limit)
+        .load::<models::WebhookDelivery>(&mut conn)?)
+}
+
+/// Queues an event of a vehicle for delivery to every active webhook that subscribes to it,
+/// belongs to the vehicle's organization and whose user the vehicle is shared with.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle the event is about.
+/// - `event`: The event name.
+/// - `payload`: The JSON document to post.
+///
+/// # Returns
+/// - `Ok(usize)`: The number of queued deliveries.
+/// - `Err(DbError)`: If the query fails.
+pub fn enqueue_webhook_event(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    event: &str,
+    payload: &serde_json::Value,
+) -> Result<usize, DbError> {
+    use crate::schema::{vehicle_members, vehicles, webhook_deliveries, webhooks};
+    use diesel::{BoolExpressionMethods, Connection, JoinOnDsl, PgArrayExpressionMethods};
+
+    let mut conn = pool.get()?;
+
+    conn.transaction(|conn| {
+        let organization_id = vehicles::table
+            .find(vehicle_id)
+            .select(vehicles::organization_id)
+            .first::<Uuid>(conn)?;
+
+        let webhook_ids = webhooks::table
+            .inner_join(
+                vehicle_members::table.on(vehicle_members::user_id
+                    .eq(webhooks::user_id)
+                    .and(vehicle_members::vehicle_id.eq(vehicle_id))),
+            )
+            .filter(vehicle_members::accepted_at.is_not_null())
+            .filter(webhooks::organization_id.eq(organization_id))
+            .filter(webhooks::is_active.eq(true))
+            .filter(webhooks::events.contains(vec![event]))
+            .select(webhooks::id)
+            .load::<Uuid>(conn)?;
+
+        let deliveries: Vec<_> = webhook_ids
+            .into_iter()
+            .map(|webhook_id| {
+                (
+                    webhook_deliveries::webhook_id.eq(webhook_id),
+                    webhook_deliveries::event.eq(event),
+                    webhook_deliveries::payload.eq(payload),
+                )
+            })
+            .collect();
+
+        Ok::<_, DbError>(
+            diesel::insert_into(webhook_deliveries::table)
+                .values(&deliveries)
+                .execute(conn)?,
+        )
+    })
+}
+
+/// Queues an event for delivery to a single webhook, regardless of its subscriptions.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `webhook_id`: The ID of the webhook.
+/// - `event`: The event name.
+/// - `payload`: The JSON document to post.
+///
+/// # Returns
+/// - `Ok(WebhookDelivery)`: The queued delivery.
+/// - `Err(DbError)`: If the query fails.
+pub fn enqueue_webhook_delivery(
+    pool: &DbPool,
+    webhook_id: Uuid,
+    event: &str,
+    payload: &serde_json::Value,
+) -> Result<models::WebhookDelivery, DbError> {
+    use crate::schema::webhook_deliveries::dsl;
+
+    let mut conn = pool.get()?;
+
+    Ok(diesel::insert_into(dsl::webhook_deliveries)
+        .values((
+            dsl::webhook_id.eq(webhook_id),
+            dsl::event.eq(event),
+            dsl::payload.eq(payload),
+        ))
+        .get_result::<models::WebhookDelivery>(&mut conn)?)
+}
+
+/// Retrieves the pending deliveries to active webhooks that are due, longest waiting first.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `limit`: The maximum number of deliveries to return.
+///
+/// # Returns
+/// - `Ok(Vec<DueWebhookDelivery>)`: The due deliveries with their webhook.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_due_webhook_deliveries(
+    pool: &DbPool,
+    limit: i64,
+) -> Result<Vec<models::DueWebhookDelivery>, DbError> {
+    use crate::schema::{webhook_deliveries, webhooks};
+    use diesel::SelectableHelper;
+
+    let mut conn = pool.get()?;
+
+    let rows = webhook_deliveries::table
+        .inner_join(webhooks::table)
+        .filter(webhook_deliveries::status.eq(models::DeliveryStatus::Pending.as_str()))
+        .filter(webhook_deliveries::next_attempt_at.le(chrono::Utc::now()))
+        .filter(webhooks::is_active.eq(true))
+        .order(webhook_deliveries::next_attempt_at.asc())
+        .limit(limit)
+        .select((
+            models::WebhookDelivery::as_select(),
+            models::Webhook::as_select(),
+        ))
+        .load::<(models::WebhookDelivery, models::Webhook)>(&mut conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(delivery, webhook)| models::DueWebhookDelivery { delivery, webhook })
+        .collect())
+}
+
+/// Records the outcome of a webhook delivery attempt.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `delivery_id`: The ID of the delivery.
+/// - `attempt`: The response status and error of the attempt, and when to retry a failure.
+///
+/// # Returns
+/// - `Ok(WebhookDelivery)`: The updated delivery: sent, pending until `retry_at`, or failed.
+/// - `Err(DbError)`: If the query fails.
+pub fn record_webhook_attempt(
+    pool: &DbPool,
+    delivery_id: Uuid,
+    attempt: models::WebhookAttempt,
+) -> Result<models::WebhookDelivery, DbError> {
+    use crate::schema::webhook_deliveries::dsl;
+
+    let mut conn = pool.get()?;
+
+    let (status, delivered_at) = match (&attempt.error, attempt.retry_at) {
+        (None, _) => (models::DeliveryStatus::Sent, Some(chrono::Utc::now())),
+        (Some(_), Some(_)) => (models::DeliveryStatus::Pending, None),
+        (Some(_), None) => (models::DeliveryStatus::Failed, None),
+    };
+
+    Ok(diesel::update(dsl::webhook_deliveries.find(delivery_id))
+        .set((
+            dsl::attempts.eq(dsl::attempts + 1),
+            dsl::status.eq(status.as_str()),
+            attempt.retry_at.map(|val| dsl::next_attempt_at.eq(val)),
+            dsl::response_status.eq(attempt.response_status),
+            dsl::last_error.eq(attempt.error),
+            dsl::delivered_at.eq(delivered_at),
+        ))
+        .get_result::<models::WebhookDelivery>(&mut conn)?)
+}
+
+/// Checks whether a refuel belongs to a vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `vehicle_id`: The ID of the vehicle.
+/// - `refuel_id`: The ID of the refuel.
 ///
 /// # Returns
-/// - `Ok(Some(Odometer))`: The latest odometer entry.
-/// - `Ok(None)`: If no odometer entry exists.
+/// - `Ok(bool)`: Whether the vehicle has such a refuel.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_latest_odometer(
+pub fn refuel_belongs_to_vehicle(
     pool: &DbPool,
     vehicle_id: Uuid,
-) -> Result<Option<models::Odometer>, DbError> {
-    use crate::schema::odometer::dsl;
+    refuel_id: Uuid,
+) -> Result<bool, DbError> {
+    use crate::schema::refuel::dsl;
 
     let mut conn = pool.get()?;
-    Ok(dsl::odometer
-        .filter(dsl::vehicle_id.eq(vehicle_id))
-        .order(dsl::timestamp.desc())
-        .first::<models::Odometer>(&mut conn)
-        .optional()?)
+
+    Ok(diesel::select(diesel::dsl::exists(
+        dsl::refuel
+            .filter(dsl::id.eq(refuel_id))
+            .filter(dsl::vehicle_id.eq(vehicle_id)),
+    ))
+    .get_result::<bool>(&mut conn)?)
 }
 
-/// Retrieves time-series odometer data for a specific vehicle within a date range.
+/// Inserts the metadata of an uploaded document.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `document`: The document to insert.
+///
+/// # Returns
+/// - `Ok(Document)`: The inserted document.
+/// - `Err(DbError)`: If the insert fails.
+pub fn create_document(
+    conn: &mut PgConnection,
+    document: models::NewDocument,
+) -> Result<models::Document, DbError> {
+    use crate::schema::documents::dsl;
+    use diesel::SelectableHelper;
+
+    Ok(diesel::insert_into(dsl::documents)
+        .values(&document)
+        .returning(models::Document::as_returning())
+        .get_result::<models::Document>(conn)?)
+}
+
+/// Retrieves the documents of a vehicle, newest first.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `start`: The start date/time of the range.
-/// - `end`: The end date/time of the range.
+/// - `refuel_id`: Only documents attached to this refuel, if given.
 ///
 /// # Returns
-/// - `Ok(Vec<Odometer>)`: A list of odometer entries.
+/// - `Ok(Vec<Document>)`: The documents.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_odometer_timeseries(
+pub fn get_documents(
     pool: &DbPool,
     vehicle_id: Uuid,
-    start: NaiveDateTime,
-    end: NaiveDateTime,
-) -> Result<Vec<models::Odometer>, DbError> {
-    use crate::schema::odometer::dsl;
+    refuel_id: Option<Uuid>,
+) -> Result<Vec<models::Document>, DbError> {
+    use crate::schema::documents::dsl;
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
-    Ok(dsl::odometer
+    let mut query = dsl::documents
         .filter(dsl::vehicle_id.eq(vehicle_id))
-        .filter(dsl::timestamp.between(start, end))
-        .order(dsl::timestamp.asc())
-        .load::<models::Odometer>(&mut conn)?)
+        .into_boxed();
+    if let Some(refuel_id) = refuel_id {
+        query = query.filter(dsl::refuel_id.eq(refuel_id));
+    }
+
+    Ok(query
+        .order(dsl::created_at.desc())
+        .select(models::Document::as_select())
+        .load::<models::Document>(&mut conn)?)
 }
 
-/// Creates a new refueling entry for a specific vehicle, along with an associated odometer entry.
+/// Retrieves a document of a vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `refuel_quantity`: The quantity of fuel.
-/// - `odometer_value`: The odometer reading.
-/// - `timestamp`: Optional timestamp for the refueling event.
+/// - `document_id`: The ID of the document.
 ///
 /// # Returns
-/// - `Ok(RefuelWithOdometer)`: The newly created refueling entry with odometer details.
+/// - `Ok(Some(Document))`: The document.
+/// - `Ok(None)`: If the vehicle has no such document.
 /// - `Err(DbError)`: If the query fails.
-pub fn create_new_refuel(
+pub fn get_document(
     pool: &DbPool,
     vehicle_id: Uuid,
-    refuel_quantity: f32,
-    odometer_value: f32,
-    timestamp: Option<NaiveDateTime>,
-) -> Result<models::RefuelWithOdometer, DbError> {
-    use crate::schema::{odometer, refuel};
-    use diesel::prelude::*;
+    document_id: Uuid,
+) -> Result<Option<models::Document>, DbError> {
+    use crate::schema::documents::dsl;
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
 
-    conn.transaction(|conn| {
-        let new_odometer = diesel::insert_into(odometer::table)
-            .values((
-                odometer::vehicle_id.eq(vehicle_id),
-                odometer::odometer_value.eq(odometer_value),
-                timestamp.map(|ts| odometer::timestamp.eq(ts)),
-            ))
-            .get_result::<models::Odometer>(conn)?;
+    Ok(dsl::documents
+        .filter(dsl::id.eq(document_id))
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .select(models::Document::as_select())
+        .first::<models::Document>(&mut conn)
+        .optional()?)
+}
 
-        let new_refuel = diesel::insert_into(refuel::table)
-            .values((
-                refuel::vehicle_id.eq(vehicle_id),
-                refuel::odometer_id.eq(new_odometer.id),
-                refuel::refuel_quantity.eq(refuel_quantity),
-                timestamp.map(|ts| refuel::timestamp.eq(ts)),
-            ))
-            .get_result::<models::Refuel>(conn)?;
-
-        let result = models::RefuelWithOdometer {
-            id: new_refuel.id,
-            vehicle_id: new_refuel.vehicle_id,
-            odometer_id: new_refuel.odometer_id,
-            refuel_quantity: new_refuel.refuel_quantity,
-            odometer_value: new_odometer.odometer_value,
-            timestamp: new_refuel.timestamp,
-        };
+/// Deletes the metadata of a document of a vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `document_id`: The ID of the document.
+///
+/// # Returns
+/// - `Ok(Some(Document))`: The deleted document.
+/// - `Ok(None)`: If the vehicle has no such document.
+/// - `Err(DbError)`: If the delete fails.
+pub fn delete_document(
+    conn: &mut PgConnection,
+    vehicle_id: Uuid,
+    document_id: Uuid,
+) -> Result<Option<models::Document>, DbError> {
+    use crate::schema::documents::dsl;
+    use diesel::SelectableHelper;
 
-        Ok::<models::RefuelWithOdometer, DbError>(result)
-    })
-    .map_err(DbError::from)
+    Ok(diesel::delete(
+        dsl::documents
+            .filter(dsl::id.eq(document_id))
+            .filter(dsl::vehicle_id.eq(vehicle_id)),
+    )
+    .returning(models::Document::as_returning())
+    .get_result::<models::Document>(conn)
+    .optional()?)
 }
 
-/// Retrieves the latest refueling entry for a specific vehicle.
+/// Inserts an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `policy`: The policy to insert.
+///
+/// # Returns
+/// - `Ok(InsurancePolicy)`: The inserted policy.
+/// - `Err(DbError)`: If the insert fails.
+pub fn create_insurance_policy(
+    conn: &mut PgConnection,
+    policy: models::NewInsurancePolicy,
+) -> Result<models::InsurancePolicy, DbError> {
+    use crate::schema::insurance_policies::dsl;
+    use diesel::SelectableHelper;
+
+    Ok(diesel::insert_into(dsl::insurance_policies)
+        .values(&policy)
+        .returning(models::InsurancePolicy::as_returning())
+        .get_result::<models::InsurancePolicy>(conn)?)
+}
+
+/// Retrieves the insurance policies of a vehicle, latest-ending first.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `vehicle_id`: The ID of the vehicle.
 ///
 /// # Returns
-/// - `Ok(Some(RefuelWithOdometer))`: The latest refueling entry.
-/// - `Ok(None)`: If no refueling entry exists.
+/// - `Ok(Vec<InsurancePolicy>)`: The policies.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_latest_refuel(
+pub fn get_insurance_policies(
     pool: &DbPool,
     vehicle_id: Uuid,
-) -> Result<Option<models::RefuelWithOdometer>, DbError> {
-    use crate::schema::{odometer, refuel};
+) -> Result<Vec<models::InsurancePolicy>, DbError> {
+    use crate::schema::insurance_policies::dsl;
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
-    Ok(refuel::table
-        .inner_join(odometer::table)
-        .filter(refuel::vehicle_id.eq(vehicle_id))
-        .order(refuel::timestamp.desc())
-        .select((
-            refuel::id,
-            refuel::vehicle_id,
-            refuel::odometer_id,
-            refuel::refuel_quantity,
-            odometer::odometer_value,
-            refuel::timestamp,
-        ))
-        .first::<models::RefuelWithOdometer>(&mut conn)
-        .optional()?)
+
+    Ok(dsl::insurance_policies
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .order((dsl::end_date.desc(), dsl::start_date.desc()))
+        .select(models::InsurancePolicy::as_select())
+        .load::<models::InsurancePolicy>(&mut conn)?)
 }
 
-/// Retrieves time-series refueling data for a specific vehicle within a date range.
+/// Retrieves an insurance policy of a vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `start`: The start date/time of the range.
-/// - `end`: The end date/time of the range.
+/// - `policy_id`: The ID of the policy.
 ///
 /// # Returns
-/// - `Ok(Vec<RefuelWithOdometer>)`: A list of refueling entries with odometer details.
+/// - `Ok(Some(InsurancePolicy))`: The policy.
+/// - `Ok(None)`: If the vehicle has no such policy.
 /// - `Err(DbError)`: If the query fails.
-pub fn get_refuel_timeseries(
+pub fn get_insurance_policy(
     pool: &DbPool,
     vehicle_id: Uuid,
-    start: NaiveDateTime,
-    end: NaiveDateTime,
-) -> Result<Vec<models::RefuelWithOdometer>, DbError> {
-    use crate::schema::{odometer, refuel};
+    policy_id: Uuid,
+) -> Result<Option<models::InsurancePolicy>, DbError> {
+    use crate::schema::insurance_policies::dsl;
+    use diesel::SelectableHelper;
 
     let mut conn = pool.get()?;
-    Ok(refuel::table
-        .inner_join(odometer::table)
-        .filter(refuel::vehicle_id.eq(vehicle_id))
-        .filter(refuel::timestamp.between(start, end))
-        .order(refuel::timestamp.asc())
-        .select((
-            refuel::id,
-            refuel::vehicle_id,
-            refuel::odometer_id,
-            refuel::refuel_quantity,
-            odometer::odometer_value,
-            refuel::timestamp,
-        ))
-        .load::<models::RefuelWithOdometer>(&mut conn)?)
+
+    Ok(dsl::insurance_policies
+        .filter(dsl::id.eq(policy_id))
+        .filter(dsl::vehicle_id.eq(vehicle_id))
+        .select(models::InsurancePolicy::as_select())
+        .first::<models::InsurancePolicy>(&mut conn)
+        .optional()?)
 }
 
-pub fn get_traveled_distance(
-    pool: &DbPool,
+/// Replaces the details of an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `policy_id`: The ID of the policy.
+/// - `details`: The updated details; `vehicle_id` selects the vehicle, and `user_id` is kept.
+///
+/// # Returns
+/// - `Ok(Some(InsurancePolicy))`: The updated policy.
+/// - `Ok(None)`: If the vehicle has no such policy.
+/// - `Err(DbError)`: If the update fails.
+pub fn update_insurance_policy(
+    conn: &mut PgConnection,
+    policy_id: Uuid,
+    details: models::NewInsurancePolicy,
+) -> Result<Option<models::InsurancePolicy>, DbError> {
+    use crate::schema::insurance_policies::dsl;
+    use diesel::SelectableHelper;
+
+    Ok(diesel::update(
+        dsl::insurance_policies
+            .filter(dsl::id.eq(policy_id))
+            .filter(dsl::vehicle_id.eq(details.vehicle_id)),
+    )
+    .set((
+        dsl::provider.eq(details.provider),
+        dsl::policy_number.eq(details.policy_number),
+        dsl::coverage_type.eq(details.coverage_type),
+        dsl::premium.eq(details.premium),
+        dsl::start_date.eq(details.start_date),
+        dsl::end_date.eq(details.end_date),
+        dsl::document_id.eq(details.document_id),
+        dsl::updated_at.eq(chrono::Utc::now()),
+    ))
+    .returning(models::InsurancePolicy::as_returning())
+    .get_result::<models::InsurancePolicy>(conn)
+    .optional()?)
+}
+
+/// Deletes an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `conn`: Database connection.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `policy_id`: The ID of the policy.
+///
+/// # Returns
+/// - `Ok(Some(InsurancePolicy))`: The deleted policy.
+/// - `Ok(None)`: If the vehicle has no such policy.
+/// - `Err(DbError)`: If the delete fails.
+pub fn delete_insurance_policy(
+    conn: &mut PgConnection,
     vehicle_id: Uuid,
-    start_date: NaiveDate,
-    end_date: NaiveDate,
-) -> Result<Option<models::TraveledDistance>, DbError> {
-    use diesel::sql_query;
+    policy_id: Uuid,
+) -> Result<Option<models::InsurancePolicy>, DbError> {
+    use crate::schema::insurance_policies::dsl;
+    use diesel::SelectableHelper;
 
-    let mut conn = pool.get()?;
+    Ok(diesel::delete(
+        dsl::insurance_policies
+            .filter(dsl::id.eq(policy_id))
+            .filter(dsl::vehicle_id.eq(vehicle_id)),
+    )
+    .returning(models::InsurancePolicy::as_returning())
+    .get_result::<models::InsurancePolicy>(conn)
+    .optional()?)
+}
 
-    let sql = r#"
-        WITH first_reading AS (
-            SELECT odometer_value
-            FROM public.odometer
-            WHERE vehicle_id = $1
-            AND timestamp >= $2::timestamptz
-            AND timestamp < ($3::timestamptz + interval '1 day')
-            ORDER BY timestamp ASC
-            LIMIT 1
-        ),
-        last_reading AS (
-            SELECT odometer_value
-            FROM public.odometer
-            WHERE vehicle_id = $1
-            AND timestamp >= $2::timestamptz
-            AND timestamp < ($3::timestamptz + interval '1 day')
-            ORDER BY timestamp DESC
-            LIMIT 1
-        )
-        SELECT
-            first_reading.odometer_value AS start_value,
-            last_reading.odometer_value AS end_value,
-            last_reading.odometer_value - first_reading.odometer_value AS traveled_distance
-        FROM
-            first_reading,
-            last_reading
-        ;
-    "#;
+/// Retrieves the last day covered by the latest-ending insurance policy of each vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_ids`: The IDs of the vehicles.
+///
+/// # Returns
+/// - `Ok(Vec<(Uuid, Option<NaiveDate>)>)`: The vehicles with at least one policy, with the end
+///   date of their latest-ending policy.
+/// - `Err(DbError)`: If the query fails.
+pub fn get_insurance_end_dates(
+    pool: &DbPool,
+    vehicle_ids: Vec<Uuid>,
+) -> Result<Vec<(Uuid, Option<NaiveDate>)>, DbError> {
+    use crate::schema::insurance_policies::dsl;
 
-    let start_date_bind = start_date.and_hms_opt(0, 0, 0).ok_or(DbError::StdError(
-        "Error converting NaiveDate to NaiveDateTime".to_string(),
-    ))?;
-    let end_date_bind = end_date.and_hms_opt(0, 0, 0).ok_or(DbError::StdError(
-        "Error converting NaiveDate to NaiveDateTime".to_string(),
-    ))?;
+    let mut conn = pool.get()?;
 
-    Ok(sql_query(sql)
-        .bind::<DieselUuid, _>(vehicle_id)
-        .bind::<Timestamptz, _>(start_date_bind)
-        .bind::<Timestamptz, _>(end_date_bind)
-        .get_result::<models::TraveledDistance>(&mut conn)
-        .optional()?)
+    Ok(dsl::insurance_policies
+        .filter(dsl::vehicle_id.eq_any(vehicle_ids))
+        .group_by(dsl::vehicle_id)
+        .select((dsl::vehicle_id, diesel::dsl::max(dsl::end_date)))
+        .load::<(Uuid, Option<NaiveDate>)>(&mut conn)?)
 }
@@ -3,8 +3,10 @@ use tera::{Context, Tera};
 use uuid::Uuid;
 
 use crate::middleware::AuthenticatedRequest;
-use crate::models::Vehicle;
-use crate::services;
+use crate::models::VehicleRole;
+use crate::requests::{ReportQuery, VehicleListQuery};
+use crate::responses::{OrganizationWithRole, VehicleInvitation, VehicleWithRole};
+use crate::services::{self, ServiceError};
 
 /// Renders the login page.
 ///
@@ -17,10 +19,28 @@ use crate::services;
 ///
 /// # Returns
 /// An `HttpResponse` containing the rendered login page.
-pub async fn render_login(tera: web::Data<Tera>, base_url: web::Data<String>) -> impl Responder {
+pub async fn render_login(
+    tera: web::Data<Tera>,
+    base_url: web::Data<String>,
+    pool: web::Data<crate::DbPool>,
+) -> impl Responder {
     let mut context = Context::new();
     context.insert("base_url", &base_url.as_str());
 
+    // Send operators to the first-run setup until an administrator exists
+    match services::needs_setup(&pool).await {
+        Ok(true) => {
+            return HttpResponse::SeeOther()
+                .insert_header(("Location", format!("{}/setup", base_url.as_str())))
+                .finish();
+        }
+        Ok(false) => {}
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(format!("Error checking setup state: {}", e));
+        }
+    }
+
     match tera.render("login.html", &context) {
         Ok(rendered) => HttpResponse::Ok().content_type("text/html").body(rendered),
         Err(e) => {
@@ -29,15 +49,61 @@ pub async fn render_login(tera: web::Data<Tera>, base_url: web::Data<String>) ->
     }
 }
 
+/// Renders the first-run setup page used to create the initial administrator.
+///
+/// Once an active administrator exists, the page redirects to the login page instead.
+///
+/// # Arguments
+/// * `tera` - Shared instance of the Tera templating engine.
+/// * `base_url` - The base URL for API requests or asset paths.
+/// * `pool` - Database connection pool.
+///
+/// # Returns
+/// An `HttpResponse` containing the rendered setup page.
+pub async fn render_setup(
+    tera: web::Data<Tera>,
+    base_url: web::Data<String>,
+    pool: web::Data<crate::DbPool>,
+) -> impl Responder {
+    let mut context = Context::new();
+    context.insert("base_url", &base_url.as_str());
+
+    match services::needs_setup(&pool).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return HttpResponse::SeeOther()
+                .insert_header(("Location", format!("{}/", base_url.as_str())))
+                .finish();
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(format!("Error checking setup state: {}", e));
+        }
+    }
+
+    let token_required = std::env::var("SETUP_TOKEN")
+        .map(|token| !token.is_empty())
+        .unwrap_or(false);
+    context.insert("setup_token_required", &token_required);
+
+    match tera.render("setup.html", &context) {
+        Ok(rendered) => HttpResponse::Ok().content_type("text/html").body(rendered),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(format!("Error rendering template: {e}"))
+        }
+    }
+}
+
 /// Renders the home (landing) page showing the user's vehicles in a grid layout.
 ///
 /// This handler uses the Tera templating engine to render the `home.html` template.
-/// Vehicle data is fetched from the database and passed to the template.
-/// A dynamic base URL is passed to the template via the `base_url` context variable.
+/// The user's organizations, the vehicles of the active organization and pending vehicle
+/// invitations are fetched from the database and passed to the template. A dynamic base URL is passed to the template via the `base_url` context variable.
 ///
 /// # Arguments
 /// * `tera` - Shared instance of the Tera templating engine.
 /// * `base_url` - The base URL for API requests or asset paths.
+/// * `query` - Listing options; archived vehicles are shown with `include_archived=true`.
 /// * `req` - The authenticated request to extract the user ID.
 ///
 /// # Returns
@@ -46,10 +112,12 @@ pub async fn render_home(
     tera: web::Data<Tera>,
     base_url: web::Data<String>,
     pool: web::Data<crate::DbPool>,
+    query: web::Query<VehicleListQuery>,
     req: HttpRequest,
 ) -> impl Responder {
     let mut context = Context::new();
     context.insert("base_url", &base_url.as_str());
+    context.insert("include_archived", &query.include_archived);
 
     // Get authenticated user ID from request extensions
     let user_id = match req.authenticated_user_id() {
@@ -59,17 +127,60 @@ pub async fn render_home(
         }
     };
 
-    // Fetch vehicles for the user
-    let vehicles: Vec<Vehicle> = match services::get_vehicles_by_user_id(&pool, user_id).await {
-        Ok(v) => v,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Error fetching vehicles: {}", e));
-        }
+    // Fetch the user's organizations for the switcher
+    let organization_id = req.active_organization_id();
+    let organizations: Vec<OrganizationWithRole> =
+        match services::get_organizations(&pool, user_id, organization_id).await {
+            Ok(o) => o,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(format!("Error fetching organizations: {}", e));
+            }
+        };
+
+    // Fetch vehicles for the user within the active organization
+    let vehicles: Vec<VehicleWithRole> = match organization_id {
+        Some(organization_id) => match services::get_vehicles_by_user_id(
+            &pool,
+            user_id,
+            organization_id,
+            query.include_archived,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(format!("Error fetching vehicles: {}", e));
+            }
+        },
+        None => Vec::new(),
     };
 
+    // Fetch vehicles other users have invited this user to
+    let invitations: Vec<VehicleInvitation> =
+        match services::get_pending_invitations(&pool, user_id).await {
+            Ok(i) => i,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(format!("Error fetching invitations: {}", e));
+            }
+        };
+
+    // Show values in the user's units
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let vehicles: Vec<VehicleWithRole> = vehicles
+        .into_iter()
+        .map(|v| units.vehicle_with_role(v))
+        .collect();
+
     // Insert vehicles into the context for rendering
     context.insert("vehicles", &vehicles);
+    context.insert("preferences", &services::preferences_response(preferences));
+    context.insert("invitations", &invitations);
+    context.insert("organizations", &organizations);
+    context.insert("user_id", &user_id);
 
     // Render the home page with vehicle data
     match tera.render("home.html", &context) {
@@ -83,7 +194,8 @@ pub async fn render_home(
 /// Renders the refuel page where users can record refueling events.
 ///
 /// This handler uses the Tera templating engine to render the `refuel.html` template.
-/// A list of vehicles is fetched from the database for the dropdown selection.
+/// The vehicles the user may record refuels for (owner or editor) are fetched from the database
+/// for the dropdown selection.
 ///
 /// # Arguments
 /// * `tera` - Shared instance of the Tera templating engine.
@@ -110,17 +222,34 @@ pub async fn render_refuel(
         }
     };
 
-    // Fetch vehicles for the user
-    let vehicles: Vec<Vehicle> = match services::get_vehicles_by_user_id(&pool, user_id).await {
-        Ok(v) => v,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Error fetching vehicles: {}", e));
-        }
+    // Vehicles are scoped to the session's active organization
+    let organization_id = match req.active_organization_id() {
+        Some(oid) => oid,
+        None => return HttpResponse::Forbidden().body("No active organization."),
     };
 
+    // Fetch vehicles the user may record refuels for
+    let vehicles: Vec<VehicleWithRole> =
+        match services::get_vehicles_by_user_id(&pool, user_id, organization_id, false).await {
+            Ok(v) => v
+                .into_iter()
+                .filter(|v| v.role != VehicleRole::Viewer.as_str())
+                .collect(),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(format!("Error fetching vehicles: {}", e));
+            }
+        };
+
     // Insert vehicles into the context for rendering
+    let preferences = req.user_preferences();
+    let units = preferences.units();
+    let vehicles: Vec<VehicleWithRole> = vehicles
+        .into_iter()
+        .map(|v| units.vehicle_with_role(v))
+        .collect();
     context.insert("vehicles", &vehicles);
+    context.insert("preferences", &services::preferences_response(preferences));
 
     // Render the refuel page with vehicle data
     match tera.render("refuel.html", &context) {
@@ -142,24 +271,35 @@ pub async fn render_vehicle(
     context.insert("base_url", &base_url.as_str());
 
     // Get authenticated user ID from request extensions
-    let _ = match req.authenticated_user_id() {
+    let user_id = match req.authenticated_user_id() {
         Some(uid) => uid,
         None => {
             return HttpResponse::Unauthorized().body("Unauthorized access. Please log in again.");
         }
     };
 
-    // Fetch vehicle data for the user
-    let vehicle = match services::get_vehicle_by_id(&pool, *vehicle_id).await {
-        Ok(v) => v,
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(format!("Error fetching vehicles: {}", e));
-        }
+    // Vehicles are scoped to the session's active organization
+    let organization_id = match req.active_organization_id() {
+        Some(oid) => oid,
+        None => return HttpResponse::Forbidden().body("No active organization."),
     };
 
+    // Fetch vehicle data for the user, provided the vehicle is shared with them
+    let vehicle =
+        match services::get_vehicle_for_user(&pool, user_id, organization_id, *vehicle_id).await {
+            Ok(v) => v,
+            Err(ServiceError::NotFound(msg)) => return HttpResponse::NotFound().body(msg),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(format!("Error fetching vehicles: {}", e));
+            }
+        };
+
     // Insert vehicles into the context for rendering
-    context.insert("vehicle", &vehicle);
+    let preferences = req.user_preferences();
+    context.insert("vehicle", &preferences.units().vehicle_with_role(vehicle));
+    context.insert("preferences", &services::preferences_response(preferences));
+    context.insert("user_id", &user_id);
 
     // Render the refuel page with vehicle data
     match tera.render("vehicle.html", &context) {
@@ -169,3 +309,74 @@ pub async fn render_vehicle(
         }
     }
 }
+
+/// Renders the printable mileage report of a vehicle.
+///
+/// The report is built from the `period`, `start_date` and `end_date` query parameters and is
+/// laid out to be printed (or saved as PDF) from the browser.
+///
+/// # Arguments
+/// * `tera` - Shared instance of the Tera templating engine.
+/// * `base_url` - The base URL for API requests or asset paths.
+/// * `pool` - The database connection pool.
+/// * `req` - The authenticated request to extract the user ID.
+/// * `vehicle_id` - The ID of the vehicle.
+/// * `query` - The period length and the range of the report.
+///
+/// # Returns
+/// An `HttpResponse` containing the rendered report page.
+pub async fn render_report(
+    tera: web::Data<Tera>,
+    base_url: web::Data<String>,
+    pool: web::Data<crate::DbPool>,
+    req: HttpRequest,
+    vehicle_id: web::Path<Uuid>,
+    query: web::Query<ReportQuery>,
+) -> impl Responder {
+    let mut context = Context::new();
+    context.insert("base_url", &base_url.as_str());
+
+    // Get authenticated user ID from request extensions
+    let user_id = match req.authenticated_user_id() {
+        Some(uid) => uid,
+        None => {
+            return HttpResponse::Unauthorized().body("Unauthorized access. Please log in again.");
+        }
+    };
+
+    // Vehicles are scoped to the session's active organization
+    let organization_id = match req.active_organization_id() {
+        Some(oid) => oid,
+        None => return HttpResponse::Forbidden().body("No active organization."),
+    };
+
+    let preferences = req.user_preferences();
+    let report = match services::get_mileage_report(
+        &pool,
+        user_id,
+        organization_id,
+        *vehicle_id,
+        query.into_inner(),
+        preferences.timezone(),
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(ServiceError::NotFound(msg)) => return HttpResponse::NotFound().body(msg),
+        Err(ServiceError::ValidationError(msg)) => return HttpResponse::BadRequest().body(msg),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(format!("Error building report: {}", e));
+        }
+    };
+
+    context.insert("report", &preferences.units().mileage_report(report));
+    context.insert("preferences", &services::preferences_response(preferences));
+
+    match tera.render("report.html", &context) {
+        Ok(rendered) => HttpResponse::Ok().content_type("text/html").body(rendered),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(format!("Error rendering template: {e}"))
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
 use serde::Deserialize;
 
+use crate::models::{nullable, timestamp};
+
 /// Represents a login request with credentials provided by the user.
 ///
 /// This struct is used to parse incoming login requests, containing:
@@ -17,10 +20,14 @@ pub struct LoginRequest {
 
 /// Represents a request to create a new user, containing the necessary details for registration.
 ///
-/// This struct is used when a superuser creates a new user, and it contains:
+/// This struct is used when an administrator creates a new user in their active organization,
+/// and it contains:
 /// - `username`: Desired unique username for the new user.
 /// - `password`: Plain-text password, which will be hashed before storage.
 /// - `full_name`: The full name of the user, for display and identification purposes.
+/// - `is_admin`: Whether the new user should be a global administrator (defaults to `false`).
+/// - `organization_admin`: Whether the new user administers the organization (defaults to `false`).
+/// - `email`: Optional address for email notifications.
 #[derive(Deserialize)]
 pub struct NewUserRequest {
     /// Desired unique username for the new user.
@@ -29,6 +36,64 @@ pub struct NewUserRequest {
     pub password: String,
     /// Full name of the user, used for display and identification purposes.
     pub full_name: String,
+    /// Whether the new user should have administrative privileges.
+    #[serde(default)]
+    pub is_admin: bool,
+    /// Whether the new user should administer the organization they are created in.
+    #[serde(default)]
+    pub organization_admin: bool,
+    /// Optional address for email notifications.
+    pub email: Option<String>,
+}
+
+/// Represents a request to change the authenticated user's email address.
+///
+/// - `email`: The new address, or `null` to stop email notifications.
+#[derive(Deserialize)]
+pub struct UpdateEmailRequest {
+    /// The new address, if any.
+    pub email: Option<String>,
+}
+
+/// Represents a request to change the authenticated user's unit and format preferences; omitted
+/// fields are left unchanged.
+///
+/// - `distance_unit`: `km` or `mi`.
+/// - `volume_unit`: `l`, `us_gal` or `imp_gal`.
+/// - `currency`: ISO 4217 currency code, e.g. `EUR`.
+/// - `date_format`: `iso`, `dmy` or `mdy`.
+/// - `timezone`: IANA timezone name, e.g. `Europe/Berlin`; days of date ranges are evaluated in it.
+#[derive(Deserialize)]
+pub struct UpdatePreferencesRequest {
+    /// Updated distance unit (optional).
+    pub distance_unit: Option<String>,
+    /// Updated volume unit (optional).
+    pub volume_unit: Option<String>,
+    /// Updated currency (optional).
+    pub currency: Option<String>,
+    /// Updated date format (optional).
+    pub date_format: Option<String>,
+    /// Updated timezone (optional).
+    pub timezone: Option<String>,
+}
+
+/// Represents the first-run setup request that creates the initial administrator.
+///
+/// This struct is only accepted while no active administrator exists, and contains:
+/// - `username`: Username for the initial administrator.
+/// - `password`: Operator-supplied plain-text password, hashed before storage.
+/// - `full_name`: Display name for the administrator.
+/// - `setup_token`: Must match the `SETUP_TOKEN` environment variable when one is configured.
+#[derive(Deserialize)]
+pub struct SetupRequest {
+    /// Username for the initial administrator.
+    pub username: String,
+    /// Plain-text password for the initial administrator.
+    pub password: String,
+    /// Full name of the initial administrator.
+    pub full_name: String,
+    /// Optional setup token guarding the first-run setup.
+    pub setup_token: Option<String>,
 }
 
 /// Represents a request to create a new vehicle.
@@ -38,6 +103,10 @@ pub struct NewUserRequest {
 /// - `model`: Model name of the vehicle.
 /// - `registration`: Registration number for the vehicle.
 /// - `registration_expiry_date`: Expiration date of the vehicle's registration.
+/// - `vin`, `model_year`, `colour`, `fuel_type`, `tank_capacity`, `purchase_date` and
+///   `purchase_price`: Optional profile details.
+/// - `odometer_unit`: `km` (the default) or `mi`; the vehicle's odometer readings are entered and
+///   shown in it.
 #[derive(Deserialize)]
 pub struct NewVehicleRequest {
     /// Brand name of the vehicle.
@@ -48,6 +117,22 @@ pub struct NewVehicleRequest {
     pub registration: String,
     /// Expiration date of the vehicle's registration.
     pub registration_expiry_date: NaiveDate,
+    /// Vehicle identification number (optional).
+    pub vin: Option<String>,
+    /// Model year (optional).
+    pub model_year: Option<i32>,
+    /// Colour of the body (optional).
+    pub colour: Option<String>,
+    /// `petrol`, `diesel`, `ev`, `hybrid` or `lpg` (optional).
+    pub fuel_type: Option<String>,
+    /// Tank capacity in liters, or battery capacity in kWh (optional).
+    pub tank_capacity: Option<Decimal>,
+    /// Unit of the odometer, `km` or `mi` (optional).
+    pub odometer_unit: Option<String>,
+    /// When the vehicle was bought (optional).
+    pub purchase_date: Option<NaiveDate>,
+    /// What the vehicle cost (optional).
+    pub purchase_price: Option<Decimal>,
 }
 
 /// Represents a request to update an existing vehicle.
@@ -57,6 +142,9 @@ pub struct NewVehicleRequest {
 /// - `model`: Optional updated model name.
 /// - `registration`: Optional updated registration number.
 /// - `registration_expiry_date`: Optional updated expiration date for the vehicle's registration.
+/// - `vin`, `model_year`, `colour`, `fuel_type`, `tank_capacity`, `purchase_date` and
+///   `purchase_price`: Optional updated profile details; `null` clears a detail.
+/// - `odometer_unit`: Optional updated odometer unit.
 #[derive(Deserialize)]
 pub struct UpdateVehicleRequest {
     /// Updated brand name (optional).
@@ -67,6 +155,72 @@ pub struct UpdateVehicleRequest {
     pub registration: Option<String>,
     /// Updated expiration date for the registration (optional).
     pub registration_expiry_date: Option<NaiveDate>,
+    /// Updated vehicle identification number (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub vin: Option<Option<String>>,
+    /// Updated model year (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub model_year: Option<Option<i32>>,
+    /// Updated colour (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub colour: Option<Option<String>>,
+    /// Updated fuel type (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub fuel_type: Option<Option<String>>,
+    /// Updated tank or battery capacity (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub tank_capacity: Option<Option<Decimal>>,
+    /// Updated odometer unit (optional).
+    pub odometer_unit: Option<String>,
+    /// Updated purchase date (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub purchase_date: Option<Option<NaiveDate>>,
+    /// Updated purchase price (optional, `null` clears it).
+    #[serde(default, deserialize_with = "nullable::deserialize")]
+    pub purchase_price: Option<Option<Decimal>>,
+}
+
+/// Represents the query parameters accepted when listing vehicles.
+///
+/// - `include_archived`: Whether archived vehicles are listed too (defaults to `false`).
+#[derive(Deserialize)]
+pub struct VehicleListQuery {
+    /// Whether archived vehicles are included in the listing.
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+/// Represents a request to create a new organization.
+///
+/// - `name`: Display name of the organization.
+#[derive(Deserialize)]
+pub struct NewOrganizationRequest {
+    /// Display name of the organization.
+    pub name: String,
+}
+
+/// Represents a request to invite an existing user to an organization.
+///
+/// - `username`: The user to invite.
+/// - `role`: The role offered in the organization: `admin` or `member`.
+#[derive(Deserialize)]
+pub struct InviteOrganizationMemberRequest {
+    /// Username of the user to invite.
+    pub username: String,
+    /// Role offered to the invited user.
+    pub role: String,
+}
+
+/// Represents a request to share a vehicle with another user.
+///
+/// - `username`: The user to invite.
+/// - `role`: The role offered to the user: `owner`, `editor` or `viewer`.
+#[derive(Deserialize)]
+pub struct InviteVehicleMemberRequest {
+    /// Username of the user to invite.
+    pub username: String,
+    /// Role offered to the invited user.
+    pub role: String,
 }
 
 /// Represents a request to create a new odometer entry.
@@ -74,12 +228,16 @@ pub struct UpdateVehicleRequest {
 /// This struct is used to parse incoming requests for creating odometer entries, containing:
 /// - `odometer_value`: The odometer reading.
 /// - `timestamp`: Optional timestamp for the entry.
+/// - `driver_id`: Optional driver; defaults to the driver assigned at the entry's time.
 #[derive(Deserialize)]
 pub struct NewOdometerRequest {
     /// Odometer reading for the vehicle.
-    pub odometer_value: f32,
+    pub odometer_value: Decimal,
     /// Optional timestamp for the odometer entry.
-    pub timestamp: Option<chrono::NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Optional ID of the user who was driving.
+    pub driver_id: Option<uuid::Uuid>,
 }
 
 /// Represents a request to create a new refuel event.
@@ -88,14 +246,166 @@ pub struct NewOdometerRequest {
 /// - `refuel_quantity`: The amount of fuel refueled.
 /// - `odometer_value`: The odometer reading at the time of refueling.
 /// - `timestamp`: Optional timestamp for the refuel event.
+/// - `driver_id`: Optional driver; defaults to the driver assigned at the event's time.
+/// - `cost`: Optional amount paid for the fuel.
 #[derive(Deserialize)]
 pub struct NewRefuelRequest {
     /// The amount of fuel refueled.
-    pub refuel_quantity: f32,
+    pub refuel_quantity: Decimal,
     /// The odometer reading at the time of refueling.
-    pub odometer_value: f32,
+    pub odometer_value: Decimal,
     /// Optional timestamp for the refuel event.
-    pub timestamp: Option<chrono::NaiveDateTime>,
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Optional ID of the user who was driving.
+    pub driver_id: Option<uuid::Uuid>,
+    /// Optional amount paid for the fuel.
+    pub cost: Option<Decimal>,
+}
+
+/// Represents a bulk import of refuels from a CSV document, such as a spreadsheet export.
+///
+/// - `csv`: The CSV document; its first record holds the column names.
+/// - `mapping`: Which CSV column holds which refuel field.
+/// - `delimiter`: Optional field delimiter (defaults to `,`).
+/// - `date_format`: Optional `chrono` format of the timestamp column; RFC 3339 timestamps and ISO
+///   8601 dates and date-times are accepted by default. Values without an offset are local times in
+///   the user's timezone.
+/// - `dry_run`: Whether to only validate the rows, without importing them.
+#[derive(Deserialize)]
+pub struct RefuelImportRequest {
+    /// The CSV document, header record first.
+    pub csv: String,
+    /// Which CSV column holds which refuel field.
+    pub mapping: RefuelImportMapping,
+    /// Optional field delimiter.
+    pub delimiter: Option<char>,
+    /// Optional format of the timestamp column.
+    pub date_format: Option<String>,
+    /// Whether to only validate the rows.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Maps refuel fields to the names of the CSV columns holding them.
+#[derive(Deserialize)]
+pub struct RefuelImportMapping {
+    /// Column of the refuel date or date-time.
+    pub timestamp: String,
+    /// Column of the odometer reading.
+    pub odometer_value: String,
+    /// Column of the amount of fuel.
+    pub refuel_quantity: String,
+    /// Optional column of the amount paid.
+    pub cost: Option<String>,
+}
+
+/// Represents a request to record a charging session of an electric or plug-in hybrid vehicle.
+///
+/// - `energy_kwh`: The energy delivered, in kWh.
+/// - `odometer_value`: The odometer reading when the session started.
+/// - `charger_type`: `ac` or `dc`.
+/// - `started_at`: When the session started; defaults to now.
+/// - `start_soc` and `end_soc`: Optional state of charge before and after, in percent.
+/// - `duration_minutes`, `cost` and `location`: Optional session details.
+/// - `driver_id`: Optional driver; defaults to the driver assigned when the session started.
+#[derive(Deserialize)]
+pub struct NewChargingSessionRequest {
+    /// The energy delivered, in kWh.
+    pub energy_kwh: f32,
+    /// The odometer reading when the session started.
+    pub odometer_value: Decimal,
+    /// `ac` or `dc`.
+    pub charger_type: String,
+    /// Optional start of the session.
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Optional state of charge before charging, in percent.
+    pub start_soc: Option<f32>,
+    /// Optional state of charge after charging, in percent.
+    pub end_soc: Option<f32>,
+    /// Optional length of the session, in minutes.
+    pub duration_minutes: Option<i32>,
+    /// Optional amount paid.
+    pub cost: Option<Decimal>,
+    /// Optional place of the charger.
+    pub location: Option<String>,
+    /// Optional ID of the user who was driving.
+    pub driver_id: Option<uuid::Uuid>,
+}
+
+/// Represents a request to assign a driver to a vehicle.
+///
+/// - `username`: The driver, who must have access to the vehicle.
+/// - `starts_at`: Start of the assignment; defaults to now.
+/// - `ends_at`: End of the assignment; open-ended when omitted.
+#[derive(Deserialize)]
+pub struct AssignDriverRequest {
+    /// Username of the driver.
+    pub username: String,
+    /// Start of the assignment.
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub starts_at: Option<DateTime<Utc>>,
+    /// End of the assignment.
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+/// Represents a request to log a trip.
+///
+/// - `started_at` and `ended_at`: When the trip started and ended.
+/// - `start_odometer_value` and `end_odometer_value`: The odometer readings at either end.
+/// - `origin`, `destination` and `purpose`: Logbook details; business trips need a purpose.
+/// - `category`: `business` or `private`.
+/// - `driver_id`: Optional driver; defaults to the driver assigned when the trip started.
+#[derive(Deserialize)]
+pub struct NewTripRequest {
+    /// When the trip started.
+    #[serde(deserialize_with = "timestamp::required")]
+    pub started_at: DateTime<Utc>,
+    /// When the trip ended.
+    #[serde(deserialize_with = "timestamp::required")]
+    pub ended_at: DateTime<Utc>,
+    /// Odometer reading at the start of the trip.
+    pub start_odometer_value: Decimal,
+    /// Odometer reading at the end of the trip.
+    pub end_odometer_value: Decimal,
+    /// Where the trip started.
+    pub origin: String,
+    /// Where the trip ended.
+    pub destination: String,
+    /// Why the trip was made.
+    #[serde(default)]
+    pub purpose: String,
+    /// `business` or `private`.
+    pub category: String,
+    /// Optional ID of the user who was driving.
+    pub driver_id: Option<uuid::Uuid>,
+}
+
+/// Represents a request to update a logged trip; omitted fields are left unchanged.
+#[derive(Deserialize)]
+pub struct UpdateTripRequest {
+    /// Updated start time (optional).
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Updated end time (optional).
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Updated start odometer reading (optional).
+    pub start_odometer_value: Option<Decimal>,
+    /// Updated end odometer reading (optional).
+    pub end_odometer_value: Option<Decimal>,
+    /// Updated origin (optional).
+    pub origin: Option<String>,
+    /// Updated destination (optional).
+    pub destination: Option<String>,
+    /// Updated purpose (optional).
+    pub purpose: Option<String>,
+    /// Updated category (optional).
+    pub category: Option<String>,
+    /// Updated driver (optional).
+    pub driver_id: Option<uuid::Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -103,3 +413,164 @@ pub struct DateIntervalRequest {
     pub start_date: chrono::NaiveDate,
     pub end_date: chrono::NaiveDate,
 }
+
+/// Represents the query parameters of a periodic mileage report.
+///
+/// - `period`: The length of each report row: `monthly`, `quarterly` or `yearly`.
+/// - `start_date` and `end_date`: The days covered by the report (inclusive).
+#[derive(Deserialize)]
+pub struct ReportQuery {
+    /// `monthly`, `quarterly` or `yearly`.
+    pub period: String,
+    /// First day of the report.
+    pub start_date: chrono::NaiveDate,
+    /// Last day of the report (inclusive).
+    pub end_date: chrono::NaiveDate,
+}
+
+/// Represents the filters accepted by the audit log query endpoint.
+///
+/// All filters are optional and combined with `AND`:
+/// - `user_id`: Only actions performed by this user.
+/// - `entity_type` and `entity_id`: Only actions on this kind of record, or this exact record.
+/// - `start` and `end`: Only actions recorded within this time range.
+/// - `limit`: Maximum number of entries to return (newest first).
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    /// Actor to filter by.
+    pub user_id: Option<uuid::Uuid>,
+    /// Entity type to filter by, e.g. `vehicle`.
+    pub entity_type: Option<String>,
+    /// Entity ID to filter by.
+    pub entity_id: Option<uuid::Uuid>,
+    /// Start of the time range (inclusive).
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub start: Option<DateTime<Utc>>,
+    /// End of the time range (inclusive).
+    #[serde(default, deserialize_with = "timestamp::optional")]
+    pub end: Option<DateTime<Utc>>,
+    /// Maximum number of entries to return.
+    pub limit: Option<i64>,
+}
+
+/// Represents the filters accepted by the notification list endpoint.
+///
+/// - `unread`: Only return notifications that have not been read yet.
+/// - `limit`: Maximum number of notifications to return (newest first).
+#[derive(Deserialize)]
+pub struct NotificationQuery {
+    /// Whether to only return unread notifications.
+    #[serde(default)]
+    pub unread: bool,
+    /// Maximum number of notifications to return.
+    pub limit: Option<i64>,
+}
+
+/// Represents the request payload for adding an outgoing webhook.
+///
+/// - `url`: The `http` or `https` URL events are posted to.
+/// - `events`: The events to subscribe to, e.g. `vehicle.created` or `refuel.created`.
+/// - `secret`: Key for the payload signatures; a random one is generated if left out.
+#[derive(Deserialize)]
+pub struct NewWebhookRequest {
+    /// Where events are posted.
+    pub url: String,
+    /// The subscribed events.
+    pub events: Vec<String>,
+    /// Optional signing key.
+    pub secret: Option<String>,
+}
+
+/// Represents the request payload for changing a webhook; fields left out are kept.
+#[derive(Deserialize)]
+pub struct UpdateWebhookRequest {
+    /// New URL events are posted to.
+    pub url: Option<String>,
+    /// New subscribed events.
+    pub events: Option<Vec<String>>,
+    /// Whether events are delivered.
+    pub is_active: Option<bool>,
+}
+
+/// Represents the filters accepted by the webhook delivery log endpoint.
+///
+/// - `status`: Only deliveries in this state (`pending`, `sent` or `failed`).
+/// - `limit`: Maximum number of deliveries to return (newest first).
+#[derive(Deserialize)]
+pub struct WebhookDeliveryQuery {
+    /// Delivery state to filter by.
+    pub status: Option<String>,
+    /// Maximum number of deliveries to return.
+    pub limit: Option<i64>,
+}
+
+/// Represents the filters accepted by the document list endpoint.
+///
+/// - `refuel_id`: Only documents attached to this refuel.
+#[derive(Deserialize)]
+pub struct DocumentQuery {
+    /// Refuel to filter by.
+    pub refuel_id: Option<uuid::Uuid>,
+}
+
+/// Represents a document upload, read from a `multipart/form-data` request.
+///
+/// - `filename`: The name of the uploaded file (the `file` part).
+/// - `bytes`: The contents of the file.
+/// - `kind`: What the document is (`insurance`, `registration`, `receipt` or `other`); defaults to
+///   `receipt` for refuel documents and `other` otherwise.
+/// - `refuel_id`: The refuel to attach the document to, if any.
+pub struct DocumentUpload {
+    /// Name of the uploaded file.
+    pub filename: String,
+    /// Contents of the file.
+    pub bytes: Vec<u8>,
+    /// Optional document kind.
+    pub kind: Option<String>,
+    /// Optional refuel to attach the document to.
+    pub refuel_id: Option<uuid::Uuid>,
+}
+
+/// Represents a request to record an insurance policy of a vehicle.
+///
+/// - `provider` and `policy_number`: Who insures the vehicle, and under which policy.
+/// - `coverage_type`: `comprehensive`, `third_party`, `total_loss_only` or `other`.
+/// - `premium`: What the policy costs for its whole term.
+/// - `start_date` and `end_date`: The first and last day covered.
+/// - `document_id`: Optional policy document, uploaded as a document of the same vehicle.
+#[derive(Deserialize)]
+pub struct NewInsurancePolicyRequest {
+    /// Name of the insurer.
+    pub provider: String,
+    /// Number of the policy.
+    pub policy_number: String,
+    /// What the policy covers.
+    pub coverage_type: String,
+    /// Premium for the whole term.
+    pub premium: Decimal,
+    /// First day covered.
+    pub start_date: chrono::NaiveDate,
+    /// Last day covered.
+    pub end_date: chrono::NaiveDate,
+    /// Optional ID of the policy document.
+    pub document_id: Option<uuid::Uuid>,
+}
+
+/// Represents a request to update an insurance policy; omitted fields are left unchanged.
+#[derive(Deserialize)]
+pub struct UpdateInsurancePolicyRequest {
+    /// Updated insurer (optional).
+    pub provider: Option<String>,
+    /// Updated policy number (optional).
+    pub policy_number: Option<String>,
+    /// Updated coverage type (optional).
+    pub coverage_type: Option<String>,
+    /// Updated premium (optional).
+    pub premium: Option<Decimal>,
+    /// Updated first day covered (optional).
+    pub start_date: Option<chrono::NaiveDate>,
+    /// Updated last day covered (optional).
+    pub end_date: Option<chrono::NaiveDate>,
+    /// Updated policy document (optional).
+    pub document_id: Option<uuid::Uuid>,
+}
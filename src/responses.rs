@@ -0,0 +1,371 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::{
+    ChargingSession, DriverAssignment, Organization, Trip, UserPreferences, Vehicle, Webhook,
+};
+
+/// Represents the liveness report returned by the liveness probe.
+///
+/// Liveness only states that the process is up and serving requests; it deliberately does not
+/// touch the database so that a database outage does not get the container restarted.
+#[derive(Serialize)]
+pub struct LivenessResponse {
+    /// Always `"alive"` when the server can answer.
+    pub status: &'static str,
+    /// Version of the running build.
+    pub version: &'static str,
+    /// Seconds elapsed since the server started.
+    pub uptime_seconds: u64,
+}
+
+/// Represents the readiness report returned by the readiness probe.
+///
+/// The instance is ready when the database answers a round-trip query and no migrations
+/// are pending.
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// `"ready"` or `"not_ready"`.
+    pub status: &'static str,
+    /// Version of the running build.
+    pub version: &'static str,
+    /// Seconds elapsed since the server started.
+    pub uptime_seconds: u64,
+    /// Result of the database round-trip check.
+    pub database: DatabaseHealth,
+    /// Current state of the connection pool.
+    pub pool: PoolHealth,
+    /// Migration status of the connected database.
+    pub migrations: MigrationHealth,
+}
+
+/// Result of running `SELECT 1` against the database.
+#[derive(Serialize)]
+pub struct DatabaseHealth {
+    /// Whether the query succeeded.
+    pub reachable: bool,
+    /// Round-trip latency of the query in milliseconds, if it succeeded.
+    pub latency_ms: Option<f64>,
+    /// `"unavailable"` if the query failed; the details are logged.
+    pub error: Option<String>,
+}
+
+/// Snapshot of the r2d2 connection pool.
+#[derive(Serialize)]
+pub struct PoolHealth {
+    /// Maximum number of connections the pool will open.
+    pub max_size: u32,
+    /// Number of connections currently open.
+    pub connections: u32,
+    /// Number of open connections that are idle.
+    pub idle_connections: u32,
+}
+
+/// Migration status of the connected database.
+#[derive(Serialize)]
+pub struct MigrationHealth {
+    /// Whether every embedded migration has been applied.
+    pub up_to_date: bool,
+    /// Names of migrations that have not been applied yet, as in the `migrations` directory.
+    pub pending: Vec<String>,
+    /// `"unavailable"` if the migration status could not be determined; the details are logged.
+    pub error: Option<String>,
+}
+
+/// A user's unit and format preferences, with the labels values are shown with.
+#[derive(Serialize)]
+pub struct PreferencesResponse {
+    /// The preferences themselves; their fields are serialized inline.
+    #[serde(flatten)]
+    pub preferences: UserPreferences,
+    /// Abbreviation of the distance unit, e.g. `km`.
+    pub distance_label: String,
+    /// Abbreviation of the volume unit, e.g. `L`.
+    pub volume_label: String,
+    /// Label of fuel economies, e.g. `km/L`.
+    pub economy_label: String,
+    /// `strftime` pattern of the date format, e.g. `%d/%m/%Y`.
+    pub date_pattern: String,
+}
+
+/// A vehicle together with the requesting user's role on it.
+#[derive(Serialize)]
+pub struct VehicleWithRole {
+    /// The vehicle itself; its fields are serialized inline.
+    #[serde(flatten)]
+    pub vehicle: Vehicle,
+    /// The requesting user's role: `owner`, `editor` or `viewer`.
+    pub role: String,
+    /// Whether the vehicle is insured; `None` if no policy was recorded.
+    pub insurance: Option<InsuranceStatus>,
+}
+
+/// Insurance coverage of a vehicle, as of today.
+#[derive(Serialize)]
+pub struct InsuranceStatus {
+    /// `active`, `expiring` (ending within 30 days) or `expired`.
+    pub status: String,
+    /// Last day covered by the vehicle's latest-ending policy.
+    pub end_date: NaiveDate,
+    /// Days from today until `end_date`; negative once expired.
+    pub days_left: i64,
+}
+
+/// A member of a vehicle, or a user with a pending invitation to it.
+#[derive(Serialize)]
+pub struct VehicleMemberResponse {
+    /// ID of the member.
+    pub user_id: Uuid,
+    /// Username of the member.
+    pub username: String,
+    /// Full name of the member.
+    pub full_name: String,
+    /// Role of the member: `owner`, `editor` or `viewer`.
+    pub role: String,
+    /// ID of the user who sent the invitation, if any.
+    pub invited_by: Option<Uuid>,
+    /// When the invitation was accepted; `None` while it is pending.
+    pub accepted_at: Option<DateTime<Utc>>,
+    /// When the membership (or invitation) was created.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A pending invitation to a vehicle, addressed to the requesting user.
+#[derive(Serialize)]
+pub struct VehicleInvitation {
+    /// The vehicle the user is invited to.
+    pub vehicle: Vehicle,
+    /// Role offered to the user.
+    pub role: String,
+    /// ID of the user who sent the invitation, if any.
+    pub invited_by: Option<Uuid>,
+    /// When the invitation was sent.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A pending invitation to an organization, addressed to the requesting user.
+#[derive(Serialize)]
+pub struct OrganizationInvitation {
+    /// The organization the user is invited to.
+    pub organization: Organization,
+    /// Role offered to the user.
+    pub role: String,
+    /// ID of the user who sent the invitation, if any.
+    pub invited_by: Option<Uuid>,
+    /// When the invitation was sent.
+    pub created_at: DateTime<Utc>,
+}
+
+/// An organization together with the requesting user's role in it.
+#[derive(Serialize)]
+pub struct OrganizationWithRole {
+    /// The organization itself; its fields are serialized inline.
+    #[serde(flatten)]
+    pub organization: Organization,
+    /// The requesting user's role: `admin` or `member`.
+    pub role: String,
+    /// Whether this is the organization the current session is working with.
+    pub active: bool,
+}
+
+/// A member of an organization.
+#[derive(Serialize)]
+pub struct OrganizationMemberResponse {
+    /// ID of the member.
+    pub user_id: Uuid,
+    /// Username of the member.
+    pub username: String,
+    /// Full name of the member.
+    pub full_name: String,
+    /// Role of the member: `admin` or `member`.
+    pub role: String,
+    /// When the member joined the organization.
+    pub created_at: DateTime<Utc>,
+}
+
+/// A driver assignment of a vehicle, together with the driver's name.
+#[derive(Serialize)]
+pub struct DriverAssignmentResponse {
+    /// The assignment itself; its fields are serialized inline.
+    #[serde(flatten)]
+    pub assignment: DriverAssignment,
+    /// Username of the driver.
+    pub username: String,
+    /// Full name of the driver.
+    pub full_name: String,
+}
+
+/// Distance driven and fuel added by one driver of a vehicle within a date range.
+///
+/// Each odometer reading attributes the distance since the previous reading in the range to its
+/// driver, or to its submitter when no driver is recorded. Readings with neither are grouped
+/// under a `driver_id` of `None`.
+#[derive(Serialize)]
+pub struct DriverUsage {
+    /// ID of the driver, if known.
+    pub driver_id: Option<Uuid>,
+    /// Username of the driver, if known.
+    pub username: Option<String>,
+    /// Full name of the driver, if known.
+    pub full_name: Option<String>,
+    /// Distance driven.
+    pub distance: Decimal,
+    /// Quantity of fuel added.
+    pub refuel_quantity: Decimal,
+    /// Number of odometer readings attributed to the driver.
+    pub odometer_readings: usize,
+    /// Number of refuels attributed to the driver.
+    pub refuels: usize,
+}
+
+/// A logbook trip together with its odometer readings.
+#[derive(Serialize)]
+pub struct TripResponse {
+    /// The trip itself; its fields are serialized inline.
+    #[serde(flatten)]
+    pub trip: Trip,
+    /// Odometer reading at the start of the trip.
+    pub start_odometer_value: Decimal,
+    /// Odometer reading at the end of the trip.
+    pub end_odometer_value: Decimal,
+    /// Distance driven on the trip.
+    pub distance: Decimal,
+}
+
+/// A charging session together with its odometer reading and efficiency.
+#[derive(Serialize)]
+pub struct ChargingSessionResponse {
+    /// The session itself; its fields are serialized inline.
+    #[serde(flatten)]
+    pub session: ChargingSession,
+    /// Odometer reading when the session started.
+    pub odometer_value: Decimal,
+    /// Distance driven since the previous session, if the odometer advanced.
+    pub distance: Option<f64>,
+    /// Energy used per 100 distance units (km or mi) since the previous session, if it can be
+    /// computed.
+    pub kwh_per_100_distance: Option<f64>,
+}
+
+/// Charging totals and efficiency of a vehicle's sessions that started within a date range.
+#[derive(Serialize)]
+pub struct ChargingSummary {
+    /// First day of the range.
+    pub start_date: NaiveDate,
+    /// Last day of the range (inclusive).
+    pub end_date: NaiveDate,
+    /// Number of sessions.
+    pub sessions: usize,
+    /// Energy delivered.
+    pub energy_kwh: f32,
+    /// What the sessions cost, counting only sessions with a recorded cost.
+    pub cost: Decimal,
+    /// Distance driven between the sessions an efficiency could be computed for.
+    pub distance: f64,
+    /// Average energy used per 100 distance units (km or mi) over that distance.
+    pub kwh_per_100_distance: Option<f64>,
+}
+
+/// Business and private mileage of a vehicle's trips that started within a date range.
+#[derive(Serialize)]
+pub struct TripSummary {
+    /// First day of the range.
+    pub start_date: NaiveDate,
+    /// Last day of the range (inclusive).
+    pub end_date: NaiveDate,
+    /// Distance driven on business trips.
+    pub business_distance: Decimal,
+    /// Distance driven on private trips.
+    pub private_distance: Decimal,
+    /// Number of business trips.
+    pub business_trips: usize,
+    /// Number of private trips.
+    pub private_trips: usize,
+}
+
+/// One period of a mileage report, or the totals of the whole report.
+#[derive(Serialize)]
+pub struct ReportRow {
+    /// Label of the period, e.g. `2024-12`, `2024-Q4` or `2024`; `Total` for the totals.
+    pub period: String,
+    /// First day of the period that falls within the report.
+    pub start_date: NaiveDate,
+    /// Last day of the period that falls within the report.
+    pub end_date: NaiveDate,
+    /// Distance traveled, from the first to the last odometer reading of the period.
+    pub distance: Decimal,
+    /// Quantity of fuel added.
+    pub refuel_quantity: Decimal,
+    /// What the refuels cost, counting only refuels with a recorded cost.
+    pub cost: Decimal,
+    /// Insurance premiums, spread evenly over the days each policy covers.
+    pub insurance_cost: Decimal,
+    /// Cost of ownership: the refuel costs plus the insurance premiums.
+    pub total_cost: Decimal,
+    /// Distance of business trips logged in the period.
+    pub business_distance: Decimal,
+    /// Distance of private trips logged in the period.
+    pub private_distance: Decimal,
+    /// Share of the distance traveled that was logged as business trips, from 0 to 1.
+    pub business_share: Option<f32>,
+}
+
+/// Periodic mileage report of a vehicle, for bookkeeping.
+#[derive(Serialize)]
+pub struct MileageReport {
+    /// The vehicle the report is about.
+    pub vehicle: Vehicle,
+    /// Length of each row: `monthly`, `quarterly` or `yearly`.
+    pub period: String,
+    /// First day of the report.
+    pub start_date: NaiveDate,
+    /// Last day of the report (inclusive).
+    pub end_date: NaiveDate,
+    /// One row per period, oldest first.
+    pub rows: Vec<ReportRow>,
+    /// Totals of all rows.
+    pub total: ReportRow,
+}
+
+/// A CSV row that could not be imported.
+#[derive(Serialize)]
+pub struct ImportRowError {
+    /// Line of the CSV document the row starts on (the header being line 1).
+    pub row: usize,
+    /// Why the row was rejected.
+    pub message: String,
+}
+
+/// Outcome of a bulk import.
+#[derive(Serialize)]
+pub struct ImportReport {
+    /// Whether the rows were only validated.
+    pub dry_run: bool,
+    /// Number of data rows in the document.
+    pub rows: usize,
+    /// Number of rows imported; 0 on a dry run or when any row was rejected.
+    pub imported: usize,
+    /// The rejected rows, in document order.
+    pub errors: Vec<ImportRowError>,
+}
+
+/// Number of unread notifications in the user's inbox, shown on the navbar bell.
+#[derive(Serialize)]
+pub struct UnreadNotifications {
+    /// Unread, not dismissed notifications.
+    pub unread: i64,
+}
+
+/// A newly added webhook, with the secret its payloads are signed with.
+///
+/// The secret is only returned here; listings leave it out.
+#[derive(Serialize)]
+pub struct CreatedWebhook {
+    /// The webhook.
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key of the `X-Vimana-Signature` HMAC-SHA256 signatures.
+    pub secret: String,
+}
@@ -1,13 +1,122 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_log (id) {
+        id -> Uuid,
+        user_id -> Nullable<Uuid>,
+        action -> Text,
+        entity_type -> Text,
+        entity_id -> Nullable<Uuid>,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        request_id -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    charging_sessions (id) {
+        id -> Uuid,
+        vehicle_id -> Uuid,
+        odometer_id -> Uuid,
+        started_at -> Timestamptz,
+        energy_kwh -> Float4,
+        charger_type -> Text,
+        start_soc -> Nullable<Float4>,
+        end_soc -> Nullable<Float4>,
+        duration_minutes -> Nullable<Int4>,
+        cost -> Nullable<Numeric>,
+        location -> Nullable<Text>,
+        user_id -> Nullable<Uuid>,
+        driver_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    documents (id) {
+        id -> Uuid,
+        vehicle_id -> Uuid,
+        refuel_id -> Nullable<Uuid>,
+        kind -> Text,
+        filename -> Text,
+        content_type -> Text,
+        size_bytes -> Int8,
+        storage_key -> Text,
+        thumbnail_key -> Nullable<Text>,
+        user_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    driver_assignments (id) {
+        id -> Uuid,
+        vehicle_id -> Uuid,
+        user_id -> Uuid,
+        assigned_by -> Nullable<Uuid>,
+        starts_at -> Timestamptz,
+        ends_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    insurance_policies (id) {
+        id -> Uuid,
+        vehicle_id -> Uuid,
+        provider -> Text,
+        policy_number -> Text,
+        coverage_type -> Text,
+        premium -> Numeric,
+        start_date -> Date,
+        end_date -> Date,
+        document_id -> Nullable<Uuid>,
+        user_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    notification_deliveries (id) {
+        id -> Uuid,
+        notification_id -> Uuid,
+        channel -> Text,
+        status -> Text,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    notifications (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        vehicle_id -> Nullable<Uuid>,
+        kind -> Text,
+        title -> Text,
+        body -> Text,
+        dedup_key -> Text,
+        read_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        dismissed_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     odometer (id) {
         id -> Uuid,
         vehicle_id -> Uuid,
         timestamp -> Nullable<Timestamptz>,
-        odometer_value -> Float4,
+        odometer_value -> Numeric,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        user_id -> Nullable<Uuid>,
+        driver_id -> Nullable<Uuid>,
     }
 }
 
@@ -17,7 +126,30 @@ diesel::table! {
         vehicle_id -> Uuid,
         odometer_id -> Uuid,
         timestamp -> Nullable<Timestamptz>,
-        refuel_quantity -> Float4,
+        refuel_quantity -> Numeric,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+        user_id -> Nullable<Uuid>,
+        driver_id -> Nullable<Uuid>,
+        cost -> Nullable<Numeric>,
+    }
+}
+
+diesel::table! {
+    organization_members (organization_id, user_id) {
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        role -> Text,
+        created_at -> Timestamptz,
+        invited_by -> Nullable<Uuid>,
+        accepted_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    organizations (id) {
+        id -> Uuid,
+        name -> Text,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
     }
@@ -30,6 +162,38 @@ diesel::table! {
         session_token -> Text,
         expires_at -> Timestamptz,
         created_at -> Nullable<Timestamptz>,
+        active_organization_id -> Nullable<Uuid>,
+    }
+}
+
+diesel::table! {
+    trips (id) {
+        id -> Uuid,
+        vehicle_id -> Uuid,
+        start_odometer_id -> Uuid,
+        end_odometer_id -> Uuid,
+        started_at -> Timestamptz,
+        ended_at -> Timestamptz,
+        origin -> Text,
+        destination -> Text,
+        purpose -> Text,
+        category -> Text,
+        user_id -> Nullable<Uuid>,
+        driver_id -> Nullable<Uuid>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    user_preferences (user_id) {
+        user_id -> Uuid,
+        distance_unit -> Text,
+        volume_unit -> Text,
+        currency -> Text,
+        date_format -> Text,
+        timezone -> Text,
+        updated_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -41,6 +205,20 @@ diesel::table! {
         full_name -> Text,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        is_admin -> Bool,
+        is_active -> Bool,
+        email -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    vehicle_members (vehicle_id, user_id) {
+        vehicle_id -> Uuid,
+        user_id -> Uuid,
+        role -> Text,
+        invited_by -> Nullable<Uuid>,
+        accepted_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -54,19 +232,96 @@ diesel::table! {
         user_id -> Uuid,
         created_at -> Nullable<Timestamptz>,
         updated_at -> Nullable<Timestamptz>,
+        archived_at -> Nullable<Timestamptz>,
+        organization_id -> Uuid,
+        vin -> Nullable<Text>,
+        model_year -> Nullable<Int4>,
+        colour -> Nullable<Text>,
+        fuel_type -> Nullable<Text>,
+        tank_capacity -> Nullable<Numeric>,
+        odometer_unit -> Text,
+        purchase_date -> Nullable<Date>,
+        purchase_price -> Nullable<Numeric>,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Uuid,
+        webhook_id -> Uuid,
+        event -> Text,
+        payload -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        response_status -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        organization_id -> Uuid,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        is_active -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
     }
 }
 
+diesel::joinable!(audit_log -> users (user_id));
+diesel::joinable!(charging_sessions -> odometer (odometer_id));
+diesel::joinable!(charging_sessions -> vehicles (vehicle_id));
+diesel::joinable!(documents -> refuel (refuel_id));
+diesel::joinable!(documents -> users (user_id));
+diesel::joinable!(documents -> vehicles (vehicle_id));
+diesel::joinable!(driver_assignments -> vehicles (vehicle_id));
+diesel::joinable!(insurance_policies -> documents (document_id));
+diesel::joinable!(insurance_policies -> users (user_id));
+diesel::joinable!(insurance_policies -> vehicles (vehicle_id));
+diesel::joinable!(notification_deliveries -> notifications (notification_id));
+diesel::joinable!(notifications -> users (user_id));
+diesel::joinable!(notifications -> vehicles (vehicle_id));
 diesel::joinable!(odometer -> vehicles (vehicle_id));
+diesel::joinable!(organization_members -> organizations (organization_id));
+diesel::joinable!(organization_members -> users (user_id));
 diesel::joinable!(refuel -> odometer (odometer_id));
 diesel::joinable!(refuel -> vehicles (vehicle_id));
+diesel::joinable!(sessions -> organizations (active_organization_id));
 diesel::joinable!(sessions -> users (user_id));
+diesel::joinable!(trips -> vehicles (vehicle_id));
+diesel::joinable!(user_preferences -> users (user_id));
+diesel::joinable!(vehicle_members -> vehicles (vehicle_id));
+diesel::joinable!(vehicles -> organizations (organization_id));
 diesel::joinable!(vehicles -> users (user_id));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
+diesel::joinable!(webhooks -> organizations (organization_id));
+diesel::joinable!(webhooks -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_log,
+    charging_sessions,
+    documents,
+    driver_assignments,
+    insurance_policies,
+    notification_deliveries,
+    notifications,
     odometer,
+    organization_members,
+    organizations,
     refuel,
     sessions,
+    trips,
+    user_preferences,
     users,
+    vehicle_members,
     vehicles,
+    webhook_deliveries,
+    webhooks,
 );
@@ -1,17 +1,51 @@
 use crate::{
+    documents, metrics,
+    middleware::current_request_id,
     models::{
-        NewSession, NewUser, NewVehicle, Odometer, RefuelWithOdometer, TraveledDistance, User,
-        Vehicle,
+        AuditLog, Backup, ChargerType, ChargingSession, ChargingSessionDetails, CoverageType,
+        DateFormat, DeliveryStatus, Document, DocumentFile, DocumentKind, DriverAssignment,
+        FuelType, InsurancePolicy, NewAuditLog, NewDocument, NewDriverAssignment,
+        NewInsurancePolicy, NewNotification, NewSession, NewUser, NewVehicle, NewWebhook,
+        Notification, NotificationKind, Odometer, OdometerUnit, Organization, OrganizationMember,
+        OrganizationRole, RefuelDetails, RefuelWithOdometer, SurroundingOdometer, TraveledDistance,
+        Trip, TripCategory, TripDetails, User, UserPreferences, Vehicle, VehicleChanges,
+        VehicleMember, VehicleRole, VolumeUnit, Webhook, WebhookDelivery, WebhookEvent,
     },
     queries::{self, DbError},
-    requests::LoginRequest,
-    DbPool,
+    requests::{
+        AssignDriverRequest, AuditLogQuery, DocumentQuery, DocumentUpload, LoginRequest,
+        NewChargingSessionRequest, NewInsurancePolicyRequest, NewOdometerRequest, NewRefuelRequest,
+        NewTripRequest, NewUserRequest, NewWebhookRequest, NotificationQuery, RefuelImportRequest,
+        ReportQuery, SetupRequest, UpdateEmailRequest, UpdateInsurancePolicyRequest,
+        UpdatePreferencesRequest, UpdateTripRequest, UpdateVehicleRequest, UpdateWebhookRequest,
+        WebhookDeliveryQuery,
+    },
+    responses::{
+        ChargingSessionResponse, ChargingSummary, CreatedWebhook, DatabaseHealth,
+        DriverAssignmentResponse, DriverUsage, ImportReport, ImportRowError, InsuranceStatus,
+        LivenessResponse, MigrationHealth, MileageReport, OrganizationInvitation,
+        OrganizationMemberResponse, OrganizationWithRole, PoolHealth, PreferencesResponse,
+        ReadinessResponse, ReportRow, TripResponse, TripSummary, UnreadNotifications,
+        VehicleInvitation, VehicleMemberResponse, VehicleWithRole,
+    },
+    units::{self, Units},
+    webhooks, DbPool,
 };
-use actix_web::http::StatusCode;
+use actix_web::{error::BlockingError, http::StatusCode, web};
 use bcrypt::{hash, verify, DEFAULT_COST};
-use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono::{
+    DateTime, Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use diesel::PgConnection;
+use futures::stream::{self, LocalBoxStream, StreamExt};
 use rand::{distributions::Alphanumeric, Rng};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
+use std::time::Instant;
 use uuid::Uuid;
 
 /// Represents service-layer errors, including DB and application-specific issues.
@@ -53,6 +87,12 @@ impl From<DbError> for ServiceError {
     }
 }
 
+impl From<BlockingError> for ServiceError {
+    fn from(_: BlockingError) -> Self {
+        ServiceError::Other("Blocking task failed".to_string())
+    }
+}
+
 /// Converts `ServiceError` to Actix Web's `ResponseError` for HTTP responses.
 ///
 /// This implementation maps service errors to appropriate HTTP status codes
@@ -70,15 +110,387 @@ impl actix_web::ResponseError for ServiceError {
     }
 
     fn error_response(&self) -> actix_web::HttpResponse {
+        // Logged here so the line carries the request ID of the failing request
+        if self.status_code().is_server_error() {
+            log::error!("{}", self);
+        }
         actix_web::HttpResponse::build(self.status_code()).body(self.to_string())
     }
 }
 
-const SUPERUSER_ID_STR: &str = "7763abad-f33d-4308-b89d-8897e9037d16";
+/// Runs a blocking database operation on Actix's blocking thread pool.
+///
+/// The `queries` functions use synchronous Diesel I/O and `pool.get()`, which would otherwise
+/// stall every other request scheduled on the same worker thread while they wait on Postgres.
+///
+/// # Arguments
+/// - `pool`: The database connection pool, cloned into the blocking task.
+/// - `f`: The operation to run with the pool.
+///
+/// # Returns
+/// - `Ok(T)`: The value returned by the operation.
+/// - `Err(ServiceError)`: If the operation fails or the blocking task is cancelled.
+pub async fn run_blocking<F, T>(pool: &DbPool, f: F) -> Result<T, ServiceError>
+where
+    F: FnOnce(&DbPool) -> Result<T, DbError> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = pool.clone();
+    Ok(web::block(move || f(&pool)).await??)
+}
+
+/// Number of audit entries returned when the caller does not pass a limit.
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+
+/// Upper bound for the number of audit entries returned in one response.
+const MAX_AUDIT_LIMIT: i64 = 1000;
+
+/// Serializes a record into a JSON snapshot for the audit log.
+fn snapshot<T: Serialize>(value: &T) -> Option<serde_json::Value> {
+    serde_json::to_value(value).ok()
+}
+
+/// Builds an audit log entry for a data-changing action.
+///
+/// The entry is written by `run_audited`, which also stamps it with the current request ID.
+///
+/// # Arguments
+/// - `actor`: The user who performed the action.
+/// - `action`: What was done, e.g. `create`, `update` or `delete`.
+/// - `entity_type`: The kind of record that changed, e.g. `vehicle`.
+/// - `entity_id`: The ID of the record that changed.
+/// - `before`: Snapshot of the record before the change.
+/// - `after`: Snapshot of the record after the change.
+pub fn audit(
+    actor: Option<Uuid>,
+    action: &str,
+    entity_type: &str,
+    entity_id: Option<Uuid>,
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+) -> NewAuditLog {
+    NewAuditLog {
+        user_id: actor,
+        action: action.to_string(),
+        entity_type: entity_type.to_string(),
+        entity_id,
+        before,
+        after,
+        request_id: None,
+    }
+}
+
+/// Runs a data-changing database operation and records it in the audit log.
+///
+/// The change and its audit entries are written in one transaction, so a change is never
+/// committed without its audit trail and a failed audit insert leaves nothing behind.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `change`: The operation to run on the transaction's connection.
+/// - `entries`: Builds the audit entries, usually with `audit`, from the operation's result.
+///
+/// # Returns
+/// - `Ok(T)`: The value returned by the operation.
+/// - `Err(ServiceError)`: If the operation or the audit insert fails.
+pub async fn run_audited<F, A, I, T>(
+    pool: &DbPool,
+    change: F,
+    entries: A,
+) -> Result<T, ServiceError>
+where
+    F: FnOnce(&mut PgConnection) -> Result<T, DbError> + Send + 'static,
+    A: FnOnce(&T) -> I + Send + 'static,
+    I: IntoIterator<Item = NewAuditLog>,
+    T: Send + 'static,
+{
+    let request_id = current_request_id();
+    run_blocking(pool, move |pool| {
+        queries::audited(pool, change, |value| {
+            entries(value)
+                .into_iter()
+                .map(|entry| NewAuditLog {
+                    request_id: request_id.clone(),
+                    ..entry
+                })
+                .collect::<Vec<_>>()
+        })
+    })
+    .await
+}
+
+/// Builds the JSON document posted to webhooks for an event.
+fn webhook_payload(event: WebhookEvent, data: Option<serde_json::Value>) -> serde_json::Value {
+    serde_json::json!({
+        "id": Uuid::new_v4(),
+        "event": event.as_str(),
+        "created_at": Utc::now(),
+        "data": data,
+    })
+}
+
+/// Queues an event of a vehicle for delivery to the webhooks subscribed to it.
+///
+/// This runs after the change was committed, and unlike a missing audit entry a missed event is
+/// not worth failing the request over, so a failure to queue the event is only logged.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `vehicle_id`: The vehicle the event is about.
+/// - `event`: What happened.
+/// - `data`: Snapshot of the record the event is about.
+pub async fn publish(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    event: WebhookEvent,
+    data: Option<serde_json::Value>,
+) {
+    let payload = webhook_payload(event, data);
+
+    if let Err(e) = run_blocking(pool, move |pool| {
+        queries::enqueue_webhook_event(pool, vehicle_id, event.as_str(), &payload)
+    })
+    .await
+    {
+        log::error!(
+            "Failed to queue {} event of vehicle {}: {}",
+            event.as_str(),
+            vehicle_id,
+            e
+        );
+    }
+}
+
+/// Ensures the given user is an active administrator.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the user to check.
+///
+/// # Returns
+/// - `Ok(())`: If the user is an active administrator.
+/// - `Err(ServiceError::Forbidden)`: If the user is not.
+pub async fn require_admin(pool: &DbPool, user_id: Uuid) -> Result<(), ServiceError> {
+    let is_admin = run_blocking(pool, move |pool| queries::get_user_by_id(pool, user_id))
+        .await?
+        .map(|user| user.is_admin && user.is_active)
+        .unwrap_or(false);
+
+    if is_admin {
+        Ok(())
+    } else {
+        Err(ServiceError::Forbidden("Forbidden".to_string()))
+    }
+}
+
+/// Ensures a user belongs to an organization.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the user to check.
+/// - `organization_id`: The ID of the organization.
+///
+/// # Returns
+/// - `Ok(OrganizationRole)`: The user's role in the organization.
+/// - `Err(ServiceError::NotFound)`: If the user is not a member of the organization, or has not
+///   accepted their invitation yet.
+pub async fn require_organization_member(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<OrganizationRole, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::get_organization_member(pool, organization_id, user_id)
+    })
+    .await?
+    .filter(|member| member.accepted_at.is_some())
+    .and_then(|member| OrganizationRole::parse(&member.role))
+    .ok_or_else(|| ServiceError::NotFound("Organization not found".to_string()))
+}
+
+/// Ensures a user administers an organization.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the user to check.
+/// - `organization_id`: The ID of the organization.
+///
+/// # Returns
+/// - `Ok(())`: If the user is an administrator of the organization.
+/// - `Err(ServiceError::NotFound)`: If the user is not a member of the organization.
+/// - `Err(ServiceError::Forbidden)`: If the user is a plain member.
+pub async fn require_organization_admin(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<(), ServiceError> {
+    match require_organization_member(pool, user_id, organization_id).await? {
+        OrganizationRole::Admin => Ok(()),
+        OrganizationRole::Member => Err(ServiceError::Forbidden(
+            "This action requires the admin role in the organization".to_string(),
+        )),
+    }
+}
+
+/// Ensures a user has at least the given role on a vehicle of their active organization.
+///
+/// Organization administrators have owner access to every vehicle of the organization; other
+/// members need an accepted vehicle membership. Users without access get `NotFound` rather than
+/// `Forbidden`, so vehicle IDs don't leak to users the vehicle isn't shared with, and vehicles
+/// of other organizations are not found either.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the user to check.
+/// - `organization_id`: The user's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `required`: The least privileged role that is allowed.
+///
+/// # Returns
+/// - `Ok(VehicleRole)`: The user's actual role on the vehicle.
+/// - `Err(ServiceError::NotFound)`: If the vehicle is not shared with the user.
+/// - `Err(ServiceError::Forbidden)`: If the user's role is below `required`.
+pub async fn authorize_vehicle(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    required: VehicleRole,
+) -> Result<VehicleRole, ServiceError> {
+    let not_found = || ServiceError::NotFound("Vehicle not found".to_string());
+
+    let vehicle = get_vehicle_by_id(pool, vehicle_id).await?;
+    if vehicle.organization_id != organization_id {
+        return Err(not_found());
+    }
+
+    let role = match require_organization_member(pool, user_id, organization_id).await {
+        Ok(OrganizationRole::Admin) => VehicleRole::Owner,
+        Ok(OrganizationRole::Member) => run_blocking(pool, move |pool| {
+            queries::get_vehicle_member(pool, vehicle_id, user_id)
+        })
+        .await?
+        .filter(|member| member.accepted_at.is_some())
+        .and_then(|member| VehicleRole::parse(&member.role))
+        .ok_or_else(not_found)?,
+        Err(ServiceError::NotFound(_)) => return Err(not_found()),
+        Err(e) => return Err(e),
+    };
+
+    if role >= required {
+        Ok(role)
+    } else {
+        Err(ServiceError::Forbidden(format!(
+            "This action requires the {} role on the vehicle",
+            required.as_str()
+        )))
+    }
+}
+
+/// Version of the running build, reported by the health endpoints.
+const BUILD_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Minimum accepted length for operator-supplied administrator passwords.
+const MIN_ADMIN_PASSWORD_LENGTH: usize = 8;
+
+/// Builds the liveness report.
+///
+/// # Arguments
+/// - `started_at`: The instant the server started.
+///
+/// # Returns
+/// - `LivenessResponse`: The liveness report.
+pub fn liveness(started_at: Instant) -> LivenessResponse {
+    LivenessResponse {
+        status: "alive",
+        version: BUILD_VERSION,
+        uptime_seconds: started_at.elapsed().as_secs(),
+    }
+}
+
+/// Error reported by a readiness check that failed.
+const HEALTH_CHECK_UNAVAILABLE: &str = "unavailable";
+
+/// Builds the readiness report by checking the database, the pool and pending migrations.
+///
+/// Failures are reported inside the returned report rather than as errors, so callers can
+/// always return the full diagnostic to the orchestrator. The endpoint is unauthenticated, so
+/// failed checks only say `"unavailable"`; their details are logged.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `started_at`: The instant the server started.
+///
+/// # Returns
+/// - `ReadinessResponse`: The readiness report; `status` is `"ready"` only if every check passed.
+pub async fn readiness(pool: &DbPool, started_at: Instant) -> ReadinessResponse {
+    let database = match run_blocking(pool, queries::ping).await {
+        Ok(latency) => DatabaseHealth {
+            reachable: true,
+            latency_ms: Some(latency.as_secs_f64() * 1000.0),
+            error: None,
+        },
+        Err(e) => {
+            log::error!("Readiness check could not reach the database: {}", e);
+            DatabaseHealth {
+                reachable: false,
+                latency_ms: None,
+                error: Some(HEALTH_CHECK_UNAVAILABLE.to_string()),
+            }
+        }
+    };
+
+    let migrations = match run_blocking(pool, queries::pending_migrations).await {
+        Ok(pending) => MigrationHealth {
+            up_to_date: pending.is_empty(),
+            pending,
+            error: None,
+        },
+        Err(e) => {
+            log::error!("Readiness check could not read the migration status: {}", e);
+            MigrationHealth {
+                up_to_date: false,
+                pending: Vec::new(),
+                error: Some(HEALTH_CHECK_UNAVAILABLE.to_string()),
+            }
+        }
+    };
+
+    let state = pool.state();
+    let pool_health = PoolHealth {
+        max_size: pool.max_size(),
+        connections: state.connections,
+        idle_connections: state.idle_connections,
+    };
+
+    let ready = database.reachable && migrations.up_to_date;
+
+    ReadinessResponse {
+        status: if ready { "ready" } else { "not_ready" },
+        version: BUILD_VERSION,
+        uptime_seconds: started_at.elapsed().as_secs(),
+        database,
+        pool: pool_health,
+        migrations,
+    }
+}
+
+/// Refreshes the scrape-time gauges and encodes all metrics for Prometheus.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+///
+/// # Returns
+/// - `Ok(String)`: The metrics in the Prometheus text exposition format.
+/// - `Err(ServiceError)`: If the domain counts cannot be queried or encoding fails.
+pub async fn render_metrics(pool: &DbPool) -> Result<String, ServiceError> {
+    let state = pool.state();
+    metrics::set_pool_state(pool.max_size(), state.connections, state.idle_connections);
+
+    let counts = run_blocking(pool, queries::get_domain_counts).await?;
+    metrics::set_domain_counts(&counts);
 
-lazy_static::lazy_static! {
-    /// Static reference to the superuser UUID, used for authorization checks.
-    static ref SUPERUSER_ID: Uuid = Uuid::parse_str(SUPERUSER_ID_STR).expect("Invalid superuser ID format");
+    metrics::encode().map_err(ServiceError::Other)
 }
 
 /// Handles user login by validating credentials and creating a session.
@@ -91,10 +503,26 @@ lazy_static::lazy_static! {
 /// - `Ok(String)`: The session token upon successful login.
 /// - `Err(ServiceError)`: If login fails due to invalid credentials or other issues.
 pub async fn login(pool: &DbPool, req: &LoginRequest) -> Result<String, ServiceError> {
-    let user = queries::get_user_by_username(pool, &req.username)?
-        .ok_or_else(|| ServiceError::Unauthorized("Invalid username or password".to_string()))?;
+    let result = try_login(pool, req).await;
+    metrics::observe_login(result.is_ok());
+    result
+}
+
+/// Validates the credentials and creates a session, without recording metrics.
+async fn try_login(pool: &DbPool, req: &LoginRequest) -> Result<String, ServiceError> {
+    let username = req.username.clone();
+    let user = run_blocking(pool, move |pool| {
+        queries::get_user_by_username(pool, &username)
+    })
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(|| ServiceError::Unauthorized("Invalid username or password".to_string()))?;
 
-    let is_valid = verify(&req.password, &user.hashed_password)
+    // bcrypt is deliberately CPU-heavy, so keep it off the worker thread as well
+    let password = req.password.clone();
+    let hashed_password = user.hashed_password.clone();
+    let is_valid = web::block(move || verify(&password, &hashed_password))
+        .await?
         .map_err(|_| ServiceError::Other("Password verification error".to_string()))?;
 
     if is_valid {
@@ -106,16 +534,40 @@ pub async fn login(pool: &DbPool, req: &LoginRequest) -> Result<String, ServiceE
 
         let expires_at = Utc::now() + Duration::days(1);
 
-        queries::create_new_session(
+        // Sessions start out in the first organization the user joined
+        let user_id = user.id;
+        let active_organization_id = run_blocking(pool, move |pool| {
+            queries::get_organizations_by_user_id(pool, user_id)
+        })
+        .await?
+        .first()
+        .map(|(organization, _)| organization.id);
+
+        let session_id = Uuid::new_v4();
+        let new_session = NewSession {
+            id: session_id,
+            user_id: Some(user.id),
+            session_token: session_token.clone(),
+            expires_at,
+            created_at: Some(Utc::now()),
+            active_organization_id,
+        };
+        // The session token is a credential, so only the session's metadata is audited
+        run_audited(
             pool,
-            &NewSession {
-                id: Uuid::new_v4(),
-                user_id: Some(user.id),
-                session_token: session_token.clone(),
-                expires_at: expires_at.naive_utc(),
-                created_at: Some(Utc::now().naive_utc()),
+            move |conn| queries::create_new_session(conn, &new_session),
+            move |_| {
+                [audit(
+                    Some(user_id),
+                    "login",
+                    "session",
+                    Some(session_id),
+                    None,
+                    Some(serde_json::json!({ "expires_at": expires_at })),
+                )]
             },
-        )?;
+        )
+        .await?;
 
         Ok(session_token)
     } else {
@@ -136,299 +588,6026 @@ pub async fn login(pool: &DbPool, req: &LoginRequest) -> Result<String, ServiceE
 /// - `Ok(())`: If the session was successfully deleted.
 /// - `Err(ServiceError)`: If the session is not found or another error occurs.
 pub async fn logout(pool: &DbPool, user_id: Uuid, token: &str) -> Result<(), ServiceError> {
-    match queries::delete_session(pool, user_id, token)? {
-        0 => Err(ServiceError::NotFound("Session not found".to_string())),
-        _ => Ok(()),
+    let token = token.to_string();
+    let deleted = run_audited(
+        pool,
+        move |conn| queries::delete_session(conn, user_id, &token),
+        move |deleted| {
+            deleted
+                .iter()
+                .map(|session_id| {
+                    audit(
+                        Some(user_id),
+                        "logout",
+                        "session",
+                        Some(*session_id),
+                        None,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>()
+        },
+    )
+    .await?;
+
+    if deleted.is_empty() {
+        return Err(ServiceError::NotFound("Session not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Trims an email address and checks that it looks like one; a blank address counts as none.
+fn normalize_email(email: Option<String>) -> Result<Option<String>, ServiceError> {
+    let email = match email.as_deref().map(str::trim) {
+        None | Some("") => return Ok(None),
+        Some(email) => email,
+    };
+    match email.split_once('@') {
+        Some((local, domain))
+            if !local.is_empty()
+                && !domain.is_empty()
+                && !domain.contains('@')
+                && !email.contains(char::is_whitespace) =>
+        {
+            Ok(Some(email.to_string()))
+        }
+        _ => Err(ServiceError::ValidationError(
+            "Email address is not valid".to_string(),
+        )),
     }
 }
 
-/// Handles the creation of a new user, restricted to superusers.
+/// Handles the creation of a new user within the caller's active organization.
+///
+/// Administrators of the organization may create users in it; global administrators may
+/// create users in any organization they are working with, and are the only ones who can
+/// create other global administrators.
 ///
 /// # Arguments
 /// - `pool`: The database connection pool.
-/// - `user_id`: The ID of the authenticated user (must be the superuser).
-/// - `new_username`: The username for the new user.
-/// - `new_password`: The password for the new user.
-/// - `new_full_name`: The full name of the new user.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization, which the new user joins.
+/// - `request`: The new user's details and roles.
 ///
 /// # Returns
 /// - `Ok(User)`: The newly created user record.
-/// - `Err(ServiceError)`: If the authenticated user is not a superuser or other issues occur.
+/// - `Err(ServiceError::Forbidden)`: If the caller may not create (such) users.
+/// - `Err(ServiceError)`: If other issues occur.
 pub async fn create_user(
     pool: &DbPool,
     user_id: Uuid,
-    new_username: String,
-    new_password: String,
-    new_full_name: String,
+    organization_id: Uuid,
+    request: NewUserRequest,
 ) -> Result<User, ServiceError> {
-    if user_id == *SUPERUSER_ID {
-        let new_hashed_password = hash(&new_password, DEFAULT_COST)
-            .map_err(|_| ServiceError::Other("Failed to hash password".to_string()))?;
-
-        let new_user = NewUser {
-            id: Uuid::new_v4(),
-            username: new_username,
-            hashed_password: new_hashed_password,
-            full_name: new_full_name,
-            created_at: None,
-            updated_at: None,
-        };
-
-        let new_user = queries::create_new_user(pool, &new_user)?;
+    if request.is_admin
+        || require_organization_admin(pool, user_id, organization_id)
+            .await
+            .is_err()
+    {
+        require_admin(pool, user_id).await?;
+    }
 
-        Ok(new_user)
+    let NewUserRequest {
+        username: new_username,
+        password: new_password,
+        full_name: new_full_name,
+        is_admin: new_is_admin,
+        organization_admin,
+        email: new_email,
+    } = request;
+    let new_email = normalize_email(new_email)?;
+    let role = if organization_admin {
+        OrganizationRole::Admin
     } else {
-        Err(ServiceError::Forbidden("Forbidden".to_string()))
-    }
+        OrganizationRole::Member
+    };
+
+    let new_hashed_password = web::block(move || hash(&new_password, DEFAULT_COST))
+        .await?
+        .map_err(|_| ServiceError::Other("Failed to hash password".to_string()))?;
+
+    let new_user = NewUser {
+        id: Uuid::new_v4(),
+        username: new_username,
+        hashed_password: new_hashed_password,
+        full_name: new_full_name,
+        created_at: None,
+        updated_at: None,
+        is_admin: new_is_admin,
+        email: new_email,
+    };
+
+    let new_user = run_audited(
+        pool,
+        move |conn| queries::create_new_user(conn, &new_user, organization_id, role),
+        move |new_user| {
+            [audit(
+                Some(user_id),
+                "create",
+                "user",
+                Some(new_user.id),
+                None,
+                snapshot(new_user),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(new_user)
 }
 
-/// Service to create a new vehicle.
+/// Service to change the email address the authenticated user gets notifications at.
+///
+/// # Arguments
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `request`: The new address, or `None` to stop email notifications.
 ///
-/// Validates the input and calls the `create_vehicle` query.
+/// # Returns
+/// - `Ok(User)`: The updated user.
+/// - `Err(ServiceError::ValidationError)`: If the address is not valid.
+/// - `Err(ServiceError)`: If other issues occur.
+pub async fn update_email(
+    pool: &DbPool,
+    user_id: Uuid,
+    request: UpdateEmailRequest,
+) -> Result<User, ServiceError> {
+    let email = normalize_email(request.email)?;
+    let before = run_blocking(pool, move |pool| queries::get_user_by_id(pool, user_id))
+        .await?
+        .ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+
+    let user = run_audited(
+        pool,
+        move |conn| queries::update_user_email(conn, user_id, email),
+        move |user| {
+            user.as_ref().map(|user| {
+                audit(
+                    Some(user_id),
+                    "update",
+                    "user",
+                    Some(user_id),
+                    snapshot(&before),
+                    snapshot(user),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+
+    Ok(user)
+}
+
+/// Builds the response for a user's preferences, with the labels values are shown with.
+pub fn preferences_response(preferences: UserPreferences) -> PreferencesResponse {
+    let units = preferences.units();
+    PreferencesResponse {
+        distance_label: units.distance.as_str().to_string(),
+        volume_label: units.volume.label().to_string(),
+        economy_label: units.economy_label(),
+        date_pattern: preferences.date_format().pattern().to_string(),
+        preferences,
+    }
+}
+
+/// Service to get the authenticated user's unit and format preferences.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `new_vehicle`: The vehicle data to insert.
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the authenticated user.
 ///
 /// # Returns
-/// - `Ok(Vehicle)`: The newly created vehicle.
+/// - `Ok(PreferencesResponse)`: The preferences; the defaults if the user has not chosen any.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn create_vehicle(
+pub async fn get_preferences(
     pool: &DbPool,
-    new_vehicle: &NewVehicle,
-) -> Result<Vehicle, ServiceError> {
-    queries::create_vehicle(pool, new_vehicle).map_err(ServiceError::DbError)
+    user_id: Uuid,
+) -> Result<PreferencesResponse, ServiceError> {
+    let preferences = run_blocking(pool, move |pool| {
+        queries::get_user_preferences(pool, user_id)
+    })
+    .await?
+    .unwrap_or_else(|| UserPreferences::defaults(user_id));
+    Ok(preferences_response(preferences))
 }
 
-/// Service to get all vehicles owned by a specific user.
+/// Service to change the authenticated user's unit and format preferences.
 ///
-/// Calls the `get_vehicles_by_user_id` query.
+/// Values already recorded are not changed; they are stored in kilometers and liters and only
+/// shown in the chosen units.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `user_id`: The ID of the user.
+/// - `pool`: The database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `request`: The preferences to change; omitted ones are kept.
 ///
 /// # Returns
-/// - `Ok(Vec<Vehicle>)`: A list of vehicles owned by the user.
-/// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_vehicles_by_user_id(
+/// - `Ok(PreferencesResponse)`: The updated preferences.
+/// - `Err(ServiceError::ValidationError)`: If a unit, currency, date format or timezone is
+///   unknown.
+/// - `Err(ServiceError)`: If other issues occur.
+pub async fn update_preferences(
     pool: &DbPool,
     user_id: Uuid,
-) -> Result<Vec<Vehicle>, ServiceError> {
-    queries::get_vehicles_by_user_id(pool, user_id).map_err(ServiceError::DbError)
+    request: UpdatePreferencesRequest,
+) -> Result<PreferencesResponse, ServiceError> {
+    let before = run_blocking(pool, move |pool| {
+        queries::get_user_preferences(pool, user_id)
+    })
+    .await?
+    .unwrap_or_else(|| UserPreferences::defaults(user_id));
+
+    let mut preferences = before.clone();
+    if let Some(unit) = request.distance_unit {
+        preferences.distance_unit = OdometerUnit::parse(&unit.trim().to_lowercase())
+            .ok_or_else(|| {
+                ServiceError::ValidationError(format!(
+                    "Distance unit must be km or mi, got {}",
+                    unit
+                ))
+            })?
+            .as_str()
+            .to_string();
+    }
+    if let Some(unit) = request.volume_unit {
+        preferences.volume_unit = VolumeUnit::parse(&unit.trim().to_lowercase())
+            .ok_or_else(|| {
+                ServiceError::ValidationError(format!(
+                    "Volume unit must be one of l, us_gal, imp_gal, got {}",
+                    unit
+                ))
+            })?
+            .as_str()
+            .to_string();
+    }
+    if let Some(currency) = request.currency {
+        let code = currency.trim().to_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(ServiceError::ValidationError(format!(
+                "Currency must be a three-letter ISO 4217 code, got {}",
+                currency
+            )));
+        }
+        preferences.currency = code;
+    }
+    if let Some(format) = request.date_format {
+        preferences.date_format = DateFormat::parse(&format.trim().to_lowercase())
+            .ok_or_else(|| {
+                ServiceError::ValidationError(format!(
+                    "Date format must be one of iso, dmy, mdy, got {}",
+                    format
+                ))
+            })?
+            .as_str()
+            .to_string();
+    }
+    if let Some(timezone) = request.timezone {
+        preferences.timezone = timezone
+            .trim()
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| ServiceError::ValidationError(format!("Unknown timezone {}", timezone)))?
+            .name()
+            .to_string();
+    }
+    preferences.updated_at = Some(Utc::now());
+
+    let preferences = run_audited(
+        pool,
+        move |conn| queries::upsert_user_preferences(conn, &preferences),
+        move |preferences| {
+            [audit(
+                Some(user_id),
+                "update",
+                "user_preferences",
+                Some(user_id),
+                snapshot(&before),
+                snapshot(preferences),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(preferences_response(preferences))
 }
 
-/// Service to get a vehicle by its ID.
-///
-/// Calls the `get_vehicle_by_id` query.
+/// Checks whether the first-run setup still has to be completed.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
+/// - `pool`: The database connection pool.
 ///
 /// # Returns
-/// - `Ok(Vehicle)`: The vehicle data, if found.
-/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist.
+/// - `Ok(true)`: If no active administrator exists yet.
+/// - `Ok(false)`: If the instance already has an active administrator.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_vehicle_by_id(pool: &DbPool, vehicle_id: Uuid) -> Result<Vehicle, ServiceError> {
-    queries::get_vehicle_by_id(pool, vehicle_id)?
-        .ok_or_else(|| ServiceError::NotFound("Vehicle not found".to_string()))
+pub async fn needs_setup(pool: &DbPool) -> Result<bool, ServiceError> {
+    Ok(run_blocking(pool, queries::count_active_admins).await? == 0)
 }
 
-/// Service to update a vehicle by its ID.
+/// Creates the initial administrator during first-run setup.
 ///
-/// Calls the `update_vehicle_by_id` query and validates the input fields.
+/// Without a setup token, only a fresh installation can be set up. An existing installation
+/// without an active administrator, such as one whose seeded superuser was disabled, already
+/// has users and data, so whoever reaches the setup endpoint first must not be able to claim it.
 ///
 /// # Arguments
-/// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle to update.
-/// - `new_brand`: Updated brand (optional).
-/// - `new_model`: Updated model (optional).
-/// - `new_registration`: Updated registration (optional).
-/// - `new_registration_expiry_date`: Updated registration expiry date (optional).
+/// - `pool`: The database connection pool.
+/// - `expected_token`: The configured setup token, if the operator set one.
+/// - `req`: The setup request containing the administrator's credentials.
 ///
 /// # Returns
-/// - `Ok(Vehicle)`: The updated vehicle data.
-/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist.
-/// - `Err(ServiceError)`: If the operation fails.
-pub async fn update_vehicle_by_id(
+/// - `Ok(User)`: The newly created administrator.
+/// - `Err(ServiceError::Unauthorized)`: If the setup token does not match.
+/// - `Err(ServiceError::ValidationError)`: If the username or password is unacceptable.
+/// - `Err(ServiceError::Forbidden)`: If setup has already been completed, or users exist and no
+///   setup token is configured.
+pub async fn setup_initial_admin(
     pool: &DbPool,
-    vehicle_id: Uuid,
-    new_brand: Option<String>,
-    new_model: Option<String>,
-    new_registration: Option<String>,
-    new_registration_expiry_date: Option<NaiveDate>,
-) -> Result<Vehicle, ServiceError> {
-    queries::update_vehicle_by_id(
+    expected_token: Option<&str>,
+    req: &SetupRequest,
+) -> Result<User, ServiceError> {
+    match expected_token {
+        Some(expected_token) => {
+            if req.setup_token.as_deref() != Some(expected_token) {
+                return Err(ServiceError::Unauthorized(
+                    "Invalid setup token".to_string(),
+                ));
+            }
+        }
+        // Users are only created by setup until an administrator exists, and concurrent setups
+        // are serialized by `create_initial_admin`, so an empty table cannot fill up meanwhile
+        None => {
+            if run_blocking(pool, queries::count_users).await? > 0 {
+                return Err(ServiceError::Forbidden(
+                    "Set SETUP_TOKEN to set up an installation that already has users".to_string(),
+                ));
+            }
+        }
+    }
+
+    let username = req.username.trim();
+    if username.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Username must not be empty".to_string(),
+        ));
+    }
+    if req.password.chars().count() < MIN_ADMIN_PASSWORD_LENGTH {
+        return Err(ServiceError::ValidationError(format!(
+            "Password must be at least {} characters long",
+            MIN_ADMIN_PASSWORD_LENGTH
+        )));
+    }
+
+    let password = req.password.clone();
+    let hashed_password = web::block(move || hash(&password, DEFAULT_COST))
+        .await?
+        .map_err(|_| ServiceError::Other("Failed to hash password".to_string()))?;
+
+    let admin = NewUser {
+        id: Uuid::new_v4(),
+        username: username.to_string(),
+        hashed_password,
+        full_name: req.full_name.trim().to_string(),
+        created_at: None,
+        updated_at: None,
+        is_admin: true,
+        email: None,
+    };
+
+    let admin = run_audited(
         pool,
-        vehicle_id,
-        new_brand,
-        new_model,
-        new_registration,
-        new_registration_expiry_date,
+        move |conn| queries::create_initial_admin(conn, &admin),
+        move |admin| {
+            admin.as_ref().map(|admin| {
+                audit(
+                    Some(admin.id),
+                    "setup",
+                    "user",
+                    Some(admin.id),
+                    None,
+                    snapshot(admin),
+                )
+            })
+        },
     )
-    .map_err(ServiceError::DbError)
+    .await?
+    .ok_or_else(|| ServiceError::Forbidden("Setup has already been completed".to_string()))?;
+
+    Ok(admin)
 }
 
-/// Service to delete a vehicle by its ID.
-///
-/// Calls the `delete_vehicle_by_id` query.
+/// Service to list the organizations a user belongs to.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle to delete.
+/// - `user_id`: The ID of the user.
+/// - `active_organization_id`: The session's active organization, flagged in the result.
 ///
 /// # Returns
-/// - `Ok(())`: If the vehicle was deleted.
-/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist.
+/// - `Ok(Vec<OrganizationWithRole>)`: The organizations, in the order the user joined them.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn delete_vehicle_by_id(pool: &DbPool, vehicle_id: Uuid) -> Result<(), ServiceError> {
-    match queries::delete_vehicle_by_id(pool, vehicle_id)? {
-        0 => Err(ServiceError::NotFound("Vehicle not found".to_string())),
-        _ => Ok(()),
-    }
+pub async fn get_organizations(
+    pool: &DbPool,
+    user_id: Uuid,
+    active_organization_id: Option<Uuid>,
+) -> Result<Vec<OrganizationWithRole>, ServiceError> {
+    let organizations = run_blocking(pool, move |pool| {
+        queries::get_organizations_by_user_id(pool, user_id)
+    })
+    .await?;
+
+    Ok(organizations
+        .into_iter()
+        .map(|(organization, role)| OrganizationWithRole {
+            active: Some(organization.id) == active_organization_id,
+            organization,
+            role,
+        })
+        .collect())
 }
 
-/// Service to create a new odometer entry.
-///
-/// Calls the `create_new_odometer` query to insert a new odometer record.
+/// Service to create a new organization, administered by its creator.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle for which the odometer entry is created.
-/// - `odometer_value`: The odometer reading.
-/// - `timestamp`: Optional timestamp for the odometer entry.
+/// - `user_id`: The ID of the creating user.
+/// - `name`: The name of the organization.
 ///
 /// # Returns
-/// - `Ok(Odometer)`: The newly created odometer record.
+/// - `Ok(Organization)`: The newly created organization.
+/// - `Err(ServiceError::ValidationError)`: If the name is blank.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn create_new_odometer(
+pub async fn create_organization(
     pool: &DbPool,
-    vehicle_id: Uuid,
-    odometer_value: f32,
-    timestamp: Option<NaiveDateTime>,
-) -> Result<Odometer, ServiceError> {
-    queries::create_new_odometer(pool, vehicle_id, odometer_value, timestamp)
-        .map_err(ServiceError::DbError)
+    user_id: Uuid,
+    name: String,
+) -> Result<Organization, ServiceError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Organization name must not be empty".to_string(),
+        ));
+    }
+
+    let organization = run_audited(
+        pool,
+        move |conn| queries::create_organization(conn, &name, user_id),
+        move |organization| {
+            [audit(
+                Some(user_id),
+                "create",
+                "organization",
+                Some(organization.id),
+                None,
+                snapshot(organization),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(organization)
 }
 
-/// Service to get the latest odometer entry for a vehicle.
-///
-/// Calls the `get_latest_odometer` query to retrieve the most recent odometer record.
+/// Service to switch the organization the current session works with.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
-/// - `vehicle_id`: The ID of the vehicle.
+/// - `user_id`: The ID of the user.
+/// - `token`: The session token of the current session.
+/// - `organization_id`: The organization to switch to.
 ///
 /// # Returns
-/// - `Ok(Odometer)`: The latest odometer record.
-/// - `Err(ServiceError::NotFound)`: If no odometer record exists for the vehicle.
+/// - `Ok(())`: If the session now works with the organization.
+/// - `Err(ServiceError::NotFound)`: If the user is not a member of the organization.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_latest_odometer(
+pub async fn switch_organization(
     pool: &DbPool,
+    user_id: Uuid,
+    token: &str,
+    organization_id: Uuid,
+) -> Result<(), ServiceError> {
+    require_organization_member(pool, user_id, organization_id).await?;
+
+    let token = token.to_string();
+    match run_blocking(pool, move |pool| {
+        queries::set_active_organization(pool, user_id, &token, organization_id)
+    })
+    .await?
+    {
+        0 => Err(ServiceError::NotFound("Session not found".to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Service to list the members of an organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the organization).
+/// - `organization_id`: The ID of the organization.
+///
+/// # Returns
+/// - `Ok(Vec<OrganizationMemberResponse>)`: The members, in the order they joined.
+/// - `Err(ServiceError::NotFound)`: If the user is not a member of the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_organization_members(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<Vec<OrganizationMemberResponse>, ServiceError> {
+    require_organization_member(pool, user_id, organization_id).await?;
+
+    let members = run_blocking(pool, move |pool| {
+        queries::get_organization_members(pool, organization_id)
+    })
+    .await?;
+
+    Ok(members
+        .into_iter()
+        .map(|(member, username, full_name)| OrganizationMemberResponse {
+            user_id: member.user_id,
+            username,
+            full_name,
+            role: member.role,
+            created_at: member.created_at,
+        })
+        .collect())
+}
+
+/// Service to invite an existing user to an organization.
+///
+/// The invitation grants nothing until the invited user accepts it. So that the inviter cannot
+/// probe which usernames exist, unknown and inactive users, existing members and users with a
+/// pending invitation are all answered the same way as a new invitation, without creating one.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the inviting user (must administer the organization).
+/// - `organization_id`: The ID of the organization.
+/// - `username`: The username of the user to invite.
+/// - `role`: The role offered: `admin` or `member`.
+///
+/// # Returns
+/// - `Ok(())`: If the invitation was handled.
+/// - `Err(ServiceError::NotFound)`: If the actor is not in the organization.
+/// - `Err(ServiceError::Forbidden)`: If the actor does not administer the organization.
+/// - `Err(ServiceError::ValidationError)`: If the role is unknown.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn invite_organization_member(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    username: String,
+    role: String,
+) -> Result<(), ServiceError> {
+    let role = OrganizationRole::parse(&role)
+        .ok_or_else(|| ServiceError::ValidationError("Role must be admin or member".to_string()))?;
+    require_organization_admin(pool, actor_id, organization_id).await?;
+
+    let Some(invitee) = run_blocking(pool, move |pool| {
+        queries::get_user_by_username(pool, &username)
+    })
+    .await?
+    .filter(|user| user.is_active) else {
+        return Ok(());
+    };
+
+    let member = OrganizationMember {
+        organization_id,
+        user_id: invitee.id,
+        role: role.as_str().to_string(),
+        created_at: Utc::now(),
+        invited_by: Some(actor_id),
+        accepted_at: None,
+    };
+    run_audited(
+        pool,
+        move |conn| queries::create_organization_member(conn, &member),
+        move |member| {
+            member.as_ref().map(|member| {
+                audit(
+                    Some(actor_id),
+                    "invite",
+                    "organization_member",
+                    Some(organization_id),
+                    None,
+                    snapshot(member),
+                )
+            })
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Service to list the pending organization invitations addressed to a user.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the invited user.
+///
+/// # Returns
+/// - `Ok(Vec<OrganizationInvitation>)`: The pending invitations, oldest first.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_pending_organization_invitations(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<Vec<OrganizationInvitation>, ServiceError> {
+    let invitations = run_blocking(pool, move |pool| {
+        queries::get_pending_organization_invitations(pool, user_id)
+    })
+    .await?;
+
+    Ok(invitations
+        .into_iter()
+        .map(|(member, organization)| OrganizationInvitation {
+            organization,
+            role: member.role,
+            invited_by: member.invited_by,
+            created_at: member.created_at,
+        })
+        .collect())
+}
+
+/// Service to accept a pending invitation to an organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the invited user.
+/// - `organization_id`: The ID of the organization.
+///
+/// # Returns
+/// - `Ok(OrganizationMember)`: The accepted membership.
+/// - `Err(ServiceError::NotFound)`: If the user has no pending invitation to the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn accept_organization_invitation(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<OrganizationMember, ServiceError> {
+    let member = run_audited(
+        pool,
+        move |conn| queries::accept_organization_member(conn, organization_id, user_id),
+        move |member| {
+            member.as_ref().map(|member| {
+                audit(
+                    Some(user_id),
+                    "accept",
+                    "organization_member",
+                    Some(organization_id),
+                    None,
+                    snapshot(member),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Invitation not found".to_string()))?;
+
+    Ok(member)
+}
+
+/// Service to remove a member from an organization, or withdraw an invitation.
+///
+/// Administrators may remove anyone; any member may leave, which is also how an invitation is
+/// declined. The member also loses access to the organization's vehicles. The last administrator
+/// cannot be removed.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user performing the removal.
+/// - `organization_id`: The ID of the organization.
+/// - `member_id`: The ID of the member to remove.
+///
+/// # Returns
+/// - `Ok(())`: If the member was removed.
+/// - `Err(ServiceError::NotFound)`: If the actor or the member is not in the organization.
+/// - `Err(ServiceError::Forbidden)`: If the actor removes someone else without being an administrator.
+/// - `Err(ServiceError::ValidationError)`: If the member is the organization's last administrator.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn remove_organization_member(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), ServiceError> {
+    if actor_id != member_id {
+        require_organization_admin(pool, actor_id, organization_id).await?;
+    }
+    run_blocking(pool, move |pool| {
+        queries::get_organization_member(pool, organization_id, member_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Member not found".to_string()))?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_organization_member(conn, organization_id, member_id),
+        move |removed| {
+            removed.as_ref().map(|removed| {
+                audit(
+                    Some(actor_id),
+                    "remove",
+                    "organization_member",
+                    Some(organization_id),
+                    snapshot(removed),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        ServiceError::ValidationError("An organization must keep at least one admin".to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Validates a vehicle identification number, including its check digit.
+///
+/// VINs are 17 characters long, leave out `I`, `O` and `Q`, and carry a check digit (`0` to `9`
+/// or `X`) in the ninth position, computed as in ISO 3779 / 49 CFR 565.
+///
+/// # Returns
+/// - `Ok(String)`: The VIN, uppercased and without spaces or dashes.
+/// - `Err(ServiceError::ValidationError)`: If the VIN is malformed or its check digit is wrong.
+fn normalize_vin(vin: &str) -> Result<String, ServiceError> {
+    const WEIGHTS: [u32; 17] = [8, 7, 6, 5, 4, 3, 2, 10, 0, 9, 8, 7, 6, 5, 4, 3, 2];
+
+    let vin: String = vin
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if vin.len() != 17 {
+        return Err(ServiceError::ValidationError(
+            "VIN must be 17 characters long".to_string(),
+        ));
+    }
+
+    let mut sum = 0;
+    for (c, weight) in vin.chars().zip(WEIGHTS) {
+        let value = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            'A' | 'J' => 1,
+            'B' | 'K' | 'S' => 2,
+            'C' | 'L' | 'T' => 3,
+            'D' | 'M' | 'U' => 4,
+            'E' | 'N' | 'V' => 5,
+            'F' | 'W' => 6,
+            'G' | 'P' | 'X' => 7,
+            'H' | 'Y' => 8,
+            'R' | 'Z' => 9,
+            _ => {
+                return Err(ServiceError::ValidationError(format!(
+                    "VIN must not contain '{}'",
+                    c
+                )))
+            }
+        };
+        sum += value * weight;
+    }
+    let check_digit = match sum % 11 {
+        10 => 'X',
+        remainder => char::from_digit(remainder, 10).unwrap_or('X'),
+    };
+    if vin.chars().nth(8) != Some(check_digit) {
+        return Err(ServiceError::ValidationError(
+            "VIN check digit does not match".to_string(),
+        ));
+    }
+    Ok(vin)
+}
+
+/// Validates the profile details of a new or updated vehicle, normalizing them.
+///
+/// # Returns
+/// - `Ok(VehicleChanges)`: The validated details; blank optional text fields are cleared, and a
+///   blank odometer unit is kept.
+/// - `Err(ServiceError::ValidationError)`: If a detail is invalid.
+fn validate_vehicle_changes(mut changes: VehicleChanges) -> Result<VehicleChanges, ServiceError> {
+    let non_blank =
+        |value: String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+    let blank_to_null =
+        |value: Option<Option<String>>| value.map(|value| value.and_then(non_blank));
+
+    changes.vin = blank_to_null(changes.vin)
+        .map(|vin| vin.map(|vin| normalize_vin(&vin)).transpose())
+        .transpose()?;
+    changes.colour = blank_to_null(changes.colour);
+    changes.fuel_type = blank_to_null(changes.fuel_type)
+        .map(|fuel_type| {
+            fuel_type
+                .map(|fuel_type| {
+                    FuelType::parse(&fuel_type.to_lowercase())
+                        .map(|fuel_type| fuel_type.as_str().to_string())
+                        .ok_or_else(|| {
+                            ServiceError::ValidationError(format!(
+                                "Fuel type must be one of petrol, diesel, ev, hybrid, lpg, got {}",
+                                fuel_type
+                            ))
+                        })
+                })
+                .transpose()
+        })
+        .transpose()?;
+    changes.odometer_unit = changes
+        .odometer_unit
+        .and_then(non_blank)
+        .map(|unit| {
+            OdometerUnit::parse(&unit.to_lowercase())
+                .map(|unit| unit.as_str().to_string())
+                .ok_or_else(|| {
+                    ServiceError::ValidationError(format!(
+                        "Odometer unit must be km or mi, got {}",
+                        unit
+                    ))
+                })
+        })
+        .transpose()?;
+
+    let today = Utc::now().date_naive();
+    if changes
+        .model_year
+        .flatten()
+        .is_some_and(|year| year < 1886 || year > today.year() + 1)
+    {
+        return Err(ServiceError::ValidationError(
+            "Model year is out of range".to_string(),
+        ));
+    }
+    if changes
+        .tank_capacity
+        .flatten()
+        .is_some_and(|capacity| capacity <= Decimal::ZERO)
+    {
+        return Err(ServiceError::ValidationError(
+            "Tank capacity must be positive".to_string(),
+        ));
+    }
+    if changes
+        .purchase_date
+        .flatten()
+        .is_some_and(|date| date > today)
+    {
+        return Err(ServiceError::ValidationError(
+            "Purchase date must not be in the future".to_string(),
+        ));
+    }
+    changes.purchase_price = changes
+        .purchase_price
+        .map(|price| {
+            price
+                .map(|price| check_amount(price, "Purchase price"))
+                .transpose()
+        })
+        .transpose()
+        .map_err(ServiceError::ValidationError)?;
+    Ok(changes)
+}
+
+/// Service to create a new vehicle.
+///
+/// Validates the input and calls the `create_vehicle` query. The creator must belong to the
+/// organization the vehicle is created in, and becomes the vehicle's owner.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `new_vehicle`: The vehicle data to insert.
+/// - `units`: The units the tank capacity is given in, and the vehicle is returned in.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The newly created vehicle.
+/// - `Err(ServiceError::ValidationError)`: If the profile details are invalid.
+/// - `Err(ServiceError::NotFound)`: If the creator is not a member of the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_vehicle(
+    pool: &DbPool,
+    new_vehicle: NewVehicle,
+    units: Units,
+) -> Result<Vehicle, ServiceError> {
+    require_organization_member(pool, new_vehicle.user_id, new_vehicle.organization_id).await?;
+
+    let profile = validate_vehicle_changes(VehicleChanges {
+        vin: Some(new_vehicle.vin),
+        model_year: Some(new_vehicle.model_year),
+        colour: Some(new_vehicle.colour),
+        fuel_type: Some(new_vehicle.fuel_type),
+        tank_capacity: Some(new_vehicle.tank_capacity),
+        odometer_unit: Some(new_vehicle.odometer_unit),
+        purchase_date: Some(new_vehicle.purchase_date),
+        purchase_price: Some(new_vehicle.purchase_price),
+        ..Default::default()
+    })?;
+    let fuel_type = profile.fuel_type.flatten();
+    let tank_capacity = profile
+        .tank_capacity
+        .flatten()
+        .map(|capacity| units.capacity_from_user(fuel_type.as_deref(), capacity));
+    tank_capacity
+        .map(check_capacity)
+        .transpose()
+        .map_err(ServiceError::ValidationError)?;
+    let new_vehicle = NewVehicle {
+        vin: profile.vin.flatten(),
+        model_year: profile.model_year.flatten(),
+        colour: profile.colour.flatten(),
+        fuel_type,
+        tank_capacity,
+        odometer_unit: profile
+            .odometer_unit
+            .unwrap_or_else(OdometerUnit::default_name),
+        purchase_date: profile.purchase_date.flatten(),
+        purchase_price: profile.purchase_price.flatten(),
+        ..new_vehicle
+    };
+
+    let vehicle = run_audited(
+        pool,
+        move |conn| queries::create_vehicle(conn, &new_vehicle),
+        move |vehicle| {
+            [audit(
+                Some(vehicle.user_id),
+                "create",
+                "vehicle",
+                Some(vehicle.id),
+                None,
+                snapshot(vehicle),
+            )]
+        },
+    )
+    .await?;
+    publish(
+        pool,
+        vehicle.id,
+        WebhookEvent::VehicleCreated,
+        snapshot(&vehicle),
+    )
+    .await;
+
+    Ok(units.vehicle(vehicle))
+}
+
+/// Service to get the vehicles of the caller's active organization that are shared with them.
+///
+/// Calls the `get_vehicles_by_user_id` query.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the user.
+/// - `organization_id`: The caller's active organization.
+/// - `include_archived`: Whether archived vehicles are returned too.
+///
+/// # Returns
+/// - `Ok(Vec<VehicleWithRole>)`: The user's vehicles, each with the user's role on it.
+///   Organization administrators get every vehicle of the organization, as owner.
+/// - `Err(ServiceError::NotFound)`: If the user is not a member of the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_vehicles_by_user_id(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    include_archived: bool,
+) -> Result<Vec<VehicleWithRole>, ServiceError> {
+    let vehicles = if require_organization_member(pool, user_id, organization_id).await?
+        == OrganizationRole::Admin
+    {
+        run_blocking(pool, move |pool| {
+            queries::get_vehicles_by_organization_id(pool, organization_id, include_archived)
+        })
+        .await?
+        .into_iter()
+        .map(|vehicle| (vehicle, VehicleRole::Owner.as_str().to_string()))
+        .collect()
+    } else {
+        run_blocking(pool, move |pool| {
+            queries::get_vehicles_by_user_id(pool, user_id, organization_id, include_archived)
+        })
+        .await?
+    };
+
+    let vehicle_ids: Vec<Uuid> = vehicles.iter().map(|(vehicle, _)| vehicle.id).collect();
+    let end_dates: HashMap<Uuid, NaiveDate> = run_blocking(pool, move |pool| {
+        queries::get_insurance_end_dates(pool, vehicle_ids)
+    })
+    .await?
+    .into_iter()
+    .filter_map(|(vehicle_id, end_date)| Some((vehicle_id, end_date?)))
+    .collect();
+
+    let today = Utc::now().date_naive();
+    Ok(vehicles
+        .into_iter()
+        .map(|(vehicle, role)| VehicleWithRole {
+            insurance: end_dates
+                .get(&vehicle.id)
+                .map(|&end_date| insurance_status(end_date, today)),
+            vehicle,
+            role,
+        })
+        .collect())
+}
+
+/// Service to get a vehicle by its ID.
+///
+/// Calls the `get_vehicle_by_id` query.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The vehicle data, if found.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_vehicle_by_id(pool: &DbPool, vehicle_id: Uuid) -> Result<Vehicle, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::get_vehicle_by_id(pool, vehicle_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Vehicle not found".to_string()))
+}
+
+/// Service to get the units a user enters and reads a vehicle's values in: their own units, with
+/// odometer readings in the unit of the vehicle's odometer.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `units`: The user's units.
+///
+/// # Returns
+/// - `Ok(Units)`: The units for the vehicle.
+/// - `Err(ServiceError::NotFound)`: If the vehicle is not shared with the user.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn vehicle_units(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    units: Units,
+) -> Result<Units, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+    let vehicle = get_vehicle_by_id(pool, vehicle_id).await?;
+    Ok(units.for_vehicle(&vehicle))
+}
+
+/// Service to get a vehicle shared with the requesting user.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(VehicleWithRole)`: The vehicle and the user's role on it.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist or is not shared with the user.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_vehicle_for_user(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<VehicleWithRole, ServiceError> {
+    let role = authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+    let vehicle = get_vehicle_by_id(pool, vehicle_id).await?;
+    let end_date = run_blocking(pool, move |pool| {
+        queries::get_insurance_end_dates(pool, vec![vehicle_id])
+    })
+    .await?
+    .into_iter()
+    .find_map(|(_, end_date)| end_date);
+
+    Ok(VehicleWithRole {
+        vehicle,
+        role: role.as_str().to_string(),
+        insurance: end_date.map(|end_date| insurance_status(end_date, Utc::now().date_naive())),
+    })
+}
+
+/// Service to update a vehicle by its ID.
+///
+/// Calls the `update_vehicle_by_id` query and validates the input fields. Changing the fuel type
+/// to or from electric clears the tank capacity, unless the update sets a new one.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user performing the update.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle to update.
+/// - `update`: The fields to update; fields left out are kept.
+/// - `units`: The units the tank capacity is given in, and the vehicle is returned in.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The updated vehicle data.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist or is not shared with the actor.
+/// - `Err(ServiceError::Forbidden)`: If the actor is only a viewer.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn update_vehicle_by_id(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    update: UpdateVehicleRequest,
+    units: Units,
+) -> Result<Vehicle, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    let before = get_vehicle_by_id(pool, vehicle_id).await?;
+
+    let changes = validate_vehicle_changes(VehicleChanges {
+        brand: update.brand,
+        model: update.model,
+        registration: update.registration,
+        registration_expiry_date: update.registration_expiry_date,
+        vin: update.vin,
+        model_year: update.model_year,
+        colour: update.colour,
+        fuel_type: update.fuel_type,
+        tank_capacity: update.tank_capacity,
+        odometer_unit: update.odometer_unit,
+        purchase_date: update.purchase_date,
+        purchase_price: update.purchase_price,
+    })?;
+    let fuel_type = changes
+        .fuel_type
+        .clone()
+        .unwrap_or_else(|| before.fuel_type.clone());
+    // A battery capacity is in kWh and a tank capacity in liters, so switching to or from
+    // electric clears the stored capacity unless a new one comes with the change
+    let switches_energy =
+        units::is_battery(fuel_type.as_deref()) != units::is_battery(before.fuel_type.as_deref());
+    let tank_capacity = match changes.tank_capacity {
+        None if switches_energy => Some(None),
+        tank_capacity => tank_capacity,
+    };
+    let tank_capacity = tank_capacity.map(|capacity| {
+        capacity.map(|capacity| units.capacity_from_user(fuel_type.as_deref(), capacity))
+    });
+    tank_capacity
+        .flatten()
+        .map(check_capacity)
+        .transpose()
+        .map_err(ServiceError::ValidationError)?;
+    let changes = VehicleChanges {
+        tank_capacity,
+        ..changes
+    };
+    let vehicle = run_audited(
+        pool,
+        move |conn| queries::update_vehicle_by_id(conn, vehicle_id, changes),
+        move |vehicle| {
+            [audit(
+                Some(actor_id),
+                "update",
+                "vehicle",
+                Some(vehicle.id),
+                snapshot(&before),
+                snapshot(vehicle),
+            )]
+        },
+    )
+    .await?;
+    publish(
+        pool,
+        vehicle.id,
+        WebhookEvent::VehicleUpdated,
+        snapshot(&vehicle),
+    )
+    .await;
+
+    Ok(units.vehicle(vehicle))
+}
+
+/// Service to archive a vehicle by its ID.
+///
+/// Archived vehicles are hidden from listings but keep their odometer and refuel history, and
+/// can be brought back with `restore_vehicle_by_id`.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user archiving the vehicle.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle to archive.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The archived vehicle.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist or is not shared with the actor.
+/// - `Err(ServiceError::Forbidden)`: If the actor is not an owner of the vehicle.
+/// - `Err(ServiceError::ValidationError)`: If the vehicle is already archived.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn archive_vehicle_by_id(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<Vehicle, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Owner,
+    )
+    .await?;
+    let before = get_vehicle_by_id(pool, vehicle_id).await?;
+
+    let vehicle = run_audited(
+        pool,
+        move |conn| queries::archive_vehicle_by_id(conn, vehicle_id),
+        move |vehicle| {
+            vehicle.as_ref().map(|vehicle| {
+                audit(
+                    Some(actor_id),
+                    "archive",
+                    "vehicle",
+                    Some(vehicle.id),
+                    snapshot(&before),
+                    snapshot(vehicle),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::ValidationError("Vehicle is already archived".to_string()))?;
+    publish(
+        pool,
+        vehicle.id,
+        WebhookEvent::VehicleDeleted,
+        snapshot(&vehicle),
+    )
+    .await;
+
+    Ok(vehicle)
+}
+
+/// Service to restore an archived vehicle by its ID.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user restoring the vehicle.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle to restore.
+///
+/// # Returns
+/// - `Ok(Vehicle)`: The restored vehicle.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist or is not shared with the actor.
+/// - `Err(ServiceError::Forbidden)`: If the actor is not an owner of the vehicle.
+/// - `Err(ServiceError::ValidationError)`: If the vehicle is not archived.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn restore_vehicle_by_id(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<Vehicle, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Owner,
+    )
+    .await?;
+    let before = get_vehicle_by_id(pool, vehicle_id).await?;
+
+    let vehicle = run_audited(
+        pool,
+        move |conn| queries::restore_vehicle_by_id(conn, vehicle_id),
+        move |vehicle| {
+            vehicle.as_ref().map(|vehicle| {
+                audit(
+                    Some(actor_id),
+                    "restore",
+                    "vehicle",
+                    Some(vehicle.id),
+                    snapshot(&before),
+                    snapshot(vehicle),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::ValidationError("Vehicle is not archived".to_string()))?;
+
+    Ok(vehicle)
+}
+
+/// Service to permanently delete a vehicle by its ID.
+///
+/// Only administrators may purge, since the vehicle's odometer and refuel history is deleted
+/// with it.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the administrator performing the purge.
+/// - `vehicle_id`: The ID of the vehicle to delete.
+///
+/// # Returns
+/// - `Ok(())`: If the vehicle was deleted.
+/// - `Err(ServiceError::Forbidden)`: If the actor is not an administrator.
+/// - `Err(ServiceError::NotFound)`: If the vehicle does not exist.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn purge_vehicle_by_id(
+    pool: &DbPool,
+    actor_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<(), ServiceError> {
+    require_admin(pool, actor_id).await?;
+    let before = get_vehicle_by_id(pool, vehicle_id).await?;
+
+    // The vehicle's members go with it, so the event has to be queued beforehand. Archived
+    // vehicles were announced as deleted already.
+    if before.archived_at.is_none() {
+        publish(
+            pool,
+            vehicle_id,
+            WebhookEvent::VehicleDeleted,
+            snapshot(&before),
+        )
+        .await;
+    }
+
+    let before = snapshot(&before);
+    match run_audited(
+        pool,
+        move |conn| queries::purge_vehicle_by_id(conn, vehicle_id),
+        move |purged| {
+            (*purged > 0).then(|| {
+                audit(
+                    Some(actor_id),
+                    "purge",
+                    "vehicle",
+                    Some(vehicle_id),
+                    before,
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    {
+        0 => Err(ServiceError::NotFound("Vehicle not found".to_string())),
+        _ => {
+            web::block(move || documents::remove_vehicle(vehicle_id)).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Service to list the members of a vehicle, including pending invitations.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Vec<VehicleMemberResponse>)`: The members, oldest first.
+/// - `Err(ServiceError::NotFound)`: If the vehicle is not shared with the user.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_vehicle_members(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<Vec<VehicleMemberResponse>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    let members = run_blocking(pool, move |pool| {
+        queries::get_vehicle_members(pool, vehicle_id)
+    })
+    .await?;
+
+    Ok(members
+        .into_iter()
+        .map(|(member, username, full_name)| VehicleMemberResponse {
+            user_id: member.user_id,
+            username,
+            full_name,
+            role: member.role,
+            invited_by: member.invited_by,
+            accepted_at: member.accepted_at,
+            created_at: member.created_at,
+        })
+        .collect())
+}
+
+/// Service to invite a user to a vehicle.
+///
+/// The invitation grants nothing until the invited user accepts it.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the inviting user (must be an owner of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `username`: The username of the user to invite.
+/// - `role`: The role offered: `owner`, `editor` or `viewer`.
+///
+/// # Returns
+/// - `Ok(VehicleMember)`: The pending membership.
+/// - `Err(ServiceError::NotFound)`: If the vehicle is not shared with the actor or the user does not exist.
+/// - `Err(ServiceError::Forbidden)`: If the actor is not an owner of the vehicle.
+/// - `Err(ServiceError::ValidationError)`: If the role is unknown, the vehicle is archived, or the user is already a member or not in the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn invite_vehicle_member(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    username: String,
+    role: String,
+) -> Result<VehicleMember, ServiceError> {
+    let role = VehicleRole::parse(&role).ok_or_else(|| {
+        ServiceError::ValidationError("Role must be owner, editor or viewer".to_string())
+    })?;
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Owner,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let invitee = run_blocking(pool, move |pool| {
+        queries::get_user_by_username(pool, &username)
+    })
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+
+    let invitee_id = invitee.id;
+    if require_organization_member(pool, invitee_id, organization_id)
+        .await
+        .is_err()
+    {
+        return Err(ServiceError::ValidationError(
+            "User is not a member of the vehicle's organization".to_string(),
+        ));
+    }
+
+    let existing = run_blocking(pool, move |pool| {
+        queries::get_vehicle_member(pool, vehicle_id, invitee_id)
+    })
+    .await?;
+    if existing.is_some() {
+        return Err(ServiceError::ValidationError(
+            "User is already a member of this vehicle or has a pending invitation".to_string(),
+        ));
+    }
+
+    let member = VehicleMember {
+        vehicle_id,
+        user_id: invitee.id,
+        role: role.as_str().to_string(),
+        invited_by: Some(actor_id),
+        accepted_at: None,
+        created_at: Utc::now(),
+    };
+    let member = run_audited(
+        pool,
+        move |conn| queries::create_vehicle_member(conn, &member),
+        move |member| {
+            [audit(
+                Some(actor_id),
+                "invite",
+                "vehicle_member",
+                Some(vehicle_id),
+                None,
+                snapshot(member),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(member)
+}
+
+/// Service to list the pending vehicle invitations addressed to a user.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the invited user.
+///
+/// # Returns
+/// - `Ok(Vec<VehicleInvitation>)`: The pending invitations, oldest first.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_pending_invitations(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<Vec<VehicleInvitation>, ServiceError> {
+    let invitations = run_blocking(pool, move |pool| {
+        queries::get_pending_invitations(pool, user_id)
+    })
+    .await?;
+
+    Ok(invitations
+        .into_iter()
+        .map(|(member, vehicle)| VehicleInvitation {
+            vehicle,
+            role: member.role,
+            invited_by: member.invited_by,
+            created_at: member.created_at,
+        })
+        .collect())
+}
+
+/// Service to accept a pending invitation to a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the invited user.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(VehicleMember)`: The accepted membership.
+/// - `Err(ServiceError::NotFound)`: If the user has no pending invitation to the vehicle.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn accept_vehicle_invitation(
+    pool: &DbPool,
+    user_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<VehicleMember, ServiceError> {
+    let member = run_audited(
+        pool,
+        move |conn| queries::accept_vehicle_member(conn, vehicle_id, user_id),
+        move |member| {
+            member.as_ref().map(|member| {
+                audit(
+                    Some(user_id),
+                    "accept",
+                    "vehicle_member",
+                    Some(vehicle_id),
+                    None,
+                    snapshot(member),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Invitation not found".to_string()))?;
+
+    Ok(member)
+}
+
+/// Service to remove a member from a vehicle, or withdraw an invitation.
+///
+/// Owners may remove anyone; any member may remove themselves, which is also how an
+/// invitation is declined. The last owner of a vehicle cannot be removed.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user performing the removal.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `member_id`: The ID of the member to remove.
+///
+/// # Returns
+/// - `Ok(())`: If the member was removed.
+/// - `Err(ServiceError::NotFound)`: If the member (or, for others, the vehicle) is not found.
+/// - `Err(ServiceError::Forbidden)`: If the actor removes someone else without being an owner.
+/// - `Err(ServiceError::ValidationError)`: If the member is the vehicle's last owner.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn remove_vehicle_member(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    member_id: Uuid,
+) -> Result<(), ServiceError> {
+    if actor_id != member_id {
+        authorize_vehicle(
+            pool,
+            actor_id,
+            organization_id,
+            vehicle_id,
+            VehicleRole::Owner,
+        )
+        .await?;
+    }
+
+    run_blocking(pool, move |pool| {
+        queries::get_vehicle_member(pool, vehicle_id, member_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Member not found".to_string()))?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_vehicle_member(conn, vehicle_id, member_id),
+        move |removed| {
+            removed.as_ref().map(|removed| {
+                audit(
+                    Some(actor_id),
+                    "remove",
+                    "vehicle_member",
+                    Some(vehicle_id),
+                    snapshot(removed),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        ServiceError::ValidationError("A vehicle must keep at least one owner".to_string())
+    })?;
+
+    Ok(())
+}
+
+/// Ensures a vehicle exists and is not archived before new readings are recorded against it,
+/// returning the vehicle.
+async fn require_active_vehicle(pool: &DbPool, vehicle_id: Uuid) -> Result<Vehicle, ServiceError> {
+    let vehicle = get_vehicle_by_id(pool, vehicle_id).await?;
+    match vehicle.archived_at {
+        Some(_) => Err(ServiceError::ValidationError(
+            "Vehicle is archived".to_string(),
+        )),
+        None => Ok(vehicle),
+    }
+}
+
+/// Amounts of money are stored as `NUMERIC(12, 2)`: to the cent, and below this limit.
+const AMOUNT_LIMIT: i64 = 10_000_000_000;
+
+/// Checks that an amount of money is not negative and fits in the database, rounding it to the
+/// cent it is stored with.
+///
+/// # Returns
+/// - `Ok(Decimal)`: The amount, rounded to the cent.
+/// - `Err(String)`: Why the amount is invalid.
+fn check_amount(amount: Decimal, field: &str) -> Result<Decimal, String> {
+    let amount = amount.round_dp(2);
+    if amount < Decimal::ZERO {
+        Err(format!("{} must not be negative", field))
+    } else if amount >= Decimal::from(AMOUNT_LIMIT) {
+        Err(format!("{} must be less than {}", field, AMOUNT_LIMIT))
+    } else {
+        Ok(amount)
+    }
+}
+
+/// Odometer readings are stored as `NUMERIC(12, 3)` kilometers: to the meter, and below this
+/// limit.
+const ODOMETER_LIMIT: i64 = 1_000_000_000;
+
+/// Fuel quantities are stored as `NUMERIC(10, 3)` liters: to the milliliter, and below this
+/// limit.
+const REFUEL_QUANTITY_LIMIT: i64 = 10_000_000;
+
+/// Checks that a tank capacity in liters, or battery capacity in kWh, is positive and fits in
+/// the database. Capacities are stored like fuel quantities.
+///
+/// # Returns
+/// - `Ok(())`: If the capacity can be stored.
+/// - `Err(String)`: Why the capacity is invalid.
+fn check_capacity(capacity: Decimal) -> Result<(), String> {
+    if capacity <= Decimal::ZERO {
+        Err("Tank capacity must be positive".to_string())
+    } else if capacity >= Decimal::from(REFUEL_QUANTITY_LIMIT) {
+        Err(format!(
+            "Tank capacity must be less than {} L or kWh",
+            REFUEL_QUANTITY_LIMIT
+        ))
+    } else if capacity.normalize().scale() > units::DECIMAL_PLACES {
+        Err(format!(
+            "Tank capacity must not have more than {} decimal places in L or kWh",
+            units::DECIMAL_PLACES
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that an odometer reading in kilometers is not negative and fits in the database.
+///
+/// # Returns
+/// - `Ok(())`: If the reading can be stored.
+/// - `Err(String)`: Why the reading is invalid.
+fn check_odometer_value(value: Decimal) -> Result<(), String> {
+    if value < Decimal::ZERO {
+        Err("Odometer value must not be negative".to_string())
+    } else if value >= Decimal::from(ODOMETER_LIMIT) {
+        Err(format!(
+            "Odometer value must be less than {} km",
+            ODOMETER_LIMIT
+        ))
+    } else if value.normalize().scale() > units::DECIMAL_PLACES {
+        Err(format!(
+            "Odometer value must not have more than {} decimal places in km",
+            units::DECIMAL_PLACES
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that a fuel quantity in liters is positive and fits in the database.
+///
+/// # Returns
+/// - `Ok(())`: If the quantity can be stored.
+/// - `Err(String)`: Why the quantity is invalid.
+fn check_refuel_quantity(quantity: Decimal) -> Result<(), String> {
+    if quantity <= Decimal::ZERO {
+        Err("Refuel quantity must be positive".to_string())
+    } else if quantity >= Decimal::from(REFUEL_QUANTITY_LIMIT) {
+        Err(format!(
+            "Refuel quantity must be less than {} L",
+            REFUEL_QUANTITY_LIMIT
+        ))
+    } else if quantity.normalize().scale() > units::DECIMAL_PLACES {
+        Err(format!(
+            "Refuel quantity must not have more than {} decimal places in L",
+            units::DECIMAL_PLACES
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Share by which a refuel may exceed the tank capacity, in percent, allowing for fuel in the
+/// filler neck and pump inaccuracy.
+const TANK_CAPACITY_TOLERANCE_PERCENT: i64 = 5;
+
+/// Checks that a refuel fits in the vehicle's tank, if its capacity is known. The battery
+/// capacity of an electric vehicle, in kWh, says nothing about refuels.
+///
+/// # Arguments
+/// - `vehicle`: The refueled vehicle.
+/// - `refuel_quantity`: The quantity in liters.
+/// - `units`: The units to describe the quantity and capacity in.
+///
+/// # Returns
+/// - `Ok(())`: If the quantity is possible.
+/// - `Err(String)`: Why the quantity is impossible.
+fn check_tank_capacity(
+    vehicle: &Vehicle,
+    refuel_quantity: Decimal,
+    units: Units,
+) -> Result<(), String> {
+    let Some(capacity) = vehicle
+        .tank_capacity
+        .filter(|_| !units::is_battery(vehicle.fuel_type.as_deref()))
+    else {
+        return Ok(());
+    };
+    let tolerance = Decimal::ONE + Decimal::new(TANK_CAPACITY_TOLERANCE_PERCENT, 2);
+    if refuel_quantity > capacity * tolerance {
+        Err(format!(
+            "Refuel quantity of {} {} exceeds the vehicle's tank capacity of {} {}",
+            units.volume_to_user(refuel_quantity),
+            units.volume.label(),
+            units.volume_to_user(capacity),
+            units.volume.label()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Determines the driver to record on a new odometer or refuel entry.
+///
+/// An explicitly given driver must have access to the vehicle; otherwise the driver assigned
+/// at the entry's time (or now) is used, if any.
+async fn resolve_driver(
+    pool: &DbPool,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    driver_id: Option<Uuid>,
+    timestamp: Option<DateTime<Utc>>,
+) -> Result<Option<Uuid>, ServiceError> {
+    match driver_id {
+        Some(driver_id) => {
+            require_vehicle_driver(pool, driver_id, organization_id, vehicle_id).await?;
+            Ok(Some(driver_id))
+        }
+        None => {
+            let at = timestamp.unwrap_or_else(Utc::now);
+            run_blocking(pool, move |pool| {
+                queries::get_assigned_driver(pool, vehicle_id, at)
+            })
+            .await
+        }
+    }
+}
+
+/// Ensures a user may be recorded as a driver of a vehicle, i.e. has access to it.
+async fn require_vehicle_driver(
+    pool: &DbPool,
+    driver_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<(), ServiceError> {
+    match authorize_vehicle(
+        pool,
+        driver_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(ServiceError::NotFound(_)) | Err(ServiceError::Forbidden(_)) => Err(
+            ServiceError::ValidationError("Driver must have access to this vehicle".to_string()),
+        ),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the instant a local date and time in `timezone` stands for. Of an ambiguous time the
+/// earlier instant is taken; a time skipped by a daylight saving transition is moved past the gap.
+fn local_to_utc(local: NaiveDateTime, timezone: Tz) -> DateTime<Utc> {
+    timezone
+        .from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(local + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|at| at.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// Returns the first and last instant of an inclusive range of days in `timezone`.
+fn day_bounds(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = local_to_utc(start_date.and_time(NaiveTime::MIN), timezone);
+    let end = local_to_utc(
+        (end_date + Duration::days(1)).and_time(NaiveTime::MIN),
+        timezone,
+    ) - Duration::microseconds(1);
+    (start, end)
+}
+
+/// Converts an inclusive range of days in the user's timezone into the range of timestamps it
+/// covers.
+fn date_range(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), ServiceError> {
+    if end_date < start_date {
+        return Err(ServiceError::ValidationError(
+            "End date must not be before start date".to_string(),
+        ));
+    }
+    Ok(day_bounds(start_date, end_date, timezone))
+}
+
+/// Service to create a new odometer entry.
+///
+/// Calls the `create_new_odometer` query to insert a new odometer record.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user recording the entry.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle for which the odometer entry is created.
+/// - `request`: The odometer reading, with optional timestamp and driver. The driver defaults
+///   to the one assigned at the entry's time.
+///
+/// # Returns
+/// - `Ok(Odometer)`: The newly created odometer record.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_new_odometer(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: NewOdometerRequest,
+) -> Result<Odometer, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let NewOdometerRequest {
+        odometer_value,
+        timestamp,
+        driver_id,
+    } = request;
+    check_odometer_value(odometer_value).map_err(ServiceError::ValidationError)?;
+    let driver_id = resolve_driver(pool, organization_id, vehicle_id, driver_id, timestamp).await?;
+
+    let odometer = run_audited(
+        pool,
+        move |conn| {
+            queries::create_new_odometer(
+                conn,
+                vehicle_id,
+                odometer_value,
+                timestamp,
+                actor_id,
+                driver_id,
+            )
+        },
+        move |odometer| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "odometer",
+                Some(odometer.id),
+                None,
+                snapshot(odometer),
+            )]
+        },
+    )
+    .await?;
+    publish(
+        pool,
+        vehicle_id,
+        WebhookEvent::OdometerCreated,
+        snapshot(&odometer),
+    )
+    .await;
+
+    Ok(odometer)
+}
+
+/// Service to get the latest odometer entry for a vehicle.
+///
+/// Calls the `get_latest_odometer` query to retrieve the most recent odometer record.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Odometer)`: The latest odometer record.
+/// - `Err(ServiceError::NotFound)`: If no odometer record exists for the vehicle.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_latest_odometer(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<Odometer, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_latest_odometer(pool, vehicle_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("No odometer record found".to_string()))
+}
+
+/// Service to get odometer time-series data for a vehicle.
+///
+/// Calls the `get_odometer_timeseries` query to retrieve odometer records within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: The start of the date range.
+/// - `end_date`: The end of the date range.
+///
+/// # Returns
+/// - `Ok(Vec<Odometer>)`: A list of odometer records within the specified range.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_odometer_timeseries(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<Odometer>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_odometer_timeseries(pool, vehicle_id, start_date, end_date)
+    })
+    .await
+}
+
+/// Service to create a new refuel event with an associated odometer entry.
+///
+/// Calls the `create_new_refuel` query to insert a refuel record linked to an odometer entry.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user recording the refuel.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The amount of fuel and the odometer reading, with optional timestamp and
+///   driver. The driver defaults to the one assigned at the event's time.
+/// - `units`: The units to describe the amount of fuel in when it is rejected.
+///
+/// # Returns
+/// - `Ok(RefuelWithOdometer)`: The newly created refuel record along with its odometer entry.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_new_refuel(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: NewRefuelRequest,
+    units: Units,
+) -> Result<RefuelWithOdometer, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    let vehicle = require_active_vehicle(pool, vehicle_id).await?;
+
+    let NewRefuelRequest {
+        refuel_quantity,
+        odometer_value,
+        timestamp,
+        driver_id,
+        cost,
+    } = request;
+    let cost = cost
+        .map(|cost| check_amount(cost, "Cost"))
+        .transpose()
+        .map_err(ServiceError::ValidationError)?;
+    check_odometer_value(odometer_value)
+        .and_then(|()| check_refuel_quantity(refuel_quantity))
+        .and_then(|()| check_tank_capacity(&vehicle, refuel_quantity, units))
+        .map_err(ServiceError::ValidationError)?;
+    let driver_id = resolve_driver(pool, organization_id, vehicle_id, driver_id, timestamp).await?;
+
+    let details = RefuelDetails {
+        refuel_quantity,
+        odometer_value,
+        timestamp,
+        driver_id,
+        cost,
+    };
+    let refuel = run_audited(
+        pool,
+        move |conn| queries::create_new_refuel(conn, vehicle_id, actor_id, details),
+        move |refuel| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "refuel",
+                Some(refuel.id),
+                None,
+                snapshot(refuel),
+            )]
+        },
+    )
+    .await?;
+    publish(
+        pool,
+        vehicle_id,
+        WebhookEvent::RefuelCreated,
+        snapshot(&refuel),
+    )
+    .await;
+
+    Ok(refuel)
+}
+
+/// Service to get the latest refuel event for a vehicle.
+///
+/// Calls the `get_latest_refuel` query to retrieve the most recent refuel record.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(RefuelWithOdometer)`: The latest refuel record.
+/// - `Err(ServiceError::NotFound)`: If no refuel record exists for the vehicle.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_latest_refuel(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<RefuelWithOdometer, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_latest_refuel(pool, vehicle_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("No refuel record found".to_string()))
+}
+
+/// Service to get refuel time-series data for a vehicle.
+///
+/// Calls the `get_refuel_timeseries` query to retrieve refuel records within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: The start of the date range.
+/// - `end_date`: The end of the date range.
+///
+/// # Returns
+/// - `Ok(Vec<RefuelWithOdometer>)`: A list of refuel records within the specified range.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_refuel_timeseries(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+) -> Result<Vec<RefuelWithOdometer>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_refuel_timeseries(pool, vehicle_id, start_date, end_date)
+    })
+    .await
+}
+
+pub async fn get_traveled_distance(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<Option<TraveledDistance>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+    let (start, end) = day_bounds(start_date, end_date, timezone);
+
+    run_blocking(pool, move |pool| {
+        queries::get_traveled_distance(pool, vehicle_id, start, end)
+    })
+    .await
+}
+
+/// Service to query the audit log, restricted to administrators.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user (must be an active administrator).
+/// - `filter`: Optional actor, entity and time range filters.
+///
+/// # Returns
+/// - `Ok(Vec<AuditLog>)`: The matching audit entries, newest first.
+/// - `Err(ServiceError::Forbidden)`: If the user is not an administrator.
+/// - `Err(ServiceError::ValidationError)`: If the limit is not positive.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_audit_logs(
+    pool: &DbPool,
+    user_id: Uuid,
+    filter: AuditLogQuery,
+) -> Result<Vec<AuditLog>, ServiceError> {
+    require_admin(pool, user_id).await?;
+
+    let limit = filter.limit.unwrap_or(DEFAULT_AUDIT_LIMIT);
+    if limit <= 0 {
+        return Err(ServiceError::ValidationError(
+            "Limit must be positive".to_string(),
+        ));
+    }
+    let limit = limit.min(MAX_AUDIT_LIMIT);
+
+    run_blocking(pool, move |pool| {
+        queries::get_audit_logs(pool, &filter, limit)
+    })
+    .await
+}
+
+/// Service to list the driver assignments of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(Vec<DriverAssignmentResponse>)`: The assignments, newest first.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_driver_assignments(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+) -> Result<Vec<DriverAssignmentResponse>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    let assignments = run_blocking(pool, move |pool| {
+        queries::get_driver_assignments(pool, vehicle_id)
+    })
+    .await?;
+
+    Ok(assignments
+        .into_iter()
+        .map(
+            |(assignment, username, full_name)| DriverAssignmentResponse {
+                assignment,
+                username,
+                full_name,
+            },
+        )
+        .collect())
+}
+
+/// Service to assign a driver to a vehicle (vehicle owners only).
+///
+/// A driver whose open-ended assignment started earlier is handed over from when the new
+/// assignment starts; any other overlap is rejected.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user making the assignment.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The driver's username and the assignment period.
+///
+/// # Returns
+/// - `Ok(DriverAssignment)`: The new assignment.
+/// - `Err(ServiceError::ValidationError)`: If the period is invalid or overlaps another
+///   assignment, or the driver has no access to the vehicle.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn assign_driver(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: AssignDriverRequest,
+) -> Result<DriverAssignment, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Owner,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let starts_at = request.starts_at.unwrap_or_else(Utc::now);
+    if request.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        return Err(ServiceError::ValidationError(
+            "Assignment must end after it starts".to_string(),
+        ));
+    }
+
+    let username = request.username;
+    let driver = run_blocking(pool, move |pool| {
+        queries::get_user_by_username(pool, &username)
+    })
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(|| ServiceError::NotFound("User not found".to_string()))?;
+    require_vehicle_driver(pool, driver.id, organization_id, vehicle_id).await?;
+
+    let assignment = NewDriverAssignment {
+        vehicle_id,
+        user_id: driver.id,
+        assigned_by: Some(actor_id),
+        starts_at,
+        ends_at: request.ends_at,
+    };
+    let (assignment, _) = run_audited(
+        pool,
+        move |conn| queries::create_driver_assignment(conn, &assignment),
+        move |created| {
+            let Some((assignment, ended)) = created else {
+                return Vec::new();
+            };
+            let mut entries = Vec::new();
+            if let Some(ended) = ended {
+                let mut before = ended.clone();
+                before.ends_at = None;
+                entries.push(audit(
+                    Some(actor_id),
+                    "update",
+                    "driver_assignment",
+                    Some(ended.id),
+                    snapshot(&before),
+                    snapshot(ended),
+                ));
+            }
+            entries.push(audit(
+                Some(actor_id),
+                "create",
+                "driver_assignment",
+                Some(assignment.id),
+                None,
+                snapshot(assignment),
+            ));
+            entries
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        ServiceError::ValidationError(
+            "Assignment overlaps an existing driver assignment".to_string(),
+        )
+    })?;
+
+    Ok(assignment)
+}
+
+/// Service to delete a driver assignment of a vehicle (vehicle owners only).
+///
+/// Entries already attributed to the driver keep their attribution.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user deleting the assignment.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `assignment_id`: The ID of the assignment.
+///
+/// # Returns
+/// - `Ok(())`: If the assignment was deleted.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such assignment.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn remove_driver_assignment(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    assignment_id: Uuid,
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Owner,
+    )
+    .await?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_driver_assignment(conn, vehicle_id, assignment_id),
+        move |removed| {
+            removed.as_ref().map(|removed| {
+                audit(
+                    Some(actor_id),
+                    "delete",
+                    "driver_assignment",
+                    Some(removed.id),
+                    snapshot(removed),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Driver assignment not found".to_string()))?;
+
+    Ok(())
+}
+
+/// Service to report the distance driven and fuel added per driver of a vehicle.
+///
+/// The report is derived from the odometer and refuel timeseries of the date range: each
+/// odometer reading attributes the distance since the previous reading to its driver (or its
+/// submitter, if no driver is recorded), so the distances add up to the traveled distance.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: First day of the range.
+/// - `end_date`: Last day of the range (inclusive).
+/// - `timezone`: The timezone the days are in.
+///
+/// # Returns
+/// - `Ok(Vec<DriverUsage>)`: One entry per driver, longest distance first.
+/// - `Err(ServiceError::ValidationError)`: If the range ends before it starts.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_driver_report(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<Vec<DriverUsage>, ServiceError> {
+    let (start, end) = date_range(start_date, end_date, timezone)?;
+
+    let odometers =
+        get_odometer_timeseries(pool, user_id, organization_id, vehicle_id, start, end).await?;
+    let refuels =
+        get_refuel_timeseries(pool, user_id, organization_id, vehicle_id, start, end).await?;
+
+    let mut usage: Vec<DriverUsage> = Vec::new();
+    let entry = |usage: &mut Vec<DriverUsage>, driver_id: Option<Uuid>| -> usize {
+        match usage.iter().position(|u| u.driver_id == driver_id) {
+            Some(index) => index,
+            None => {
+                usage.push(DriverUsage {
+                    driver_id,
+                    username: None,
+                    full_name: None,
+                    distance: Decimal::ZERO,
+                    refuel_quantity: Decimal::ZERO,
+                    odometer_readings: 0,
+                    refuels: 0,
+                });
+                usage.len() - 1
+            }
+        }
+    };
+
+    let mut previous_value: Option<Decimal> = None;
+    for odometer in &odometers {
+        let index = entry(&mut usage, odometer.driver_id.or(odometer.user_id));
+        if let Some(previous_value) = previous_value {
+            usage[index].distance += odometer.odometer_value - previous_value;
+        }
+        usage[index].odometer_readings += 1;
+        previous_value = Some(odometer.odometer_value);
+    }
+    for refuel in &refuels {
+        let index = entry(&mut usage, refuel.driver_id.or(refuel.user_id));
+        usage[index].refuel_quantity += refuel.refuel_quantity;
+        usage[index].refuels += 1;
+    }
+
+    let driver_ids: Vec<Uuid> = usage.iter().filter_map(|u| u.driver_id).collect();
+    let drivers = run_blocking(pool, move |pool| {
+        queries::get_users_by_ids(pool, driver_ids)
+    })
+    .await?;
+    for u in usage.iter_mut() {
+        if let Some(driver) = drivers.iter().find(|d| Some(d.id) == u.driver_id) {
+            u.username = Some(driver.username.clone());
+            u.full_name = Some(driver.full_name.clone());
+        }
+    }
+
+    usage.sort_by_key(|u| std::cmp::Reverse(u.distance));
+    Ok(usage)
+}
+
+/// Builds the response for a trip and its start and end odometer values.
+fn trip_response(
+    (trip, start_odometer_value, end_odometer_value): (Trip, Decimal, Decimal),
+) -> TripResponse {
+    TripResponse {
+        trip,
+        start_odometer_value,
+        end_odometer_value,
+        distance: end_odometer_value - start_odometer_value,
+    }
+}
+
+/// Validates trip details against the vehicle's other trips and odometer readings.
+///
+/// The trip must not overlap another trip, its readings must not go backwards, and they must fit
+/// between the readings recorded before, during and after it.
+async fn validate_trip(
+    pool: &DbPool,
+    vehicle_id: Uuid,
+    details: &TripDetails,
+    existing: Option<&Trip>,
+) -> Result<(), ServiceError> {
+    let invalid = |message: String| Err(ServiceError::ValidationError(message));
+
+    let category = TripCategory::parse(&details.category);
+    if category.is_none() {
+        return invalid("Category must be business or private".to_string());
+    }
+    if details.origin.is_empty() || details.destination.is_empty() {
+        return invalid("Origin and destination must not be empty".to_string());
+    }
+    if category == Some(TripCategory::Business) && details.purpose.is_empty() {
+        return invalid("Business trips need a purpose".to_string());
+    }
+    if details.ended_at <= details.started_at {
+        return invalid("Trip must end after it starts".to_string());
+    }
+    if let Err(message) = check_odometer_value(details.start_odometer_value)
+        .and_then(|()| check_odometer_value(details.end_odometer_value))
+    {
+        return invalid(message);
+    }
+    if details.end_odometer_value < details.start_odometer_value {
+        return invalid("End odometer must not be below start odometer".to_string());
+    }
+
+    let (start, end) = (details.started_at, details.ended_at);
+    let exclude_trip_id = existing.map(|trip| trip.id);
+    let overlapping = run_blocking(pool, move |pool| {
+        queries::count_overlapping_trips(pool, vehicle_id, start, end, exclude_trip_id)
+    })
+    .await?;
+    if overlapping > 0 {
+        return invalid("Trip overlaps another trip".to_string());
+    }
+
+    let exclude = existing
+        .map(|trip| vec![trip.start_odometer_id, trip.end_odometer_id])
+        .unwrap_or_default();
+    let SurroundingOdometer {
+        before,
+        within,
+        after,
+    } = run_blocking(pool, move |pool| {
+        queries::get_odometer_around(pool, vehicle_id, start, end, exclude)
+    })
+    .await?;
+
+    let at = |odometer: &Odometer| {
+        odometer
+            .timestamp
+            .map(|ts| ts.to_string())
+            .unwrap_or_default()
+    };
+    if let Some(before) = before.filter(|o| o.odometer_value > details.start_odometer_value) {
+        return invalid(format!(
+            "Start odometer is below the reading of {} at {}",
+            before.odometer_value,
+            at(&before)
+        ));
+    }
+    if let Some(after) = after.filter(|o| o.odometer_value < details.end_odometer_value) {
+        return invalid(format!(
+            "End odometer is above the reading of {} at {}",
+            after.odometer_value,
+            at(&after)
+        ));
+    }
+    if let Some(reading) = within.iter().find(|o| {
+        o.odometer_value < details.start_odometer_value
+            || o.odometer_value > details.end_odometer_value
+    }) {
+        return invalid(format!(
+            "The reading of {} at {} does not fit within the trip",
+            reading.odometer_value,
+            at(reading)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Service to list the trips of a vehicle that started within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: First day of the range.
+/// - `end_date`: Last day of the range (inclusive).
+/// - `timezone`: The timezone the days are in.
+///
+/// # Returns
+/// - `Ok(Vec<TripResponse>)`: The trips, oldest first.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_trips(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<Vec<TripResponse>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+    let (start, end) = date_range(start_date, end_date, timezone)?;
+
+    let trips = run_blocking(pool, move |pool| {
+        queries::get_trips(pool, vehicle_id, start, end)
+    })
+    .await?;
+
+    Ok(trips.into_iter().map(trip_response).collect())
+}
+
+/// Service to get a trip of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `trip_id`: The ID of the trip.
+///
+/// # Returns
+/// - `Ok(TripResponse)`: The trip.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such trip.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_trip(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    trip_id: Uuid,
+) -> Result<TripResponse, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_trip(pool, vehicle_id, trip_id)
+    })
+    .await?
+    .map(trip_response)
+    .ok_or_else(|| ServiceError::NotFound("Trip not found".to_string()))
+}
+
+/// Service to log a trip, recording its start and end odometer readings.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user logging the trip.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The trip details.
+///
+/// # Returns
+/// - `Ok(TripResponse)`: The new trip.
+/// - `Err(ServiceError::ValidationError)`: If the details are invalid or conflict with the
+///   vehicle's other trips or odometer readings.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_trip(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: NewTripRequest,
+) -> Result<TripResponse, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let driver_id = resolve_driver(
+        pool,
+        organization_id,
+        vehicle_id,
+        request.driver_id,
+        Some(request.started_at),
+    )
+    .await?;
+    let details = TripDetails {
+        started_at: request.started_at,
+        ended_at: request.ended_at,
+        start_odometer_value: request.start_odometer_value,
+        end_odometer_value: request.end_odometer_value,
+        origin: request.origin.trim().to_string(),
+        destination: request.destination.trim().to_string(),
+        purpose: request.purpose.trim().to_string(),
+        category: request.category,
+        driver_id,
+    };
+    validate_trip(pool, vehicle_id, &details, None).await?;
+
+    let trip = run_audited(
+        pool,
+        move |conn| queries::create_trip(conn, vehicle_id, actor_id, &details),
+        move |trip| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "trip",
+                Some(trip.0.id),
+                None,
+                snapshot(&trip.0),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(trip_response(trip))
+}
+
+/// Service to update a logged trip and its odometer readings.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user updating the trip.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `trip_id`: The ID of the trip.
+/// - `request`: The fields to change.
+///
+/// # Returns
+/// - `Ok(TripResponse)`: The updated trip.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such trip.
+/// - `Err(ServiceError::ValidationError)`: If the resulting trip is invalid or conflicts with
+///   the vehicle's other trips or odometer readings.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn update_trip(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    trip_id: Uuid,
+    request: UpdateTripRequest,
+) -> Result<TripResponse, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let (trip, start_odometer_value, end_odometer_value) = run_blocking(pool, move |pool| {
+        queries::get_trip(pool, vehicle_id, trip_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Trip not found".to_string()))?;
+
+    let driver_id = match request.driver_id {
+        Some(driver_id) => {
+            require_vehicle_driver(pool, driver_id, organization_id, vehicle_id).await?;
+            Some(driver_id)
+        }
+        None => trip.driver_id,
+    };
+    let trimmed = |value: Option<String>, current: &str| {
+        value.map_or_else(|| current.to_string(), |v| v.trim().to_string())
+    };
+    let details = TripDetails {
+        started_at: request.started_at.unwrap_or(trip.started_at),
+        ended_at: request.ended_at.unwrap_or(trip.ended_at),
+        start_odometer_value: request.start_odometer_value.unwrap_or(start_odometer_value),
+        end_odometer_value: request.end_odometer_value.unwrap_or(end_odometer_value),
+        origin: trimmed(request.origin, &trip.origin),
+        destination: trimmed(request.destination, &trip.destination),
+        purpose: trimmed(request.purpose, &trip.purpose),
+        category: request.category.unwrap_or_else(|| trip.category.clone()),
+        driver_id,
+    };
+    validate_trip(pool, vehicle_id, &details, Some(&trip)).await?;
+
+    let before = snapshot(&trip);
+    let updated = run_audited(
+        pool,
+        move |conn| queries::update_trip(conn, &trip, &details),
+        move |updated| {
+            [audit(
+                Some(actor_id),
+                "update",
+                "trip",
+                Some(trip_id),
+                before,
+                snapshot(&updated.0),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(trip_response(updated))
+}
+
+/// Service to delete a logged trip and its odometer readings.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user deleting the trip.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `trip_id`: The ID of the trip.
+///
+/// # Returns
+/// - `Ok(())`: If the trip was deleted.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such trip.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn delete_trip(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    trip_id: Uuid,
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_trip(conn, vehicle_id, trip_id),
+        move |trip| {
+            trip.as_ref().map(|trip| {
+                audit(
+                    Some(actor_id),
+                    "delete",
+                    "trip",
+                    Some(trip.id),
+                    snapshot(trip),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Trip not found".to_string()))?;
+
+    Ok(())
+}
+
+/// Service to summarize business and private mileage of a vehicle's trips within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: First day of the range.
+/// - `end_date`: Last day of the range (inclusive).
+/// - `timezone`: The timezone the days are in.
+///
+/// # Returns
+/// - `Ok(TripSummary)`: The summary of the trips that started within the range.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_trip_summary(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<TripSummary, ServiceError> {
+    let trips = get_trips(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        start_date,
+        end_date,
+        timezone,
+    )
+    .await?;
+
+    let mut summary = TripSummary {
+        start_date,
+        end_date,
+        business_distance: Decimal::ZERO,
+        private_distance: Decimal::ZERO,
+        business_trips: 0,
+        private_trips: 0,
+    };
+    for trip in &trips {
+        match TripCategory::parse(&trip.trip.category) {
+            Some(TripCategory::Business) => {
+                summary.business_distance += trip.distance;
+                summary.business_trips += 1;
+            }
+            Some(TripCategory::Private) => {
+                summary.private_distance += trip.distance;
+                summary.private_trips += 1;
+            }
+            None => {}
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Computes the distance driven and the energy used between consecutive charging sessions.
+///
+/// The energy used since the previous session is taken from the drop in state of charge when
+/// both readings and the battery capacity are known. Otherwise the energy delivered by the later
+/// session is counted, which assumes the battery is charged back to the same level each time.
+///
+/// # Arguments
+/// - `sessions`: The sessions with their odometer values, oldest first.
+/// - `vehicle`: The vehicle, for its battery capacity.
+///
+/// # Returns
+/// For each session after the first, the distance in kilometers and the energy in kWh, or `None`
+/// if the odometer did not advance.
+fn charging_consumption(
+    sessions: &[(ChargingSession, Decimal)],
+    vehicle: &Vehicle,
+) -> Vec<Option<(f64, f64)>> {
+    let battery_capacity = vehicle
+        .tank_capacity
+        .filter(|_| vehicle.fuel_type.as_deref() == Some(FuelType::Ev.as_str()));
+
+    sessions
+        .windows(2)
+        .map(|pair| {
+            let (previous, previous_odometer) = &pair[0];
+            let (session, odometer) = &pair[1];
+            let distance = (odometer - previous_odometer).to_f64().unwrap_or_default();
+            let energy = match (battery_capacity, previous.end_soc, session.start_soc) {
+                (Some(capacity), Some(end_soc), Some(start_soc)) if end_soc > start_soc => {
+                    capacity.to_f64().unwrap_or_default() * f64::from(end_soc - start_soc) / 100.0
+                }
+                _ => f64::from(session.energy_kwh),
+            };
+            (distance > 0.0).then_some((distance, energy))
+        })
+        .collect()
+}
+
+/// Builds the responses for charging sessions, computing each one's efficiency against the
+/// session before it.
+///
+/// # Arguments
+/// - `vehicle`: The vehicle, for its battery capacity.
+/// - `previous`: The last session before `sessions`, if any.
+/// - `sessions`: The sessions with their odometer values, oldest first.
+fn charging_session_responses(
+    vehicle: &Vehicle,
+    previous: Option<(ChargingSession, Decimal)>,
+    sessions: Vec<(ChargingSession, Decimal)>,
+) -> Vec<ChargingSessionResponse> {
+    let skip = usize::from(previous.is_some());
+    let sessions: Vec<_> = previous.into_iter().chain(sessions).collect();
+    let consumption = std::iter::once(None).chain(charging_consumption(&sessions, vehicle));
+
+    sessions
+        .into_iter()
+        .zip(consumption)
+        .skip(skip)
+        .map(
+            |((session, odometer_value), used)| ChargingSessionResponse {
+                session,
+                odometer_value,
+                distance: used.map(|(distance, _)| distance),
+                kwh_per_100_distance: used.map(|(distance, energy)| energy / distance * 100.0),
+            },
+        )
+        .collect()
+}
+
+/// Service to record a charging session of a vehicle, with the odometer reading taken when it
+/// started.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user recording the session.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The session details. The driver defaults to the one assigned when the session
+///   started.
+///
+/// # Returns
+/// - `Ok(ChargingSessionResponse)`: The new session, with its efficiency since the previous one.
+/// - `Err(ServiceError::ValidationError)`: If the details are invalid or the vehicle runs on
+///   petrol, diesel or LPG only.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_charging_session(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: NewChargingSessionRequest,
+) -> Result<ChargingSessionResponse, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    let vehicle = require_active_vehicle(pool, vehicle_id).await?;
+
+    let invalid = |message: &str| Err(ServiceError::ValidationError(message.to_string()));
+    if matches!(
+        vehicle.fuel_type.as_deref().and_then(FuelType::parse),
+        Some(FuelType::Petrol | FuelType::Diesel | FuelType::Lpg)
+    ) {
+        return invalid("Only electric and hybrid vehicles have charging sessions");
+    }
+    let charger_type = match ChargerType::parse(&request.charger_type.trim().to_lowercase()) {
+        Some(charger_type) => charger_type,
+        None => return invalid("Charger type must be ac or dc"),
+    };
+    if request.energy_kwh <= 0.0 {
+        return invalid("Energy must be positive");
+    }
+    let soc_range = 0.0..=100.0;
+    if [request.start_soc, request.end_soc]
+        .into_iter()
+        .flatten()
+        .any(|soc| !soc_range.contains(&soc))
+    {
+        return invalid("State of charge must be between 0 and 100 percent");
+    }
+    if let (Some(start_soc), Some(end_soc)) = (request.start_soc, request.end_soc) {
+        if end_soc < start_soc {
+            return invalid("State of charge must not drop while charging");
+        }
+    }
+    if request.duration_minutes.is_some_and(|minutes| minutes <= 0) {
+        return invalid("Duration must be positive");
+    }
+    if let Err(message) = check_odometer_value(request.odometer_value) {
+        return invalid(&message);
+    }
+    let cost = match request
+        .cost
+        .map(|cost| check_amount(cost, "Cost"))
+        .transpose()
+    {
+        Ok(cost) => cost,
+        Err(message) => return invalid(&message),
+    };
+
+    let driver_id = resolve_driver(
+        pool,
+        organization_id,
+        vehicle_id,
+        request.driver_id,
+        request.started_at,
+    )
+    .await?;
+    let details = ChargingSessionDetails {
+        odometer_value: request.odometer_value,
+        started_at: request.started_at,
+        energy_kwh: request.energy_kwh,
+        charger_type: charger_type.as_str().to_string(),
+        start_soc: request.start_soc,
+        end_soc: request.end_soc,
+        duration_minutes: request.duration_minutes,
+        cost,
+        location: request
+            .location
+            .map(|location| location.trim().to_string())
+            .filter(|location| !location.is_empty()),
+        driver_id,
+    };
+    let (session, odometer_value) = run_audited(
+        pool,
+        move |conn| queries::create_charging_session(conn, vehicle_id, actor_id, &details),
+        move |(session, _)| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "charging_session",
+                Some(session.id),
+                None,
+                snapshot(session),
+            )]
+        },
+    )
+    .await?;
+    publish(
+        pool,
+        vehicle_id,
+        WebhookEvent::ChargingSessionCreated,
+        snapshot(&session),
+    )
+    .await;
+
+    let started_at = session.started_at;
+    let previous = run_blocking(pool, move |pool| {
+        queries::get_previous_charging_session(pool, vehicle_id, started_at)
+    })
+    .await?;
+    let mut responses =
+        charging_session_responses(&vehicle, previous, vec![(session, odometer_value)]);
+    responses
+        .pop()
+        .ok_or_else(|| ServiceError::Other("Charging session was not created".to_string()))
+}
+
+/// Service to list the charging sessions of a vehicle that started within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: First day of the range.
+/// - `end_date`: Last day of the range (inclusive).
+/// - `timezone`: The timezone the days are in.
+///
+/// # Returns
+/// - `Ok(Vec<ChargingSessionResponse>)`: The sessions, oldest first, each with its efficiency
+///   since the session before it.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_charging_sessions(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<Vec<ChargingSessionResponse>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+    let vehicle = get_vehicle_by_id(pool, vehicle_id).await?;
+    let (start, end) = date_range(start_date, end_date, timezone)?;
+
+    let (previous, sessions) = run_blocking(pool, move |pool| {
+        Ok((
+            queries::get_previous_charging_session(pool, vehicle_id, start)?,
+            queries::get_charging_sessions(pool, vehicle_id, start, end)?,
+        ))
+    })
+    .await?;
+
+    Ok(charging_session_responses(&vehicle, previous, sessions))
+}
+
+/// Service to summarize the charging sessions of a vehicle within a date range.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `start_date`: First day of the range.
+/// - `end_date`: Last day of the range (inclusive).
+/// - `timezone`: The timezone the days are in.
+///
+/// # Returns
+/// - `Ok(ChargingSummary)`: The energy, cost and average efficiency of the sessions.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_charging_summary(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    timezone: Tz,
+) -> Result<ChargingSummary, ServiceError> {
+    let sessions = get_charging_sessions(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        start_date,
+        end_date,
+        timezone,
+    )
+    .await?;
+
+    let mut total_distance = 0.0;
+    let mut energy_used = 0.0;
+    for session in &sessions {
+        if let (Some(distance), Some(efficiency)) = (session.distance, session.kwh_per_100_distance)
+        {
+            total_distance += distance;
+            energy_used += efficiency * distance / 100.0;
+        }
+    }
+
+    Ok(ChargingSummary {
+        start_date,
+        end_date,
+        sessions: sessions.len(),
+        energy_kwh: sessions.iter().map(|s| s.session.energy_kwh).sum(),
+        cost: sessions.iter().filter_map(|s| s.session.cost).sum(),
+        distance: total_distance,
+        kwh_per_100_distance: (total_distance > 0.0).then(|| energy_used / total_distance * 100.0),
+    })
+}
+
+/// Service to delete a charging session and its odometer reading.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user deleting the session.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `session_id`: The ID of the session.
+///
+/// # Returns
+/// - `Ok(())`: If the session was deleted.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such session.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn delete_charging_session(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    session_id: Uuid,
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_charging_session(conn, vehicle_id, session_id),
+        move |session| {
+            session.as_ref().map(|session| {
+                audit(
+                    Some(actor_id),
+                    "delete",
+                    "charging_session",
+                    Some(session.id),
+                    snapshot(session),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Charging session not found".to_string()))?;
+
+    Ok(())
+}
+
+/// Length of the rows of a mileage report.
+#[derive(Debug, Clone, Copy)]
+enum ReportPeriod {
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl ReportPeriod {
+    /// Parses a period name as accepted by the report endpoints.
+    fn parse(period: &str) -> Option<Self> {
+        match period {
+            "monthly" => Some(ReportPeriod::Monthly),
+            "quarterly" => Some(ReportPeriod::Quarterly),
+            "yearly" => Some(ReportPeriod::Yearly),
+            _ => None,
+        }
+    }
+
+    /// Number of months in a period.
+    fn months(&self) -> u32 {
+        match self {
+            ReportPeriod::Monthly => 1,
+            ReportPeriod::Quarterly => 3,
+            ReportPeriod::Yearly => 12,
+        }
+    }
+
+    /// Returns the first day of the period containing `date`.
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let month0 = date.month0() - date.month0() % self.months();
+        NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1).unwrap_or(date)
+    }
+
+    /// Returns the label of the period starting on `start`.
+    fn label(&self, start: NaiveDate) -> String {
+        match self {
+            ReportPeriod::Monthly => start.format("%Y-%m").to_string(),
+            ReportPeriod::Quarterly => format!("{}-Q{}", start.year(), start.month0() / 3 + 1),
+            ReportPeriod::Yearly => start.year().to_string(),
+        }
+    }
+}
+
+/// Longest range a mileage report may cover, in days.
+const MAX_REPORT_DAYS: i64 = 3660;
+
+/// Service to build a periodic mileage report of a vehicle.
+///
+/// Each row covers one month, quarter or year, clipped to the report's range. Its distance is
+/// the traveled distance of the period (see `get_traveled_distance`); fuel and cost come from
+/// the refuel history and the business share from the trip logbook.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `query`: The period length and the range of the report.
+/// - `timezone`: The timezone the days of the report are in.
+///
+/// # Returns
+/// - `Ok(MileageReport)`: The report.
+/// - `Err(ServiceError::ValidationError)`: If the period is unknown or the range is invalid or
+///   longer than ten years.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_mileage_report(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    query: ReportQuery,
+    timezone: Tz,
+) -> Result<MileageReport, ServiceError> {
+    let period = ReportPeriod::parse(&query.period).ok_or_else(|| {
+        ServiceError::ValidationError("Period must be monthly, quarterly or yearly".to_string())
+    })?;
+    let (start, end) = date_range(query.start_date, query.end_date, timezone)?;
+    if (query.end_date - query.start_date).num_days() > MAX_REPORT_DAYS {
+        return Err(ServiceError::ValidationError(
+            "Reports may cover at most ten years".to_string(),
+        ));
+    }
+    let vehicle = get_vehicle_for_user(pool, user_id, organization_id, vehicle_id)
+        .await?
+        .vehicle;
+
+    // Periods clipped to the report's range
+    let mut periods = Vec::new();
+    let mut period_start = period.start_of(query.start_date);
+    while period_start <= query.end_date {
+        let next = period_start
+            .checked_add_months(Months::new(period.months()))
+            .ok_or_else(|| ServiceError::Other("Report date out of range".to_string()))?;
+        periods.push((
+            period.label(period_start),
+            period_start.max(query.start_date),
+            next.pred_opt().unwrap_or(next).min(query.end_date),
+        ));
+        period_start = next;
+    }
+
+    let ranges: Vec<(DateTime<Utc>, DateTime<Utc>)> = periods
+        .iter()
+        .map(|p| day_bounds(p.1, p.2, timezone))
+        .collect();
+    let (distances, refuels, trips, policies) = run_blocking(pool, move |pool| {
+        let distances = ranges
+            .into_iter()
+            .map(|(from, to)| queries::get_traveled_distance(pool, vehicle_id, from, to))
+            .collect::<Result<Vec<_>, _>>()?;
+        let refuels = queries::get_refuel_timeseries(pool, vehicle_id, start, end)?;
+        let trips = queries::get_trips(pool, vehicle_id, start, end)?;
+        let policies = queries::get_insurance_policies(pool, vehicle_id)?;
+        Ok::<_, DbError>((distances, refuels, trips, policies))
+    })
+    .await?;
+
+    let within = |at: DateTime<Utc>, from: NaiveDate, to: NaiveDate| {
+        let day = at.with_timezone(&timezone).date_naive();
+        day >= from && day <= to
+    };
+    let mut rows: Vec<ReportRow> = periods
+        .into_iter()
+        .zip(distances)
+        .map(|((label, from, to), distance)| {
+            let mut row = ReportRow {
+                period: label,
+                start_date: from,
+                end_date: to,
+                distance: distance.map_or(Decimal::ZERO, |d| d.traveled_distance),
+                refuel_quantity: Decimal::ZERO,
+                cost: Decimal::ZERO,
+                insurance_cost: Decimal::ZERO,
+                total_cost: Decimal::ZERO,
+                business_distance: Decimal::ZERO,
+                private_distance: Decimal::ZERO,
+                business_share: None,
+            };
+            for refuel in refuels
+                .iter()
+                .filter(|r| r.timestamp.is_some_and(|at| within(at, from, to)))
+            {
+                row.refuel_quantity += refuel.refuel_quantity;
+                row.cost += refuel.cost.unwrap_or(Decimal::ZERO);
+            }
+            row.insurance_cost = policies
+                .iter()
+                .map(|policy| prorated_premium(policy, from, to))
+                .sum();
+            row.total_cost = row.cost + row.insurance_cost;
+            for (trip, start_value, end_value) in
+                trips.iter().filter(|t| within(t.0.started_at, from, to))
+            {
+                match TripCategory::parse(&trip.category) {
+                    Some(TripCategory::Business) => {
+                        row.business_distance += end_value - start_value
+                    }
+                    Some(TripCategory::Private) => row.private_distance += end_value - start_value,
+                    None => {}
+                }
+            }
+            row
+        })
+        .collect();
+
+    let mut total = ReportRow {
+        period: "Total".to_string(),
+        start_date: query.start_date,
+        end_date: query.end_date,
+        distance: Decimal::ZERO,
+        refuel_quantity: Decimal::ZERO,
+        cost: Decimal::ZERO,
+        insurance_cost: Decimal::ZERO,
+        total_cost: Decimal::ZERO,
+        business_distance: Decimal::ZERO,
+        private_distance: Decimal::ZERO,
+        business_share: None,
+    };
+    for row in &rows {
+        total.distance += row.distance;
+        total.refuel_quantity += row.refuel_quantity;
+        total.cost += row.cost;
+        total.insurance_cost += row.insurance_cost;
+        total.total_cost += row.total_cost;
+        total.business_distance += row.business_distance;
+        total.private_distance += row.private_distance;
+    }
+    for row in rows.iter_mut().chain(std::iter::once(&mut total)) {
+        if row.distance > Decimal::ZERO {
+            row.business_share = (row.business_distance / row.distance)
+                .min(Decimal::ONE)
+                .to_f32();
+        }
+    }
+
+    Ok(MileageReport {
+        vehicle,
+        period: query.period,
+        start_date: query.start_date,
+        end_date: query.end_date,
+        rows,
+        total,
+    })
+}
+
+/// Formats a mileage report as CSV, one line per period followed by the totals.
+///
+/// # Arguments
+/// - `report`: The report to format.
+///
+/// # Returns
+/// - `String`: The CSV document, with a header line.
+pub fn mileage_report_csv(report: &MileageReport) -> String {
+    let mut csv = csv_record(&[
+        "period",
+        "start_date",
+        "end_date",
+        "distance",
+        "refuel_quantity",
+        "cost",
+        "insurance_cost",
+        "total_cost",
+        "business_distance",
+        "private_distance",
+        "business_share_percent",
+    ]);
+    for row in report.rows.iter().chain(std::iter::once(&report.total)) {
+        csv.push_str(&csv_record(&[
+            row.period.clone(),
+            row.start_date.to_string(),
+            row.end_date.to_string(),
+            format!("{:.2}", row.distance),
+            format!("{:.2}", row.refuel_quantity),
+            format!("{:.2}", row.cost),
+            format!("{:.2}", row.insurance_cost),
+            format!("{:.2}", row.total_cost),
+            format!("{:.2}", row.business_distance),
+            format!("{:.2}", row.private_distance),
+            csv_optional(
+                row.business_share
+                    .map(|share| format!("{:.1}", share * 100.0)),
+            ),
+        ]));
+    }
+    csv
+}
+
+/// A CSV document produced chunk by chunk, so large exports are streamed to the client.
+pub type CsvStream = LocalBoxStream<'static, Result<web::Bytes, ServiceError>>;
+
+/// Quotes a CSV field when it contains a comma, a double quote or a line break (RFC 4180).
+///
+/// Spreadsheet applications evaluate text starting with `=`, `+`, `-`, `@`, a tab or a carriage
+/// return as a formula, so such text is prefixed with `'` to keep it text. Numbers are kept as
+/// they are.
+fn csv_field(value: &str) -> Cow<'_, str> {
+    let value =
+        if value.starts_with(['=', '+', '-', '@', '\t', '\r']) && value.parse::<f64>().is_err() {
+            Cow::Owned(format!("'{}", value))
+        } else {
+            Cow::Borrowed(value)
+        };
+    if value.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        value
+    }
+}
+
+/// Formats one CSV record, terminated by CRLF as RFC 4180 requires.
+fn csv_record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut record = fields
+        .iter()
+        .map(|field| csv_field(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    record.push_str("\r\n");
+    record
+}
+
+/// Formats an optional CSV field, leaving it empty when there is no value.
+fn csv_optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Builds a CSV stream: the header first, then the records of each vehicle in turn.
+///
+/// The records of a vehicle are only queried once the previous chunk has been sent.
+fn csv_stream<F>(pool: &DbPool, header: &[&str], vehicles: Vec<Vehicle>, records: F) -> CsvStream
+where
+    F: Fn(&DbPool, &Vehicle) -> Result<String, DbError> + Clone + Send + 'static,
+{
+    let header = web::Bytes::from(csv_record(header));
+    let pool = pool.clone();
+    let chunks = stream::iter(vehicles).then(move |vehicle| {
+        let pool = pool.clone();
+        let records = records.clone();
+        async move {
+            let chunk = run_blocking(&pool, move |pool| records(pool, &vehicle)).await?;
+            Ok(web::Bytes::from(chunk))
+        }
+    });
+    stream::once(async move { Ok(header) })
+        .chain(chunks)
+        .boxed_local()
+}
+
+/// Resolves the vehicles covered by an export: the given vehicle, or every vehicle the user can
+/// view in the organization (archived ones included) when no vehicle is given.
+async fn export_vehicles(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Option<Uuid>,
+) -> Result<Vec<Vehicle>, ServiceError> {
+    match vehicle_id {
+        Some(vehicle_id) => Ok(vec![
+            get_vehicle_for_user(pool, user_id, organization_id, vehicle_id)
+                .await?
+                .vehicle,
+        ]),
+        None => Ok(
+            get_vehicles_by_user_id(pool, user_id, organization_id, true)
+                .await?
+                .into_iter()
+                .map(|v| v.vehicle)
+                .collect(),
+        ),
+    }
+}
+
+/// Service to export the vehicles the user can view as CSV.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `include_archived`: Whether archived vehicles are exported too.
+/// - `units`: The units tank capacities are exported in.
+///
+/// # Returns
+/// - `Ok(String)`: The CSV document, with a header line.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn export_vehicles_csv(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    include_archived: bool,
+    units: Units,
+) -> Result<String, ServiceError> {
+    let vehicles =
+        get_vehicles_by_user_id(pool, user_id, organization_id, include_archived).await?;
+
+    let mut csv = csv_record(&[
+        "id",
+        "brand",
+        "model",
+        "registration",
+        "registration_expiry_date",
+        "vin",
+        "model_year",
+        "colour",
+        "fuel_type",
+        "tank_capacity",
+        "odometer_unit",
+        "purchase_date",
+        "purchase_price",
+        "role",
+        "created_at",
+        "archived_at",
+    ]);
+    for VehicleWithRole { vehicle, role, .. } in vehicles {
+        let vehicle = units.vehicle(vehicle);
+        csv.push_str(&csv_record(&[
+            vehicle.id.to_string(),
+            vehicle.brand,
+            vehicle.model,
+            vehicle.registration,
+            vehicle.registration_expiry_date.to_string(),
+            csv_optional(vehicle.vin),
+            csv_optional(vehicle.model_year),
+            csv_optional(vehicle.colour),
+            csv_optional(vehicle.fuel_type),
+            csv_optional(vehicle.tank_capacity),
+            vehicle.odometer_unit,
+            csv_optional(vehicle.purchase_date),
+            csv_optional(vehicle.purchase_price),
+            role,
+            csv_optional(vehicle.created_at),
+            csv_optional(vehicle.archived_at),
+        ]));
+    }
+    Ok(csv)
+}
+
+/// Service to export odometer readings as a CSV stream.
+///
+/// Calls the `get_odometer_timeseries` query for each exported vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The vehicle to export, or `None` for every vehicle the user can view.
+/// - `start_date` and `end_date`: The days to export (inclusive).
+/// - `preferences`: The units and timezone values are exported in.
+///
+/// # Returns
+/// - `Ok(CsvStream)`: The CSV document, with a header line, oldest reading first per vehicle.
+/// - `Err(ServiceError)`: If the vehicle is not shared with the user or the range is invalid.
+pub async fn export_odometer_csv(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Option<Uuid>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    preferences: UserPreferences,
+) -> Result<CsvStream, ServiceError> {
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+    let (start, end) = date_range(start_date, end_date, timezone)?;
+    let vehicles = export_vehicles(pool, user_id, organization_id, vehicle_id).await?;
+
+    let header = [
+        "id",
+        "vehicle_id",
+        "registration",
+        "timestamp",
+        "odometer_value",
+        "user_id",
+        "driver_id",
+    ];
+    Ok(csv_stream(pool, &header, vehicles, move |pool, vehicle| {
+        let readings = queries::get_odometer_timeseries(pool, vehicle.id, start, end)?;
+        Ok(readings
+            .into_iter()
+            .map(|reading| {
+                let reading = units.for_vehicle(vehicle).odometer(reading);
+                csv_record(&[
+                    reading.id.to_string(),
+                    vehicle.id.to_string(),
+                    vehicle.registration.clone(),
+                    csv_optional(
+                        reading
+                            .timestamp
+                            .map(|at| at.with_timezone(&timezone).to_rfc3339()),
+                    ),
+                    reading.odometer_value.normalize().to_string(),
+                    csv_optional(reading.user_id),
+                    csv_optional(reading.driver_id),
+                ])
+            })
+            .collect())
+    }))
+}
+
+/// Service to export refuels as a CSV stream.
+///
+/// Calls the `get_refuel_timeseries` query for each exported vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The vehicle to export, or `None` for every vehicle the user can view.
+/// - `start_date` and `end_date`: The days to export (inclusive).
+/// - `preferences`: The units and timezone values are exported in.
+///
+/// # Returns
+/// - `Ok(CsvStream)`: The CSV document, with a header line, oldest refuel first per vehicle.
+/// - `Err(ServiceError)`: If the vehicle is not shared with the user or the range is invalid.
+pub async fn export_refuel_csv(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Option<Uuid>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    preferences: UserPreferences,
+) -> Result<CsvStream, ServiceError> {
+    let units = preferences.units();
+    let timezone = preferences.timezone();
+    let (start, end) = date_range(start_date, end_date, timezone)?;
+    let vehicles = export_vehicles(pool, user_id, organization_id, vehicle_id).await?;
+
+    let header = [
+        "id",
+        "vehicle_id",
+        "registration",
+        "timestamp",
+        "odometer_value",
+        "refuel_quantity",
+        "cost",
+        "user_id",
+        "driver_id",
+    ];
+    Ok(csv_stream(pool, &header, vehicles, move |pool, vehicle| {
+        let refuels = queries::get_refuel_timeseries(pool, vehicle.id, start, end)?;
+        Ok(refuels
+            .into_iter()
+            .map(|refuel| {
+                let refuel = units.for_vehicle(vehicle).refuel(refuel);
+                csv_record(&[
+                    refuel.id.to_string(),
+                    vehicle.id.to_string(),
+                    vehicle.registration.clone(),
+                    csv_optional(
+                        refuel
+                            .timestamp
+                            .map(|at| at.with_timezone(&timezone).to_rfc3339()),
+                    ),
+                    refuel.odometer_value.normalize().to_string(),
+                    refuel.refuel_quantity.normalize().to_string(),
+                    csv_optional(refuel.cost),
+                    csv_optional(refuel.user_id),
+                    csv_optional(refuel.driver_id),
+                ])
+            })
+            .collect())
+    }))
+}
+
+/// Splits a CSV document into records, following RFC 4180.
+///
+/// Quoted fields may contain delimiters, doubled quotes and line breaks; records end with CRLF or
+/// LF. Blank lines are skipped. Each record comes with the line it starts on.
+fn parse_csv(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            record.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+            record.push(std::mem::take(&mut field));
+            if record.len() > 1 || !record[0].is_empty() {
+                records.push((record_line, std::mem::take(&mut record)));
+            }
+            record.clear();
+            line += 1;
+            record_line = line;
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(format!(
+            "Quoted field starting on line {} is not terminated",
+            record_line
+        ));
+    }
+    if !record.is_empty() || !field.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/// Parses an imported date or date-time, using `format` when given and RFC 3339 or ISO 8601
+/// otherwise.
+///
+/// Values without an offset are local times in `timezone`; a date without time stands for
+/// midnight.
+fn parse_import_timestamp(
+    value: &str,
+    format: Option<&str>,
+    timezone: Tz,
+) -> Option<DateTime<Utc>> {
+    if format.is_none() {
+        if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+            return Some(at.with_timezone(&Utc));
+        }
+    }
+    let formats = match format {
+        Some(format) => vec![format],
+        None => vec![
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d",
+        ],
+    };
+    formats.into_iter().find_map(|format| {
+        NaiveDateTime::parse_from_str(value, format)
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(value, format)
+                    .ok()
+                    .map(|date| date.and_time(NaiveTime::MIN))
+            })
+            .map(|local| local_to_utc(local, timezone))
+    })
+}
+
+/// Parses an imported odometer reading, fuel quantity or cost.
+fn parse_import_decimal(value: &str, field: &str) -> Result<Decimal, String> {
+    value
+        .parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(value))
+        .map_err(|_| format!("{} '{}' is not a number", field, value))
+}
+
+/// Most data rows accepted in one import.
+const MAX_IMPORT_ROWS: usize = 10_000;
+
+/// Service to import refuels of a vehicle from a CSV document.
+///
+/// The CSV columns are mapped to refuel fields by name. Each row is validated: the timestamp
+/// must parse, the odometer reading must not be negative, the fuel quantity must be positive and
+/// the cost, if any, must not be negative. Odometer readings must also keep increasing over
+/// time, together with the readings already recorded for the vehicle, and must not repeat a
+/// recorded reading (so importing a document twice is rejected). The driver of each refuel is
+/// the one assigned at its time.
+///
+/// Rows are only imported when none was rejected, all in one transaction.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user importing the refuels.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The CSV document, its column mapping and format, and whether this is a dry run.
+/// - `units`: The units of the fuel quantities in the document; odometer readings are in the
+///   vehicle's odometer unit.
+/// - `timezone`: The timezone of timestamps in the document that have no offset.
+///
+/// # Returns
+/// - `Ok(ImportReport)`: How many rows were imported, and the rejected rows.
+/// - `Err(ServiceError::ValidationError)`: If the document cannot be parsed, a mapped column is
+///   missing or there are too many rows.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn import_refuels(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: RefuelImportRequest,
+    units: Units,
+    timezone: Tz,
+) -> Result<ImportReport, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    let vehicle = require_active_vehicle(pool, vehicle_id).await?;
+    let units = units.for_vehicle(&vehicle);
+
+    let RefuelImportRequest {
+        csv,
+        mapping,
+        delimiter,
+        date_format,
+        dry_run,
+    } = request;
+
+    let mut records = parse_csv(&csv, delimiter.unwrap_or(','))
+        .map_err(ServiceError::ValidationError)?
+        .into_iter();
+    let (_, header) = records
+        .next()
+        .ok_or_else(|| ServiceError::ValidationError("CSV document is empty".to_string()))?;
+    let records: Vec<(usize, Vec<String>)> = records.collect();
+    if records.len() > MAX_IMPORT_ROWS {
+        return Err(ServiceError::ValidationError(format!(
+            "At most {} rows can be imported at once",
+            MAX_IMPORT_ROWS
+        )));
+    }
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim() == name.trim())
+            .ok_or_else(|| ServiceError::ValidationError(format!("Column '{}' not found", name)))
+    };
+    let timestamp_column = column(&mapping.timestamp)?;
+    let odometer_column = column(&mapping.odometer_value)?;
+    let quantity_column = column(&mapping.refuel_quantity)?;
+    let cost_column = mapping.cost.as_deref().map(column).transpose()?;
+
+    // Validate each row on its own
+    let mut errors = Vec::new();
+    let mut refuels: Vec<(usize, RefuelDetails)> = Vec::new();
+    for (row, record) in &records {
+        let cell = |column: usize| record.get(column).map_or("", |value| value.trim());
+        let mut reject = |message: String| errors.push(ImportRowError { row: *row, message });
+
+        let timestamp = match parse_import_timestamp(
+            cell(timestamp_column),
+            date_format.as_deref(),
+            timezone,
+        ) {
+            Some(timestamp) => Some(timestamp),
+            None => {
+                reject(format!(
+                    "Timestamp '{}' is not a valid date",
+                    cell(timestamp_column)
+                ));
+                None
+            }
+        };
+        let odometer_value = match parse_import_decimal(cell(odometer_column), "Odometer value")
+            .map(|value| units.reading_from_user(value))
+            .and_then(|value| check_odometer_value(value).map(|()| value))
+        {
+            Ok(value) => Some(value),
+            Err(message) => {
+                reject(message);
+                None
+            }
+        };
+        let refuel_quantity = match parse_import_decimal(cell(quantity_column), "Refuel quantity")
+            .map(|quantity| units.volume_from_user(quantity))
+            .and_then(|quantity| {
+                check_refuel_quantity(quantity)
+                    .and_then(|()| check_tank_capacity(&vehicle, quantity, units))
+                    .map(|()| quantity)
+            }) {
+            Ok(quantity) => Some(quantity),
+            Err(message) => {
+                reject(message);
+                None
+            }
+        };
+        let cost = match cost_column.map(cell).filter(|value| !value.is_empty()) {
+            None => Ok(None),
+            Some(value) => match parse_import_decimal(value, "Cost")
+                .and_then(|cost| check_amount(cost, "Cost"))
+            {
+                Ok(cost) => Ok(Some(cost)),
+                Err(message) => {
+                    reject(message);
+                    Err(())
+                }
+            },
+        };
+
+        if let (Some(timestamp), Some(odometer_value), Some(refuel_quantity), Ok(cost)) =
+            (timestamp, odometer_value, refuel_quantity, cost)
+        {
+            refuels.push((
+                *row,
+                RefuelDetails {
+                    refuel_quantity,
+                    odometer_value,
+                    timestamp: Some(timestamp),
+                    driver_id: None,
+                    cost,
+                },
+            ));
+        }
+    }
+
+    // Odometer readings must keep increasing, together with the vehicle's recorded readings
+    let timestamps = refuels.iter().filter_map(|(_, refuel)| refuel.timestamp);
+    if let (Some(first), Some(last)) = (timestamps.clone().min(), timestamps.max()) {
+        let surrounding = run_blocking(pool, move |pool| {
+            queries::get_odometer_around(pool, vehicle_id, first, last, Vec::new())
+        })
+        .await?;
+        let mut readings: Vec<(DateTime<Utc>, Decimal, Option<usize>)> = surrounding
+            .before
+            .into_iter()
+            .chain(surrounding.within)
+            .chain(surrounding.after)
+            .filter_map(|reading| {
+                reading
+                    .timestamp
+                    .map(|at| (at, reading.odometer_value, None))
+            })
+            .collect();
+        readings.extend(refuels.iter().filter_map(|(row, refuel)| {
+            refuel
+                .timestamp
+                .map(|at| (at, refuel.odometer_value, Some(*row)))
+        }));
+        readings.sort_by_key(|&(at, _, row)| (at, row));
+
+        let mut rejected = std::collections::BTreeSet::new();
+        for pair in readings.windows(2) {
+            let ((earlier_at, earlier, earlier_row), (later_at, later, later_row)) =
+                (pair[0], pair[1]);
+            if later == earlier && later_at == earlier_at {
+                // Sorted after the recorded reading, so the imported row is the later one
+                if let Some(row) = later_row.filter(|row| rejected.insert(*row)) {
+                    errors.push(ImportRowError {
+                        row,
+                        message: format!(
+                            "Odometer value {} is already recorded at {}",
+                            units.reading_to_user(later),
+                            later_at
+                        ),
+                    });
+                }
+                continue;
+            }
+            if later >= earlier {
+                continue;
+            }
+            match (later_row, earlier_row) {
+                (Some(row), _) if rejected.insert(row) => errors.push(ImportRowError {
+                    row,
+                    message: format!(
+                        "Odometer value {} is lower than {} recorded at {}",
+                        units.reading_to_user(later),
+                        units.reading_to_user(earlier),
+                        earlier_at
+                    ),
+                }),
+                (None, Some(row)) if rejected.insert(row) => errors.push(ImportRowError {
+                    row,
+                    message: format!(
+                        "Odometer value {} is higher than {} recorded at {}",
+                        units.reading_to_user(earlier),
+                        units.reading_to_user(later),
+                        later_at
+                    ),
+                }),
+                _ => {}
+            }
+        }
+        errors.sort_by_key(|error| error.row);
+    }
+
+    let mut report = ImportReport {
+        dry_run,
+        rows: records.len(),
+        imported: 0,
+        errors,
+    };
+    if dry_run || !report.errors.is_empty() || refuels.is_empty() {
+        return Ok(report);
+    }
+
+    let refuels: Vec<RefuelDetails> = refuels.into_iter().map(|(_, refuel)| refuel).collect();
+    let refuels = run_blocking(pool, move |pool| {
+        refuels
+            .into_iter()
+            .map(|refuel| {
+                let at = refuel.timestamp.unwrap_or_else(Utc::now);
+                Ok(RefuelDetails {
+                    driver_id: queries::get_assigned_driver(pool, vehicle_id, at)?,
+                    ..refuel
+                })
+            })
+            .collect::<Result<Vec<_>, DbError>>()
+    })
+    .await?;
+    let imported = run_audited(
+        pool,
+        move |conn| queries::import_refuels(conn, vehicle_id, actor_id, refuels),
+        move |imported| {
+            [audit(
+                Some(actor_id),
+                "import",
+                "refuel",
+                None,
+                None,
+                Some(serde_json::json!({
+                    "vehicle_id": vehicle_id,
+                    "refuel_ids": imported.iter().map(|refuel| refuel.id).collect::<Vec<_>>(),
+                })),
+            )]
+        },
+    )
+    .await?;
+    for refuel in &imported {
+        publish(
+            pool,
+            vehicle_id,
+            WebhookEvent::RefuelCreated,
+            snapshot(refuel),
+        )
+        .await;
+    }
+
+    report.imported = imported.len();
+    Ok(report)
+}
+
+/// Value of the `format` field of backup documents.
+const BACKUP_FORMAT: &str = "vimana-backup";
+
+/// Version of the backup documents written by this server.
+const BACKUP_VERSION: u32 = 2;
+
+/// Service to back up the vehicles a user owns in the active organization, with their history.
+///
+/// Archived vehicles are included.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+///
+/// # Returns
+/// - `Ok(Backup)`: The backup document.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn export_backup(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<Backup, ServiceError> {
+    let vehicles: Vec<Vehicle> = get_vehicles_by_user_id(pool, user_id, organization_id, true)
+        .await?
+        .into_iter()
+        .filter(|v| v.role == VehicleRole::Owner.as_str())
+        .map(|v| v.vehicle)
+        .collect();
+
+    let vehicles = run_blocking(pool, move |pool| {
+        vehicles
+            .into_iter()
+            .map(|vehicle| queries::get_vehicle_history(pool, vehicle))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .await?;
+
+    Ok(Backup {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        exported_at: Utc::now(),
+        user_id,
+        vehicles,
+    })
+}
+
+/// Service to restore a backup into the active organization.
+///
+/// The vehicles are restored as new vehicles owned by the requesting user, with new IDs for
+/// every record. Records attributed to the user who made the backup are attributed to the
+/// requesting user; other user references are dropped, as are the policy documents of
+/// insurance policies. Either everything is restored or nothing is.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user.
+/// - `organization_id`: The caller's active organization.
+/// - `backup`: The backup document.
+///
+/// # Returns
+/// - `Ok(Vec<Vehicle>)`: The restored vehicles.
+/// - `Err(ServiceError::ValidationError)`: If the document is not a supported backup or its
+///   records are inconsistent.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn restore_backup(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    mut backup: Backup,
+) -> Result<Vec<Vehicle>, ServiceError> {
+    require_organization_member(pool, user_id, organization_id).await?;
+
+    if backup.format != BACKUP_FORMAT {
+        return Err(ServiceError::ValidationError(
+            "Document is not a backup".to_string(),
+        ));
+    }
+    if !(1..=BACKUP_VERSION).contains(&backup.version) {
+        return Err(ServiceError::ValidationError(format!(
+            "Backup version {} is not supported",
+            backup.version
+        )));
+    }
+    if backup.version == 1 {
+        // Version 1 kept the readings of mile odometers in miles
+        let miles = Units {
+            odometer: OdometerUnit::Miles,
+            ..Units::default()
+        };
+        for history in &mut backup.vehicles {
+            if OdometerUnit::parse(&history.vehicle.odometer_unit) == Some(OdometerUnit::Miles) {
+                for reading in &mut history.odometer {
+                    reading.odometer_value = miles.reading_from_user(reading.odometer_value);
+                }
+            }
+        }
+    }
+
+    for history in &backup.vehicles {
+        let registration = &history.vehicle.registration;
+        let vehicle = &history.vehicle;
+        if vehicle
+            .fuel_type
+            .as_deref()
+            .is_some_and(|fuel_type| FuelType::parse(fuel_type).is_none())
+            || OdometerUnit::parse(&vehicle.odometer_unit).is_none()
+            || vehicle
+                .tank_capacity
+                .is_some_and(|capacity| check_capacity(capacity).is_err())
+            || vehicle
+                .purchase_price
+                .is_some_and(|price| check_amount(price, "Purchase price").is_err())
+        {
+            return Err(ServiceError::ValidationError(format!(
+                "Vehicle {} has an invalid fuel type, odometer unit, tank capacity or price",
+                registration
+            )));
+        }
+        let odometer_ids: std::collections::HashSet<Uuid> =
+            history.odometer.iter().map(|reading| reading.id).collect();
+        let referenced = history
+            .refuels
+            .iter()
+            .map(|refuel| refuel.odometer_id)
+            .chain(
+                history
+                    .trips
+                    .iter()
+                    .flat_map(|trip| [trip.start_odometer_id, trip.end_odometer_id]),
+            )
+            .chain(
+                history
+                    .charging_sessions
+                    .iter()
+                    .map(|session| session.odometer_id),
+            );
+        for id in referenced {
+            if !odometer_ids.contains(&id) {
+                return Err(ServiceError::ValidationError(format!(
+                    "Vehicle {} refers to odometer reading {}, which is not in the backup",
+                    registration, id
+                )));
+            }
+        }
+        if let Some(trip) = history
+            .trips
+            .iter()
+            .find(|trip| TripCategory::parse(&trip.category).is_none())
+        {
+            return Err(ServiceError::ValidationError(format!(
+                "Trip {} of vehicle {} has an unknown category",
+                trip.id, registration
+            )));
+        }
+        if let Some(session) = history.charging_sessions.iter().find(|session| {
+            ChargerType::parse(&session.charger_type).is_none()
+                || session.energy_kwh <= 0.0
+                || [session.start_soc, session.end_soc]
+                    .into_iter()
+                    .flatten()
+                    .any(|soc| !(0.0..=100.0).contains(&soc))
+                || session
+                    .cost
+                    .is_some_and(|cost| check_amount(cost, "Cost").is_err())
+        }) {
+            return Err(ServiceError::ValidationError(format!(
+                "Charging session {} of vehicle {} has an invalid charger type, energy, state of \
+                 charge or cost",
+                session.id, registration
+            )));
+        }
+        if history
+            .odometer
+            .iter()
+            .any(|reading| check_odometer_value(reading.odometer_value).is_err())
+            || history.refuels.iter().any(|refuel| {
+                check_refuel_quantity(refuel.refuel_quantity).is_err()
+                    || refuel
+                        .cost
+                        .is_some_and(|cost| check_amount(cost, "Cost").is_err())
+            })
+        {
+            return Err(ServiceError::ValidationError(format!(
+                "Vehicle {} has out of range readings, quantities or costs",
+                registration
+            )));
+        }
+        if let Some(policy) = history.insurance_policies.iter().find(|policy| {
+            policy.provider.trim().is_empty()
+                || policy.policy_number.trim().is_empty()
+                || CoverageType::parse(&policy.coverage_type).is_none()
+                || check_amount(policy.premium, "Premium").is_err()
+                || policy.end_date < policy.start_date
+        }) {
+            return Err(ServiceError::ValidationError(format!(
+                "Insurance policy {} of vehicle {} is missing its provider or policy number, or has \
+                 an invalid coverage type, premium or term",
+                policy.id, registration
+            )));
+        }
+    }
+
+    let original_user_id = backup.user_id;
+    run_audited(
+        pool,
+        move |conn| {
+            queries::restore_vehicle_histories(
+                conn,
+                user_id,
+                organization_id,
+                original_user_id,
+                backup.vehicles,
+            )
+        },
+        move |vehicles| {
+            vehicles
+                .iter()
+                .map(|vehicle| {
+                    audit(
+                        Some(user_id),
+                        "import",
+                        "vehicle",
+                        Some(vehicle.id),
+                        None,
+                        snapshot(vehicle),
+                    )
+                })
+                .collect::<Vec<_>>()
+        },
+    )
+    .await
+}
+
+/// Picks the reminder window a registration expiring in `days_left` days falls in: the smallest
+/// window (in days) that is at least `days_left`, or `overdue` once the registration expired.
+fn registration_reminder_window(days_left: i64, windows: &[i64]) -> Option<String> {
+    if days_left < 0 {
+        return Some("overdue".to_string());
+    }
+    windows
+        .iter()
+        .filter(|&&window| days_left <= window)
+        .min()
+        .map(|window| format!("{}d", window))
+}
+
+/// Service to notify vehicle owners of registrations that expire soon or have expired.
+///
+/// Each owner is notified once per reminder window (e.g. 30, 7 and 1 days before expiry) and
+/// once after expiry. A window that was missed, e.g. while the server was down, is skipped in
+/// favour of the current one. Renewing the registration (changing its expiry date) starts the
+/// reminders over.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `today`: The current date.
+/// - `windows`: The reminder windows, in days before expiry.
+/// - `channels`: The names of the channels to deliver the reminders through.
+///
+/// # Returns
+/// - `Ok(Vec<Notification>)`: The notifications created by this run.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_registration_reminders(
+    pool: &DbPool,
+    today: NaiveDate,
+    windows: Vec<i64>,
+    channels: Vec<&'static str>,
+) -> Result<Vec<Notification>, ServiceError> {
+    let until = today + Duration::days(windows.iter().copied().max().unwrap_or(0));
+
+    run_blocking(pool, move |pool| {
+        let mut created = Vec::new();
+        for (vehicle, owner_id) in queries::get_expiring_registrations(pool, until)? {
+            let expiry = vehicle.registration_expiry_date;
+            let days_left = (expiry - today).num_days();
+            let Some(window) = registration_reminder_window(days_left, &windows) else {
+                continue;
+            };
+
+            let title = match days_left {
+                ..=-1 => format!("Registration of {} has expired", vehicle.registration),
+                0 => format!("Registration of {} expires today", vehicle.registration),
+                1 => format!("Registration of {} expires tomorrow", vehicle.registration),
+                _ => format!(
+                    "Registration of {} expires in {} days",
+                    vehicle.registration, days_left
+                ),
+            };
+            let notification = NewNotification {
+                user_id: owner_id,
+                vehicle_id: Some(vehicle.id),
+                kind: NotificationKind::RegistrationExpiry.as_str().to_string(),
+                title,
+                body: format!(
+                    "The registration of your {} {} ({}) {} on {}.",
+                    vehicle.brand,
+                    vehicle.model,
+                    vehicle.registration,
+                    if days_left < 0 { "expired" } else { "expires" },
+                    expiry
+                ),
+                dedup_key: format!("registration_expiry:{}:{}:{}", vehicle.id, expiry, window),
+            };
+            if let Some(notification) =
+                queries::create_notification(pool, &notification, &channels)?
+            {
+                created.push(notification);
+            }
+        }
+        Ok(created)
+    })
+    .await
+}
+
+/// Number of earlier refuels whose fuel economy makes up the baseline of a fuel economy alert.
+const FUEL_ECONOMY_BASELINE_REFUELS: usize = 5;
+
+/// Minimum number of earlier fuel economies needed before a drop is reported.
+const FUEL_ECONOMY_MIN_BASELINE: usize = 3;
+
+/// How far back refuels are loaded to compute the fuel economy baseline.
+const FUEL_ECONOMY_HISTORY_DAYS: i64 = 365;
+
+/// Computes the fuel economy (distance per unit of fuel) of each refuel that follows another,
+/// assuming every refuel fills the tank: the distance since the previous refuel divided by the
+/// quantity refueled.
+///
+/// # Arguments
+/// - `refuels`: The refuels, oldest first.
+///
+/// # Returns
+/// The refuels with a valid fuel economy, oldest first, with their economy.
+fn fuel_economies(refuels: &[RefuelWithOdometer]) -> Vec<(&RefuelWithOdometer, f64)> {
+    refuels
+        .windows(2)
+        .filter_map(|pair| {
+            let distance = (pair[1].odometer_value - pair[0].odometer_value).to_f64()?;
+            let quantity = pair[1].refuel_quantity.to_f64()?;
+            (distance > 0.0 && quantity > 0.0).then(|| (&pair[1], distance / quantity))
+        })
+        .collect()
+}
+
+/// Service to notify vehicle owners when the fuel economy of a recent refuel drops well below
+/// the vehicle's recent average, which often points at a mechanical problem or a fuel leak.
+///
+/// The economy of the latest refuel is compared against the average of up to
+/// `FUEL_ECONOMY_BASELINE_REFUELS` refuels before it. Each refuel is reported at most once.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `since`: Only refuels from this time on are checked.
+/// - `drop_percent`: How far below the average, in percent, the economy has to drop.
+/// - `channels`: The names of the channels to deliver the alerts through.
+///
+/// # Returns
+/// - `Ok(Vec<Notification>)`: The notifications created by this run.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_fuel_economy_alerts(
+    pool: &DbPool,
+    since: DateTime<Utc>,
+    drop_percent: f64,
+    channels: Vec<&'static str>,
+) -> Result<Vec<Notification>, ServiceError> {
+    let now = Utc::now();
+
+    run_blocking(pool, move |pool| {
+        let mut created = Vec::new();
+        for (vehicle, owner_id) in queries::get_recently_refueled_vehicles(pool, since)? {
+            let refuels = queries::get_refuel_timeseries(
+                pool,
+                vehicle.id,
+                since - Duration::days(FUEL_ECONOMY_HISTORY_DAYS),
+                now,
+            )?;
+            let economies = fuel_economies(&refuels);
+            let Some(((latest, economy), earlier)) = economies.split_last() else {
+                continue;
+            };
+            if latest.timestamp.is_none_or(|timestamp| timestamp < since) {
+                continue;
+            }
+
+            let baseline = &earlier[earlier.len().saturating_sub(FUEL_ECONOMY_BASELINE_REFUELS)..];
+            if baseline.len() < FUEL_ECONOMY_MIN_BASELINE {
+                continue;
+            }
+            let average =
+                baseline.iter().map(|(_, economy)| economy).sum::<f64>() / baseline.len() as f64;
+            let drop = (1.0 - economy / average) * 100.0;
+            if drop < drop_percent {
+                continue;
+            }
+
+            let units = queries::get_user_preferences(pool, owner_id)?
+                .unwrap_or_else(|| UserPreferences::defaults(owner_id))
+                .units();
+            let notification = NewNotification {
+                user_id: owner_id,
+                vehicle_id: Some(vehicle.id),
+                kind: NotificationKind::FuelEconomyDrop.as_str().to_string(),
+                title: format!("Fuel economy of {} dropped", vehicle.registration),
+                body: format!(
+                    "Your {} {} ({}) averaged {:.1} {} since its previous refuel, {:.0}% below \
+                     its recent average of {:.1} {}.",
+                    vehicle.brand,
+                    vehicle.model,
+                    vehicle.registration,
+                    units.economy_to_user(*economy),
+                    units.economy_label(),
+                    drop,
+                    units.economy_to_user(average),
+                    units.economy_label()
+                ),
+                dedup_key: format!("fuel_economy_drop:{}", latest.id),
+            };
+            if let Some(notification) =
+                queries::create_notification(pool, &notification, &channels)?
+            {
+                created.push(notification);
+            }
+        }
+        Ok(created)
+    })
+    .await
+}
+
+/// Number of notifications returned when the caller does not pass a limit.
+const DEFAULT_NOTIFICATION_LIMIT: i64 = 50;
+
+/// Upper bound for the number of notifications returned in one response.
+const MAX_NOTIFICATION_LIMIT: i64 = 500;
+
+/// Service to list the authenticated user's inbox.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `query`: Whether to only list unread notifications, and how many.
+///
+/// # Returns
+/// - `Ok(Vec<Notification>)`: The notifications that were not dismissed, newest first.
+/// - `Err(ServiceError::ValidationError)`: If the limit is not positive.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_notifications(
+    pool: &DbPool,
+    user_id: Uuid,
+    query: NotificationQuery,
+) -> Result<Vec<Notification>, ServiceError> {
+    let limit = query.limit.unwrap_or(DEFAULT_NOTIFICATION_LIMIT);
+    if limit <= 0 {
+        return Err(ServiceError::ValidationError(
+            "Limit must be positive".to_string(),
+        ));
+    }
+    let limit = limit.min(MAX_NOTIFICATION_LIMIT);
+
+    run_blocking(pool, move |pool| {
+        queries::get_notifications(pool, user_id, query.unread, limit)
+    })
+    .await
+}
+
+/// Service to count the unread notifications in the authenticated user's inbox.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+///
+/// # Returns
+/// - `Ok(UnreadNotifications)`: The number of unread notifications.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn count_unread_notifications(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<UnreadNotifications, ServiceError> {
+    let unread = run_blocking(pool, move |pool| {
+        queries::count_unread_notifications(pool, user_id)
+    })
+    .await?;
+
+    Ok(UnreadNotifications { unread })
+}
+
+/// Service to mark a notification of the authenticated user as read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `notification_id`: The ID of the notification.
+///
+/// # Returns
+/// - `Ok(Notification)`: The read notification.
+/// - `Err(ServiceError::NotFound)`: If the user has no such notification in their inbox.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn mark_notification_read(
+    pool: &DbPool,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> Result<Notification, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::mark_notification_read(pool, user_id, notification_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Notification not found".to_string()))
+}
+
+/// Service to mark all notifications of the authenticated user as read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+///
+/// # Returns
+/// - `Ok(UnreadNotifications)`: The number of unread notifications left, i.e. zero.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn mark_all_notifications_read(
+    pool: &DbPool,
+    user_id: Uuid,
+) -> Result<UnreadNotifications, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::mark_all_notifications_read(pool, user_id)
+    })
+    .await?;
+
+    count_unread_notifications(pool, user_id).await
+}
+
+/// Service to dismiss a notification from the authenticated user's inbox.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `notification_id`: The ID of the notification.
+///
+/// # Returns
+/// - `Ok(())`: If the notification was dismissed.
+/// - `Err(ServiceError::NotFound)`: If the user has no such notification in their inbox.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn dismiss_notification(
+    pool: &DbPool,
+    user_id: Uuid,
+    notification_id: Uuid,
+) -> Result<(), ServiceError> {
+    let dismissed = run_blocking(pool, move |pool| {
+        queries::dismiss_notification(pool, user_id, notification_id)
+    })
+    .await?;
+
+    if dismissed {
+        Ok(())
+    } else {
+        Err(ServiceError::NotFound("Notification not found".to_string()))
+    }
+}
+
+/// Length of the signing secrets generated for webhooks.
+const WEBHOOK_SECRET_LENGTH: usize = 32;
+
+/// Number of deliveries returned when the caller does not pass a limit.
+const DEFAULT_WEBHOOK_DELIVERY_LIMIT: i64 = 100;
+
+/// Upper bound for the number of deliveries returned in one response.
+const MAX_WEBHOOK_DELIVERY_LIMIT: i64 = 1000;
+
+/// Checks that a webhook URL is an absolute `http` or `https` URL that does not point to a
+/// loopback, private or link-local address (see `webhooks::check_target`).
+async fn validate_webhook_url(url: &str) -> Result<String, ServiceError> {
+    let url = match reqwest::Url::parse(url.trim()) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => {
+            parsed.to_string()
+        }
+        _ => {
+            return Err(ServiceError::ValidationError(
+                "Webhook URL must be an http or https URL".to_string(),
+            ))
+        }
+    };
+    webhooks::check_target(&url)
+        .await
+        .map_err(ServiceError::ValidationError)?;
+    Ok(url)
+}
+
+/// Checks that webhook subscriptions name known events, dropping duplicates.
+fn validate_webhook_events(events: Vec<String>) -> Result<Vec<String>, ServiceError> {
+    let mut validated: Vec<String> = Vec::new();
+    for event in events {
+        let event = WebhookEvent::parse(event.trim()).ok_or_else(|| {
+            ServiceError::ValidationError(format!(
+                "Unknown webhook event '{}', expected one of {}",
+                event,
+                WebhookEvent::SUBSCRIBABLE
+                    .iter()
+                    .map(WebhookEvent::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })?;
+        if !validated.iter().any(|known| known == event.as_str()) {
+            validated.push(event.as_str().to_string());
+        }
+    }
+    if validated.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Webhook must subscribe to at least one event".to_string(),
+        ));
+    }
+    Ok(validated)
+}
+
+/// Retrieves one of the user's webhooks in their active organization.
+///
+/// # Returns
+/// - `Ok(Webhook)`: The webhook.
+/// - `Err(ServiceError::NotFound)`: If the user has no such webhook in the organization.
+async fn authorize_webhook(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    webhook_id: Uuid,
+) -> Result<Webhook, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::get_webhook_by_id(pool, webhook_id)
+    })
+    .await?
+    .filter(|webhook| webhook.user_id == user_id && webhook.organization_id == organization_id)
+    .ok_or_else(|| ServiceError::NotFound("Webhook not found".to_string()))
+}
+
+/// Service to add an outgoing webhook for the vehicles shared with the user in the active
+/// organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+/// - `request`: The URL, the subscribed events and an optional signing secret.
+///
+/// # Returns
+/// - `Ok(CreatedWebhook)`: The new webhook with its signing secret.
+/// - `Err(ServiceError::ValidationError)`: If the URL, events or secret are not valid.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_webhook(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    request: NewWebhookRequest,
+) -> Result<CreatedWebhook, ServiceError> {
+    require_organization_member(pool, user_id, organization_id).await?;
+
+    let url = validate_webhook_url(&request.url).await?;
+    let events = validate_webhook_events(request.events)?;
+    let secret = match request.secret {
+        Some(secret) if secret.len() < 16 => {
+            return Err(ServiceError::ValidationError(
+                "Webhook secret must be at least 16 characters long".to_string(),
+            ))
+        }
+        Some(secret) => secret,
+        None => rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(WEBHOOK_SECRET_LENGTH)
+            .map(char::from)
+            .collect(),
+    };
+
+    let new_webhook = NewWebhook {
+        user_id,
+        organization_id,
+        url,
+        secret,
+        events,
+    };
+    let webhook = run_audited(
+        pool,
+        move |conn| queries::create_webhook(conn, &new_webhook),
+        move |webhook| {
+            [audit(
+                Some(user_id),
+                "create",
+                "webhook",
+                Some(webhook.id),
+                None,
+                snapshot(webhook),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(CreatedWebhook {
+        secret: webhook.secret.clone(),
+        webhook,
+    })
+}
+
+/// Service to list the user's webhooks in the active organization.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+///
+/// # Returns
+/// - `Ok(Vec<Webhook>)`: The webhooks, oldest first, without their secrets.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_webhooks(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+) -> Result<Vec<Webhook>, ServiceError> {
+    run_blocking(pool, move |pool| {
+        queries::get_webhooks_by_user(pool, user_id, organization_id)
+    })
+    .await
+}
+
+/// Service to change the URL or subscriptions of one of the user's webhooks, or to pause or
+/// resume it.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+/// - `webhook_id`: The ID of the webhook.
+/// - `update`: The fields to change; fields left out are kept.
+///
+/// # Returns
+/// - `Ok(Webhook)`: The updated webhook.
+/// - `Err(ServiceError::NotFound)`: If the user has no such webhook in the organization.
+/// - `Err(ServiceError::ValidationError)`: If the URL or events are not valid.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn update_webhook(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    webhook_id: Uuid,
+    update: UpdateWebhookRequest,
+) -> Result<Webhook, ServiceError> {
+    let before = authorize_webhook(pool, user_id, organization_id, webhook_id).await?;
+
+    let url = match update.url {
+        Some(url) => Some(validate_webhook_url(&url).await?),
+        None => None,
+    };
+    let events = update.events.map(validate_webhook_events).transpose()?;
+    let is_active = update.is_active;
+
+    let webhook = run_audited(
+        pool,
+        move |conn| queries::update_webhook(conn, webhook_id, url, events, is_active),
+        move |webhook| {
+            [audit(
+                Some(user_id),
+                "update",
+                "webhook",
+                Some(webhook.id),
+                snapshot(&before),
+                snapshot(webhook),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(webhook)
+}
+
+/// Service to delete one of the user's webhooks along with its delivery log.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+/// - `webhook_id`: The ID of the webhook.
+///
+/// # Returns
+/// - `Ok(())`: If the webhook was deleted.
+/// - `Err(ServiceError::NotFound)`: If the user has no such webhook in the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn delete_webhook(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    webhook_id: Uuid,
+) -> Result<(), ServiceError> {
+    let before = authorize_webhook(pool, user_id, organization_id, webhook_id).await?;
+
+    let before = snapshot(&before);
+    run_audited(
+        pool,
+        move |conn| queries::delete_webhook(conn, webhook_id),
+        move |_| {
+            [audit(
+                Some(user_id),
+                "delete",
+                "webhook",
+                Some(webhook_id),
+                before,
+                None,
+            )]
+        },
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Service to read the delivery log of one of the user's webhooks.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+/// - `webhook_id`: The ID of the webhook.
+/// - `query`: Optional status filter and limit.
+///
+/// # Returns
+/// - `Ok(Vec<WebhookDelivery>)`: The deliveries, newest first.
+/// - `Err(ServiceError::NotFound)`: If the user has no such webhook in the organization.
+/// - `Err(ServiceError::ValidationError)`: If the status or limit is not valid.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_webhook_deliveries(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    webhook_id: Uuid,
+    query: WebhookDeliveryQuery,
+) -> Result<Vec<WebhookDelivery>, ServiceError> {
+    authorize_webhook(pool, user_id, organization_id, webhook_id).await?;
+
+    let status = match query.status.as_deref() {
+        None => None,
+        Some(status) => Some(
+            DeliveryStatus::parse(status)
+                .filter(|status| *status != DeliveryStatus::Skipped)
+                .ok_or_else(|| {
+                    ServiceError::ValidationError(
+                        "Status must be pending, sent or failed".to_string(),
+                    )
+                })?,
+        ),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_WEBHOOK_DELIVERY_LIMIT);
+    if limit <= 0 {
+        return Err(ServiceError::ValidationError(
+            "Limit must be positive".to_string(),
+        ));
+    }
+    let limit = limit.min(MAX_WEBHOOK_DELIVERY_LIMIT);
+
+    run_blocking(pool, move |pool| {
+        queries::get_webhook_deliveries(pool, webhook_id, status, limit)
+    })
+    .await
+}
+
+/// Service to queue a `ping` event for one of the user's webhooks, to test the receiver.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the authenticated user.
+/// - `organization_id`: The caller's active organization.
+/// - `webhook_id`: The ID of the webhook.
+///
+/// # Returns
+/// - `Ok(WebhookDelivery)`: The queued delivery, to look up in the delivery log.
+/// - `Err(ServiceError::NotFound)`: If the user has no such webhook in the organization.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn ping_webhook(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    webhook_id: Uuid,
+) -> Result<WebhookDelivery, ServiceError> {
+    let webhook = authorize_webhook(pool, user_id, organization_id, webhook_id).await?;
+
+    let payload = webhook_payload(
+        WebhookEvent::Ping,
+        Some(serde_json::json!({ "webhook_id": webhook.id, "events": webhook.events })),
+    );
+    run_blocking(pool, move |pool| {
+        queries::enqueue_webhook_delivery(pool, webhook_id, WebhookEvent::Ping.as_str(), &payload)
+    })
+    .await
+}
+
+/// Service to check that a user may upload documents to a vehicle, before the upload is read.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user uploading the document (must be an editor of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+///
+/// # Returns
+/// - `Ok(())`: If the user may upload documents to the vehicle.
+/// - `Err(ServiceError)`: If the vehicle is not found, archived or not editable by the user.
+pub async fn authorize_document_upload(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-) -> Result<Odometer, ServiceError> {
-    queries::get_latest_odometer(pool, vehicle_id)?
-        .ok_or_else(|| ServiceError::NotFound("No odometer record found".to_string()))
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+    Ok(())
 }
 
-/// Service to get odometer time-series data for a vehicle.
+/// Service to upload a document to a vehicle, optionally attaching it to one of its refuels.
 ///
-/// Calls the `get_odometer_timeseries` query to retrieve odometer records within a date range.
+/// The file is stored on disk (see `documents`) and its metadata in the database; the file is
+/// removed again if the metadata cannot be stored.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user uploading the document.
+/// - `organization_id`: The caller's active organization.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `start_date`: The start of the date range.
-/// - `end_date`: The end of the date range.
+/// - `upload`: The uploaded file and its details.
 ///
 /// # Returns
-/// - `Ok(Vec<Odometer>)`: A list of odometer records within the specified range.
+/// - `Ok(Document)`: The new document.
+/// - `Err(ServiceError::ValidationError)`: If the file is empty, too large or of an unsupported
+///   type, or the kind or refuel is invalid.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_odometer_timeseries(
+pub async fn upload_document(
     pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-    start_date: NaiveDateTime,
-    end_date: NaiveDateTime,
-) -> Result<Vec<Odometer>, ServiceError> {
-    queries::get_odometer_timeseries(pool, vehicle_id, start_date, end_date)
-        .map_err(ServiceError::DbError)
+    upload: DocumentUpload,
+) -> Result<Document, ServiceError> {
+    authorize_document_upload(pool, actor_id, organization_id, vehicle_id).await?;
+
+    let default_kind = match upload.refuel_id {
+        Some(_) => DocumentKind::Receipt,
+        None => DocumentKind::Other,
+    };
+    let kind = match upload.kind.as_deref().map(str::trim) {
+        None | Some("") => default_kind,
+        Some(kind) => DocumentKind::parse(kind).ok_or_else(|| {
+            ServiceError::ValidationError(format!(
+                "Kind must be one of insurance, registration, receipt, other, got {}",
+                kind
+            ))
+        })?,
+    };
+
+    if let Some(refuel_id) = upload.refuel_id {
+        let belongs = run_blocking(pool, move |pool| {
+            queries::refuel_belongs_to_vehicle(pool, vehicle_id, refuel_id)
+        })
+        .await?;
+        if !belongs {
+            return Err(ServiceError::ValidationError(
+                "Refuel does not belong to the vehicle".to_string(),
+            ));
+        }
+    }
+
+    if upload.bytes.is_empty() {
+        return Err(ServiceError::ValidationError("File is empty".to_string()));
+    }
+    if upload.bytes.len() > *documents::DOCUMENT_MAX_BYTES {
+        return Err(ServiceError::ValidationError(format!(
+            "File must be at most {} bytes",
+            *documents::DOCUMENT_MAX_BYTES
+        )));
+    }
+    let content_type = documents::detect_content_type(&upload.bytes).ok_or_else(|| {
+        ServiceError::ValidationError(
+            "File must be a PDF or a JPEG, PNG, GIF or WebP image".to_string(),
+        )
+    })?;
+
+    let filename = upload
+        .filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let filename = if filename.is_empty() {
+        "document".to_string()
+    } else {
+        filename
+    };
+
+    let document_id = Uuid::new_v4();
+    let size_bytes = upload.bytes.len() as i64;
+    let (storage_key, thumbnail_key) =
+        web::block(move || documents::store(vehicle_id, document_id, &upload.bytes, content_type))
+            .await?
+            .map_err(|e| ServiceError::Other(format!("Failed to store document: {}", e)))?;
+
+    let new_document = NewDocument {
+        id: document_id,
+        vehicle_id,
+        refuel_id: upload.refuel_id,
+        kind: kind.as_str().to_string(),
+        filename,
+        content_type: content_type.to_string(),
+        size_bytes,
+        storage_key: storage_key.clone(),
+        thumbnail_key: thumbnail_key.clone(),
+        user_id: Some(actor_id),
+    };
+    let document = match run_audited(
+        pool,
+        move |conn| queries::create_document(conn, new_document),
+        move |document| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "document",
+                Some(document.id),
+                None,
+                snapshot(document),
+            )]
+        },
+    )
+    .await
+    {
+        Ok(document) => document,
+        Err(e) => {
+            if let Err(cleanup) =
+                web::block(move || documents::remove(&storage_key, thumbnail_key.as_deref())).await
+            {
+                log::error!("Failed to remove the stored document file: {}", cleanup);
+            }
+            return Err(e);
+        }
+    };
+
+    Ok(document)
 }
 
-/// Service to create a new refuel event with an associated odometer entry.
+/// Service to list the documents of a vehicle.
 ///
-/// Calls the `create_new_refuel` query to insert a refuel record linked to an odometer entry.
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `query`: Optional refuel to filter by.
+///
+/// # Returns
+/// - `Ok(Vec<Document>)`: The documents, newest first.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_documents(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    query: DocumentQuery,
+) -> Result<Vec<Document>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_documents(pool, vehicle_id, query.refuel_id)
+    })
+    .await
+}
+
+/// Service to get the stored file of a document of a vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `refuel_quantity`: The amount of fuel refueled.
-/// - `odometer_value`: The odometer reading during the refuel.
-/// - `timestamp`: Optional timestamp for the refuel event.
+/// - `document_id`: The ID of the document.
 ///
 /// # Returns
-/// - `Ok(RefuelWithOdometer)`: The newly created refuel record along with its odometer entry.
+/// - `Ok(DocumentFile)`: Where the file is stored, with its media type and original name.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such document.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn create_new_refuel(
+pub async fn get_document_file(
     pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-    refuel_quantity: f32,
-    odometer_value: f32,
-    timestamp: Option<NaiveDateTime>,
-) -> Result<RefuelWithOdometer, ServiceError> {
-    queries::create_new_refuel(pool, vehicle_id, refuel_quantity, odometer_value, timestamp)
-        .map_err(ServiceError::DbError)
+    document_id: Uuid,
+) -> Result<DocumentFile, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    let document = run_blocking(pool, move |pool| {
+        queries::get_document(pool, vehicle_id, document_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Document not found".to_string()))?;
+
+    Ok(DocumentFile {
+        path: documents::path(&document.storage_key),
+        content_type: document.content_type,
+        filename: document.filename,
+    })
 }
 
-/// Service to get the latest refuel event for a vehicle.
+/// Service to get the thumbnail of an image document of a vehicle.
 ///
-/// Calls the `get_latest_refuel` query to retrieve the most recent refuel record.
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `document_id`: The ID of the document.
+///
+/// # Returns
+/// - `Ok(DocumentFile)`: Where the JPEG thumbnail is stored.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such document or it has no thumbnail.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_document_thumbnail(
+    pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    document_id: Uuid,
+) -> Result<DocumentFile, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    let document = run_blocking(pool, move |pool| {
+        queries::get_document(pool, vehicle_id, document_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Document not found".to_string()))?;
+    let thumbnail_key = document
+        .thumbnail_key
+        .ok_or_else(|| ServiceError::NotFound("Document has no thumbnail".to_string()))?;
+
+    let stem = document
+        .filename
+        .rsplit_once('.')
+        .map_or(document.filename.as_str(), |(stem, _)| stem);
+    Ok(DocumentFile {
+        path: documents::path(&thumbnail_key),
+        content_type: "image/jpeg".to_string(),
+        filename: format!("{}.thumb.jpg", stem),
+    })
+}
+
+/// Service to delete a document of a vehicle, including its stored file.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user deleting the document.
+/// - `organization_id`: The caller's active organization.
 /// - `vehicle_id`: The ID of the vehicle.
+/// - `document_id`: The ID of the document.
 ///
 /// # Returns
-/// - `Ok(RefuelWithOdometer)`: The latest refuel record.
-/// - `Err(ServiceError::NotFound)`: If no refuel record exists for the vehicle.
+/// - `Ok(())`: If the document was deleted.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such document.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_latest_refuel(
+pub async fn delete_document(
     pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-) -> Result<RefuelWithOdometer, ServiceError> {
-    queries::get_latest_refuel(pool, vehicle_id)?
-        .ok_or_else(|| ServiceError::NotFound("No refuel record found".to_string()))
+    document_id: Uuid,
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+
+    let document = run_audited(
+        pool,
+        move |conn| queries::delete_document(conn, vehicle_id, document_id),
+        move |document| {
+            document.as_ref().map(|document| {
+                audit(
+                    Some(actor_id),
+                    "delete",
+                    "document",
+                    Some(document.id),
+                    snapshot(document),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Document not found".to_string()))?;
+
+    let storage_key = document.storage_key.clone();
+    let thumbnail_key = document.thumbnail_key.clone();
+    web::block(move || documents::remove(&storage_key, thumbnail_key.as_deref())).await?;
+
+    Ok(())
 }
 
-/// Service to get refuel time-series data for a vehicle.
+/// Number of days before its end that insurance counts as expiring.
+const INSURANCE_EXPIRING_DAYS: i64 = 30;
+
+/// Describes a vehicle's insurance coverage as of a given day.
 ///
-/// Calls the `get_refuel_timeseries` query to retrieve refuel records within a date range.
+/// # Arguments
+/// - `end_date`: Last day covered by the vehicle's latest-ending policy.
+/// - `today`: The day to describe the coverage on.
+fn insurance_status(end_date: NaiveDate, today: NaiveDate) -> InsuranceStatus {
+    let days_left = (end_date - today).num_days();
+    let status = if days_left < 0 {
+        "expired"
+    } else if days_left <= INSURANCE_EXPIRING_DAYS {
+        "expiring"
+    } else {
+        "active"
+    };
+    InsuranceStatus {
+        status: status.to_string(),
+        end_date,
+        days_left,
+    }
+}
+
+/// Returns the part of a policy's premium that falls within a date range, spreading the premium
+/// evenly over the days the policy covers. The part is rounded to the cent.
+fn prorated_premium(policy: &InsurancePolicy, from: NaiveDate, to: NaiveDate) -> Decimal {
+    let covered_days = (policy.end_date - policy.start_date).num_days() + 1;
+    let overlap_days = (policy.end_date.min(to) - policy.start_date.max(from)).num_days() + 1;
+    if overlap_days <= 0 {
+        return Decimal::ZERO;
+    }
+    (policy.premium * Decimal::from(overlap_days) / Decimal::from(covered_days)).round_dp(2)
+}
+
+/// Validates the details of an insurance policy, normalizing its text fields.
+///
+/// # Returns
+/// - `Ok(NewInsurancePolicy)`: The validated details.
+/// - `Err(ServiceError::ValidationError)`: If a field is invalid, or the document does not
+///   belong to the vehicle.
+/// - `Err(ServiceError)`: If the document could not be looked up.
+async fn validate_insurance_policy(
+    pool: &DbPool,
+    mut policy: NewInsurancePolicy,
+) -> Result<NewInsurancePolicy, ServiceError> {
+    policy.provider = policy.provider.trim().to_string();
+    policy.policy_number = policy.policy_number.trim().to_string();
+    if policy.provider.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Provider is required".to_string(),
+        ));
+    }
+    if policy.policy_number.is_empty() {
+        return Err(ServiceError::ValidationError(
+            "Policy number is required".to_string(),
+        ));
+    }
+    policy.coverage_type = CoverageType::parse(policy.coverage_type.trim())
+        .ok_or_else(|| {
+            ServiceError::ValidationError(format!(
+                "Coverage type must be one of comprehensive, third_party, total_loss_only, \
+                 other, got {}",
+                policy.coverage_type
+            ))
+        })?
+        .as_str()
+        .to_string();
+    policy.premium =
+        check_amount(policy.premium, "Premium").map_err(ServiceError::ValidationError)?;
+    if policy.end_date < policy.start_date {
+        return Err(ServiceError::ValidationError(
+            "End date must not be before the start date".to_string(),
+        ));
+    }
+    if let Some(document_id) = policy.document_id {
+        let vehicle_id = policy.vehicle_id;
+        let document = run_blocking(pool, move |pool| {
+            queries::get_document(pool, vehicle_id, document_id)
+        })
+        .await?;
+        if document.is_none() {
+            return Err(ServiceError::ValidationError(
+                "Document does not belong to the vehicle".to_string(),
+            ));
+        }
+    }
+    Ok(policy)
+}
+
+/// Service to list the insurance policies of a vehicle.
 ///
 /// # Arguments
 /// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
 /// - `vehicle_id`: The ID of the vehicle.
-/// - `start_date`: The start of the date range.
-/// - `end_date`: The end of the date range.
 ///
 /// # Returns
-/// - `Ok(Vec<RefuelWithOdometer>)`: A list of refuel records within the specified range.
+/// - `Ok(Vec<InsurancePolicy>)`: The policies, latest-ending first.
 /// - `Err(ServiceError)`: If the operation fails.
-pub async fn get_refuel_timeseries(
+pub async fn get_insurance_policies(
     pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-    start_date: NaiveDateTime,
-    end_date: NaiveDateTime,
-) -> Result<Vec<RefuelWithOdometer>, ServiceError> {
-    queries::get_refuel_timeseries(pool, vehicle_id, start_date, end_date)
-        .map_err(ServiceError::DbError)
+) -> Result<Vec<InsurancePolicy>, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_insurance_policies(pool, vehicle_id)
+    })
+    .await
 }
 
-pub async fn get_traveled_distance(
+/// Service to get an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `user_id`: The ID of the requesting user (must be a member of the vehicle).
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `policy_id`: The ID of the policy.
+///
+/// # Returns
+/// - `Ok(InsurancePolicy)`: The policy.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such policy.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn get_insurance_policy(
     pool: &DbPool,
+    user_id: Uuid,
+    organization_id: Uuid,
     vehicle_id: Uuid,
-    start_date: NaiveDate,
-    end_date: NaiveDate,
-) -> Result<Option<TraveledDistance>, ServiceError> {
-    queries::get_traveled_distance(pool, vehicle_id, start_date, end_date)
-        .map_err(ServiceError::DbError)
+    policy_id: Uuid,
+) -> Result<InsurancePolicy, ServiceError> {
+    authorize_vehicle(
+        pool,
+        user_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Viewer,
+    )
+    .await?;
+
+    run_blocking(pool, move |pool| {
+        queries::get_insurance_policy(pool, vehicle_id, policy_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Insurance policy not found".to_string()))
+}
+
+/// Service to record an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user recording the policy.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `request`: The policy details.
+///
+/// # Returns
+/// - `Ok(InsurancePolicy)`: The new policy.
+/// - `Err(ServiceError::ValidationError)`: If the details are invalid.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn create_insurance_policy(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    request: NewInsurancePolicyRequest,
+) -> Result<InsurancePolicy, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+    require_active_vehicle(pool, vehicle_id).await?;
+
+    let new_policy = validate_insurance_policy(
+        pool,
+        NewInsurancePolicy {
+            vehicle_id,
+            provider: request.provider,
+            policy_number: request.policy_number,
+            coverage_type: request.coverage_type,
+            premium: request.premium,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            document_id: request.document_id,
+            user_id: Some(actor_id),
+        },
+    )
+    .await?;
+
+    let policy = run_audited(
+        pool,
+        move |conn| queries::create_insurance_policy(conn, new_policy),
+        move |policy| {
+            [audit(
+                Some(actor_id),
+                "create",
+                "insurance_policy",
+                Some(policy.id),
+                None,
+                snapshot(policy),
+            )]
+        },
+    )
+    .await?;
+
+    Ok(policy)
+}
+
+/// Service to update an insurance policy of a vehicle.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user updating the policy.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `policy_id`: The ID of the policy.
+/// - `update`: The fields to update; fields left out are kept.
+///
+/// # Returns
+/// - `Ok(InsurancePolicy)`: The updated policy.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such policy.
+/// - `Err(ServiceError::ValidationError)`: If the updated details are invalid.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn update_insurance_policy(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    policy_id: Uuid,
+    update: UpdateInsurancePolicyRequest,
+) -> Result<InsurancePolicy, ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+
+    let before = run_blocking(pool, move |pool| {
+        queries::get_insurance_policy(pool, vehicle_id, policy_id)
+    })
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Insurance policy not found".to_string()))?;
+
+    let details = validate_insurance_policy(
+        pool,
+        NewInsurancePolicy {
+            vehicle_id,
+            provider: update.provider.unwrap_or_else(|| before.provider.clone()),
+            policy_number: update
+                .policy_number
+                .unwrap_or_else(|| before.policy_number.clone()),
+            coverage_type: update
+                .coverage_type
+                .unwrap_or_else(|| before.coverage_type.clone()),
+            premium: update.premium.unwrap_or(before.premium),
+            start_date: update.start_date.unwrap_or(before.start_date),
+            end_date: update.end_date.unwrap_or(before.end_date),
+            document_id: update.document_id.or(before.document_id),
+            user_id: before.user_id,
+        },
+    )
+    .await?;
+
+    let policy = run_audited(
+        pool,
+        move |conn| queries::update_insurance_policy(conn, policy_id, details),
+        move |policy| {
+            policy.as_ref().map(|policy| {
+                audit(
+                    Some(actor_id),
+                    "update",
+                    "insurance_policy",
+                    Some(policy.id),
+                    snapshot(&before),
+                    snapshot(policy),
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Insurance policy not found".to_string()))?;
+
+    Ok(policy)
+}
+
+/// Service to delete an insurance policy of a vehicle. Its policy document is kept.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `actor_id`: The ID of the user deleting the policy.
+/// - `organization_id`: The caller's active organization.
+/// - `vehicle_id`: The ID of the vehicle.
+/// - `policy_id`: The ID of the policy.
+///
+/// # Returns
+/// - `Ok(())`: If the policy was deleted.
+/// - `Err(ServiceError::NotFound)`: If the vehicle has no such policy.
+/// - `Err(ServiceError)`: If the operation fails.
+pub async fn delete_insurance_policy(
+    pool: &DbPool,
+    actor_id: Uuid,
+    organization_id: Uuid,
+    vehicle_id: Uuid,
+    policy_id: Uuid,
+) -> Result<(), ServiceError> {
+    authorize_vehicle(
+        pool,
+        actor_id,
+        organization_id,
+        vehicle_id,
+        VehicleRole::Editor,
+    )
+    .await?;
+
+    run_audited(
+        pool,
+        move |conn| queries::delete_insurance_policy(conn, vehicle_id, policy_id),
+        move |policy| {
+            policy.as_ref().map(|policy| {
+                audit(
+                    Some(actor_id),
+                    "delete",
+                    "insurance_policy",
+                    Some(policy.id),
+                    snapshot(policy),
+                    None,
+                )
+            })
+        },
+    )
+    .await?
+    .ok_or_else(|| ServiceError::NotFound("Insurance policy not found".to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_record_quotes_fields_that_need_it() {
+        let cases: &[(&[&str], &str)] = &[
+            (&["a", "b"], "a,b\r\n"),
+            (&["say \"hi\""], "\"say \"\"hi\"\"\"\r\n"),
+            (&["a,b", "c"], "\"a,b\",c\r\n"),
+            (&["two\r\nlines"], "\"two\r\nlines\"\r\n"),
+            (&["line\nbreak"], "\"line\nbreak\"\r\n"),
+            (&["a", ""], "a,\r\n"),
+            (&["", ""], ",\r\n"),
+        ];
+        for (fields, expected) in cases {
+            assert_eq!(csv_record(fields), *expected, "fields {:?}", fields);
+        }
+    }
+
+    #[test]
+    fn csv_record_keeps_formulas_as_text() {
+        let cases: &[(&str, &str)] = &[
+            ("=1+1", "'=1+1\r\n"),
+            ("+cmd", "'+cmd\r\n"),
+            ("-2+3", "'-2+3\r\n"),
+            ("@SUM(A1)", "'@SUM(A1)\r\n"),
+            ("\tindent", "'\tindent\r\n"),
+            ("\rreturn", "\"'\rreturn\"\r\n"),
+            (
+                "=HYPERLINK(\"x\",\"y\")",
+                "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\"\r\n",
+            ),
+            ("-12.5", "-12.5\r\n"),
+            ("+3", "+3\r\n"),
+            ("a=b", "a=b\r\n"),
+        ];
+        for (field, expected) in cases {
+            assert_eq!(csv_record(&[field]), *expected, "field {:?}", field);
+        }
+    }
+
+    /// Records as `parse_csv` returns them: the line each starts on, and its fields.
+    type Records<'a> = &'a [(usize, &'a [&'a str])];
+
+    #[test]
+    fn parse_csv_splits_records() {
+        let cases: &[(&str, char, Records)] = &[
+            ("a,b\nc,d\n", ',', &[(1, &["a", "b"]), (2, &["c", "d"])]),
+            ("a,b\r\nc,d\r\n", ',', &[(1, &["a", "b"]), (2, &["c", "d"])]),
+            ("a,b\r\nc,d", ',', &[(1, &["a", "b"]), (2, &["c", "d"])]),
+            ("a;b,c\n", ';', &[(1, &["a", "b,c"])]),
+            ("\"say \"\"hi\"\"\",x\n", ',', &[(1, &["say \"hi\"", "x"])]),
+            ("\"a,b\",c\n", ',', &[(1, &["a,b", "c"])]),
+            (
+                "\"two\r\nlines\",x\r\ny,z\r\n",
+                ',',
+                &[(1, &["two\r\nlines", "x"]), (3, &["y", "z"])],
+            ),
+            (
+                "a,b,\r\n,,\n",
+                ',',
+                &[(1, &["a", "b", ""]), (2, &["", "", ""])],
+            ),
+            ("a,\n\n\r\nb", ',', &[(1, &["a", ""]), (4, &["b"])]),
+            ("\u{feff}a\n", ',', &[(1, &["a"])]),
+            ("", ',', &[]),
+        ];
+        for (text, delimiter, expected) in cases {
+            let expected = expected
+                .iter()
+                .map(|(line, fields)| (*line, fields.iter().map(|f| f.to_string()).collect()))
+                .collect::<Vec<(usize, Vec<String>)>>();
+            assert_eq!(parse_csv(text, *delimiter), Ok(expected), "text {:?}", text);
+        }
+    }
+
+    #[test]
+    fn parse_csv_rejects_unterminated_quotes() {
+        let cases: &[(&str, usize)] = &[
+            ("\"open", 1),
+            ("a,b\nc,\"open\nstill open", 2),
+            ("a\n\"x\"\"\n", 2),
+        ];
+        for (text, line) in cases {
+            assert_eq!(
+                parse_csv(text, ','),
+                Err(format!(
+                    "Quoted field starting on line {} is not terminated",
+                    line
+                )),
+                "text {:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_vin_validates_and_normalizes() {
+        let cases: &[(&str, Result<&str, &str>)] = &[
+            ("1M8GDM9AXKP042788", Ok("1M8GDM9AXKP042788")),
+            ("1hgcm82633a004352", Ok("1HGCM82633A004352")),
+            (" 1HG-CM826 33A004352 ", Ok("1HGCM82633A004352")),
+            ("11111111111111111", Ok("11111111111111111")),
+            ("1HGCM82633A00435", Err("VIN must be 17 characters long")),
+            ("1HGCM82633A0043521", Err("VIN must be 17 characters long")),
+            ("1HGCM82633A00435I", Err("VIN must not contain 'I'")),
+            ("1HGCM82633A0O4352", Err("VIN must not contain 'O'")),
+            ("1HGCM82643A004352", Err("VIN check digit does not match")),
+        ];
+        for (vin, expected) in cases {
+            let expected = expected
+                .map(str::to_string)
+                .map_err(|message| format!("Validation error: {}", message));
+            assert_eq!(
+                normalize_vin(vin).map_err(|e| e.to_string()),
+                expected,
+                "VIN {:?}",
+                vin
+            );
+        }
+    }
+
+    #[test]
+    fn day_bounds_cover_whole_local_days() {
+        let cases: &[(&str, &str, Tz, &str, &str)] = &[
+            (
+                "2025-03-01",
+                "2025-03-01",
+                Tz::UTC,
+                "2025-03-01T00:00:00Z",
+                "2025-03-01T23:59:59.999999Z",
+            ),
+            (
+                "2025-03-01",
+                "2025-03-31",
+                Tz::Asia__Bangkok,
+                "2025-02-28T17:00:00Z",
+                "2025-03-31T16:59:59.999999Z",
+            ),
+            // A 23 hour day, when daylight saving time starts
+            (
+                "2025-03-09",
+                "2025-03-09",
+                Tz::America__New_York,
+                "2025-03-09T05:00:00Z",
+                "2025-03-10T03:59:59.999999Z",
+            ),
+            // A 25 hour day, when daylight saving time ends
+            (
+                "2025-10-26",
+                "2025-10-26",
+                Tz::Europe__Berlin,
+                "2025-10-25T22:00:00Z",
+                "2025-10-26T22:59:59.999999Z",
+            ),
+            // Midnight was skipped, so the day starts at 01:00
+            (
+                "2018-11-04",
+                "2018-11-04",
+                Tz::America__Sao_Paulo,
+                "2018-11-04T03:00:00Z",
+                "2018-11-05T01:59:59.999999Z",
+            ),
+        ];
+        for (start, end, timezone, expected_start, expected_end) in cases {
+            let bounds = day_bounds(start.parse().unwrap(), end.parse().unwrap(), *timezone);
+            assert_eq!(
+                bounds,
+                (
+                    expected_start.parse().unwrap(),
+                    expected_end.parse().unwrap()
+                ),
+                "{} to {} in {}",
+                start,
+                end,
+                timezone
+            );
+        }
+    }
 }
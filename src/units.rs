@@ -0,0 +1,454 @@
+//! Conversion between the units values are stored in and the units users work in.
+//!
+//! Distances (odometer readings and everything derived from them) are stored in kilometers and
+//! fuel quantities (refuels and tank capacities) in liters. Each user picks the units they enter
+//! and read values in (see `UserPreferences`); handlers and renders convert requests from and
+//! responses to those units. Odometer readings are the exception: they are entered and read in
+//! the unit of the vehicle's odometer (`Vehicle::odometer_unit`), so they match its dashboard.
+//! Energy is always in kWh. Backups and webhook payloads carry the stored values.
+use crate::models::{
+    FuelType, Odometer, OdometerUnit, RefuelWithOdometer, TraveledDistance, Vehicle, VolumeUnit,
+};
+use crate::requests::{
+    NewChargingSessionRequest, NewOdometerRequest, NewRefuelRequest, NewTripRequest,
+    UpdateTripRequest,
+};
+use crate::responses::{
+    ChargingSessionResponse, ChargingSummary, DriverUsage, MileageReport, ReportRow, TripResponse,
+    TripSummary, VehicleWithRole,
+};
+use rust_decimal::Decimal;
+
+/// The units a user enters and reads distances and fuel quantities in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Units {
+    pub distance: OdometerUnit,
+    pub volume: VolumeUnit,
+    /// The unit odometer readings are in; see `Units::for_vehicle`.
+    pub odometer: OdometerUnit,
+}
+
+impl Default for Units {
+    /// Returns the units values are stored in: kilometers and liters.
+    fn default() -> Self {
+        Units {
+            distance: OdometerUnit::Kilometers,
+            volume: VolumeUnit::Liters,
+            odometer: OdometerUnit::Kilometers,
+        }
+    }
+}
+
+/// Decimal places distances (in kilometers) and fuel quantities (in liters) are stored with: to
+/// the meter and to the milliliter.
+pub const DECIMAL_PLACES: u32 = 3;
+
+/// Rounds a converted distance or fuel quantity to the precision it is stored with.
+fn stored_precision(value: Decimal) -> Decimal {
+    value.round_dp(DECIMAL_PLACES).normalize()
+}
+
+/// Converts a value entered by the user with a conversion factor. Values too large to convert
+/// saturate, and are then rejected as out of range.
+fn convert_from_user(value: Decimal, factor: Decimal) -> Decimal {
+    stored_precision(value.saturating_mul(factor))
+}
+
+/// Converts a conversion factor between units to a decimal.
+fn factor(factor: f64) -> Decimal {
+    Decimal::try_from(factor).expect("conversion factors are finite")
+}
+
+/// Whether the tank capacity of a vehicle with this fuel type is a battery capacity in kWh.
+pub fn is_battery(fuel_type: Option<&str>) -> bool {
+    fuel_type.and_then(FuelType::parse) == Some(FuelType::Ev)
+}
+
+impl Units {
+    /// Returns these units with odometer readings in the unit of the vehicle's odometer.
+    pub fn for_vehicle(self, vehicle: &Vehicle) -> Units {
+        Units {
+            odometer: OdometerUnit::parse(&vehicle.odometer_unit).unwrap_or(self.odometer),
+            ..self
+        }
+    }
+
+    /// Converts a distance entered by the user to kilometers.
+    pub fn distance_from_user(self, distance: Decimal) -> Decimal {
+        convert_from_user(distance, factor(self.distance.kilometers()))
+    }
+
+    /// Converts a distance in kilometers to the user's unit.
+    pub fn distance_to_user(self, distance: Decimal) -> Decimal {
+        stored_precision(distance / factor(self.distance.kilometers()))
+    }
+
+    /// Converts an odometer reading entered by the user to kilometers.
+    pub fn reading_from_user(self, reading: Decimal) -> Decimal {
+        convert_from_user(reading, factor(self.odometer.kilometers()))
+    }
+
+    /// Converts an odometer reading in kilometers to the unit of the odometer.
+    pub fn reading_to_user(self, reading: Decimal) -> Decimal {
+        stored_precision(reading / factor(self.odometer.kilometers()))
+    }
+
+    /// Converts a fuel quantity entered by the user to liters.
+    pub fn volume_from_user(self, quantity: Decimal) -> Decimal {
+        convert_from_user(quantity, factor(self.volume.liters()))
+    }
+
+    /// Converts a fuel quantity in liters to the user's unit.
+    pub fn volume_to_user(self, quantity: Decimal) -> Decimal {
+        stored_precision(quantity / factor(self.volume.liters()))
+    }
+
+    /// Converts a charging distance in kilometers to the user's unit.
+    fn charging_distance_to_user(self, distance: f64) -> f64 {
+        distance / self.distance.kilometers()
+    }
+
+    /// Converts an energy use in kWh per 100 kilometers to kWh per 100 of the user's unit.
+    fn energy_use_to_user(self, kwh_per_100km: f64) -> f64 {
+        kwh_per_100km * self.distance.kilometers()
+    }
+
+    /// Converts a fuel economy in kilometers per liter to the user's distance per volume unit.
+    pub fn economy_to_user(self, economy: f64) -> f64 {
+        economy / self.distance.kilometers() * self.volume.liters()
+    }
+
+    /// Returns the label of fuel economies in the user's units, e.g. `km/L` or `mi/US gal`.
+    pub fn economy_label(self) -> String {
+        format!("{}/{}", self.distance.as_str(), self.volume.label())
+    }
+
+    /// Converts a tank capacity entered by the user to liters; battery capacities stay in kWh.
+    pub fn capacity_from_user(self, fuel_type: Option<&str>, capacity: Decimal) -> Decimal {
+        if is_battery(fuel_type) {
+            stored_precision(capacity)
+        } else {
+            self.volume_from_user(capacity)
+        }
+    }
+
+    /// Converts a vehicle's stored profile to the user's units.
+    pub fn vehicle(self, mut vehicle: Vehicle) -> Vehicle {
+        if !is_battery(vehicle.fuel_type.as_deref()) {
+            vehicle.tank_capacity = vehicle
+                .tank_capacity
+                .map(|capacity| self.volume_to_user(capacity));
+        }
+        vehicle
+    }
+
+    /// Converts a vehicle listing entry to the user's units.
+    pub fn vehicle_with_role(self, mut vehicle: VehicleWithRole) -> VehicleWithRole {
+        vehicle.vehicle = self.vehicle(vehicle.vehicle);
+        vehicle
+    }
+
+    /// Converts an odometer reading to the user's units.
+    pub fn odometer(self, mut odometer: Odometer) -> Odometer {
+        odometer.odometer_value = self.reading_to_user(odometer.odometer_value);
+        odometer
+    }
+
+    /// Converts a refuel to the user's units.
+    pub fn refuel(self, mut refuel: RefuelWithOdometer) -> RefuelWithOdometer {
+        refuel.refuel_quantity = self.volume_to_user(refuel.refuel_quantity);
+        refuel.odometer_value = self.reading_to_user(refuel.odometer_value);
+        refuel
+    }
+
+    /// Converts a traveled distance to the user's units.
+    pub fn traveled_distance(self, traveled: TraveledDistance) -> TraveledDistance {
+        TraveledDistance {
+            start_value: self.reading_to_user(traveled.start_value),
+            end_value: self.reading_to_user(traveled.end_value),
+            traveled_distance: self.distance_to_user(traveled.traveled_distance),
+        }
+    }
+
+    /// Converts a driver's usage to the user's units.
+    pub fn driver_usage(self, mut usage: DriverUsage) -> DriverUsage {
+        usage.distance = self.distance_to_user(usage.distance);
+        usage.refuel_quantity = self.volume_to_user(usage.refuel_quantity);
+        usage
+    }
+
+    /// Converts a trip to the user's units.
+    pub fn trip(self, mut trip: TripResponse) -> TripResponse {
+        trip.start_odometer_value = self.reading_to_user(trip.start_odometer_value);
+        trip.end_odometer_value = self.reading_to_user(trip.end_odometer_value);
+        trip.distance = self.distance_to_user(trip.distance);
+        trip
+    }
+
+    /// Converts a trip summary to the user's units.
+    pub fn trip_summary(self, mut summary: TripSummary) -> TripSummary {
+        summary.business_distance = self.distance_to_user(summary.business_distance);
+        summary.private_distance = self.distance_to_user(summary.private_distance);
+        summary
+    }
+
+    /// Converts a charging session's odometer reading and efficiency to the user's units.
+    pub fn charging_session(self, mut session: ChargingSessionResponse) -> ChargingSessionResponse {
+        session.odometer_value = self.reading_to_user(session.odometer_value);
+        session.distance = session
+            .distance
+            .map(|distance| self.charging_distance_to_user(distance));
+        session.kwh_per_100_distance = session
+            .kwh_per_100_distance
+            .map(|energy| self.energy_use_to_user(energy));
+        session
+    }
+
+    /// Converts a charging summary to the user's units.
+    pub fn charging_summary(self, mut summary: ChargingSummary) -> ChargingSummary {
+        summary.distance = self.charging_distance_to_user(summary.distance);
+        summary.kwh_per_100_distance = summary
+            .kwh_per_100_distance
+            .map(|energy| self.energy_use_to_user(energy));
+        summary
+    }
+
+    /// Converts a row of a mileage report to the user's units.
+    fn report_row(self, mut row: ReportRow) -> ReportRow {
+        row.distance = self.distance_to_user(row.distance);
+        row.refuel_quantity = self.volume_to_user(row.refuel_quantity);
+        row.business_distance = self.distance_to_user(row.business_distance);
+        row.private_distance = self.distance_to_user(row.private_distance);
+        row
+    }
+
+    /// Converts a mileage report to the user's units.
+    pub fn mileage_report(self, mut report: MileageReport) -> MileageReport {
+        report.vehicle = self.vehicle(report.vehicle);
+        report.rows = report
+            .rows
+            .into_iter()
+            .map(|row| self.report_row(row))
+            .collect();
+        report.total = self.report_row(report.total);
+        report
+    }
+
+    /// Converts an odometer request from the user's units.
+    pub fn odometer_request(self, mut request: NewOdometerRequest) -> NewOdometerRequest {
+        request.odometer_value = self.reading_from_user(request.odometer_value);
+        request
+    }
+
+    /// Converts a refuel request from the user's units.
+    pub fn refuel_request(self, mut request: NewRefuelRequest) -> NewRefuelRequest {
+        request.refuel_quantity = self.volume_from_user(request.refuel_quantity);
+        request.odometer_value = self.reading_from_user(request.odometer_value);
+        request
+    }
+
+    /// Converts a trip request from the user's units.
+    pub fn trip_request(self, mut request: NewTripRequest) -> NewTripRequest {
+        request.start_odometer_value = self.reading_from_user(request.start_odometer_value);
+        request.end_odometer_value = self.reading_from_user(request.end_odometer_value);
+        request
+    }
+
+    /// Converts a trip update from the user's units.
+    pub fn trip_update(self, mut update: UpdateTripRequest) -> UpdateTripRequest {
+        update.start_odometer_value = update
+            .start_odometer_value
+            .map(|value| self.reading_from_user(value));
+        update.end_odometer_value = update
+            .end_odometer_value
+            .map(|value| self.reading_from_user(value));
+        update
+    }
+
+    /// Converts a charging session request from the user's units.
+    pub fn charging_request(
+        self,
+        mut request: NewChargingSessionRequest,
+    ) -> NewChargingSessionRequest {
+        request.odometer_value = self.reading_from_user(request.odometer_value);
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn decimal(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn units(distance: OdometerUnit, volume: VolumeUnit, odometer: OdometerUnit) -> Units {
+        Units {
+            distance,
+            volume,
+            odometer,
+        }
+    }
+
+    #[test]
+    fn converts_distances_readings_and_volumes() {
+        use OdometerUnit::{Kilometers as Km, Miles as Mi};
+        use VolumeUnit::{ImperialGallons as Imp, Liters as L, UsGallons as Us};
+
+        type Conversion = fn(Units, Decimal) -> Decimal;
+        let cases: &[(&str, Units, Conversion, &str, &str)] = &[
+            (
+                "distance",
+                units(Km, L, Km),
+                Units::distance_from_user,
+                "1.23456",
+                "1.235",
+            ),
+            (
+                "distance",
+                units(Mi, L, Km),
+                Units::distance_from_user,
+                "100",
+                "160.934",
+            ),
+            (
+                "distance",
+                units(Mi, L, Km),
+                Units::distance_to_user,
+                "160.9344",
+                "100",
+            ),
+            (
+                "reading",
+                units(Km, L, Mi),
+                Units::reading_from_user,
+                "1",
+                "1.609",
+            ),
+            (
+                "reading",
+                units(Mi, L, Km),
+                Units::reading_from_user,
+                "1",
+                "1",
+            ),
+            (
+                "reading",
+                units(Km, L, Mi),
+                Units::reading_to_user,
+                "1609.344",
+                "1000",
+            ),
+            (
+                "volume",
+                units(Km, Us, Km),
+                Units::volume_from_user,
+                "10",
+                "37.854",
+            ),
+            (
+                "volume",
+                units(Km, Imp, Km),
+                Units::volume_from_user,
+                "1",
+                "4.546",
+            ),
+            (
+                "volume",
+                units(Km, Us, Km),
+                Units::volume_to_user,
+                "37.854",
+                "10",
+            ),
+            (
+                "volume",
+                units(Km, L, Km),
+                Units::volume_to_user,
+                "42.5",
+                "42.5",
+            ),
+        ];
+        for (what, units, convert, value, expected) in cases {
+            assert_eq!(
+                convert(*units, decimal(value)),
+                decimal(expected),
+                "{} {} in {:?}",
+                what,
+                value,
+                units
+            );
+        }
+    }
+
+    #[test]
+    fn saturates_values_too_large_to_convert() {
+        let miles = units(OdometerUnit::Miles, VolumeUnit::Liters, OdometerUnit::Miles);
+        assert_eq!(
+            miles.distance_from_user(Decimal::MAX),
+            stored_precision(Decimal::MAX)
+        );
+        assert_eq!(
+            miles.reading_from_user(Decimal::MAX),
+            stored_precision(Decimal::MAX)
+        );
+    }
+
+    #[test]
+    fn converts_tank_capacities_but_not_battery_capacities() {
+        let gallons = units(
+            OdometerUnit::Miles,
+            VolumeUnit::UsGallons,
+            OdometerUnit::Miles,
+        );
+        assert_eq!(
+            gallons.capacity_from_user(Some("petrol"), decimal("12")),
+            decimal("45.425")
+        );
+        assert_eq!(
+            gallons.capacity_from_user(None, decimal("12")),
+            decimal("45.425")
+        );
+        assert_eq!(
+            gallons.capacity_from_user(Some("ev"), decimal("75.0001")),
+            decimal("75")
+        );
+    }
+
+    #[test]
+    fn converts_fuel_economy() {
+        let cases: &[(Units, f64, f64, &str)] = &[
+            (Units::default(), 12.5, 12.5, "km/L"),
+            (
+                units(
+                    OdometerUnit::Miles,
+                    VolumeUnit::UsGallons,
+                    OdometerUnit::Miles,
+                ),
+                10.0,
+                23.521_458,
+                "mi/US gal",
+            ),
+            (
+                units(
+                    OdometerUnit::Miles,
+                    VolumeUnit::ImperialGallons,
+                    OdometerUnit::Miles,
+                ),
+                10.0,
+                28.248_094,
+                "mi/imp gal",
+            ),
+        ];
+        for (units, economy, expected, label) in cases {
+            assert!(
+                (units.economy_to_user(*economy) - expected).abs() < 1e-6,
+                "{} km/L in {:?}",
+                economy,
+                units
+            );
+            assert_eq!(units.economy_label(), *label);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+//! Delivery of outgoing webhooks.
+//!
+//! Events are queued in `webhook_deliveries` by `services::publish`; `spawn_worker` polls the
+//! queue every `WEBHOOK_POLL_SECS` seconds (default 5) and posts each due event to its webhook.
+//! Every request carries the headers
+//! - `X-Vimana-Event`: The event name, e.g. `refuel.created`.
+//! - `X-Vimana-Delivery`: The ID of the delivery, the same across retries.
+//! - `X-Vimana-Signature`: `sha256=` followed by the hex-encoded HMAC-SHA256 of the body, keyed
+//!   with the webhook's secret.
+//!
+//! Any `2xx` response counts as delivered. Other responses and network errors are retried with
+//! exponential backoff, starting at `RETRY_BASE_DELAY`, until `MAX_WEBHOOK_ATTEMPTS` attempts
+//! have failed. Redirects are not followed.
+//!
+//! Webhooks may only target public addresses: URLs resolving to a loopback, private or
+//! link-local address (such as the cloud metadata endpoint `169.254.169.254`) are refused when
+//! the webhook is saved and again before each delivery. The delivery itself connects through
+//! `PublicResolver`, so a host that resolves differently by the time the request is sent cannot
+//! get around the check. Deployments that deliver to services on their own network can allow
+//! such targets with `WEBHOOK_ALLOW_PRIVATE=true`.
+use actix_web::web;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use sha2::Sha256;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    models::{DueWebhookDelivery, WebhookAttempt},
+    queries, services, DbPool,
+};
+
+/// Number of failed attempts after which a delivery is given up.
+const MAX_WEBHOOK_ATTEMPTS: i32 = 8;
+
+/// Delay before the first retry; each further retry waits twice as long.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(30);
+
+/// Longest delay between two attempts.
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(6 * 3600);
+
+/// Number of due deliveries attempted per poll.
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// How long a webhook request may take before it counts as failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Computes the `X-Vimana-Signature` header value of a payload.
+///
+/// # Arguments
+/// - `secret`: The webhook's secret.
+/// - `body`: The exact bytes posted.
+///
+/// # Returns
+/// `sha256=` followed by the lowercase hex-encoded HMAC-SHA256 of the body.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Whether webhooks may target loopback, private and link-local addresses.
+fn private_targets_allowed() -> bool {
+    env::var("WEBHOOK_ALLOW_PRIVATE").is_ok_and(|value| value.trim().eq_ignore_ascii_case("true"))
+}
+
+/// Returns the IPv4 address embedded in an IPv6 address that is translated or tunnelled to
+/// IPv4: IPv4-compatible (`::a.b.c.d`), IPv4-mapped (`::ffff:a.b.c.d`), NAT64 (`64:ff9b::/96`)
+/// and 6to4 (`2002::/16`) addresses.
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    let tail = Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]);
+    match ip.segments() {
+        [0, 0, 0, 0, 0, 0 | 0xffff, _, _] => Some(tail),
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(tail),
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
+
+/// Whether an address is on the public internet, rather than the server itself, its private
+/// network or a link-local service.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            let shared = first == 100 && second & 0xc0 == 64;
+            !(first == 0
+                || shared
+                || ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation())
+        }
+        IpAddr::V6(ip) => match embedded_ipv4(ip) {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                // 64:ff9b:1::/48 is reserved for NAT64 within a local network
+                let local_nat64 = ip.segments()[..3] == [0x64, 0xff9b, 1];
+                !(local_nat64
+                    || ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || ip.is_multicast())
+            }
+        },
+    }
+}
+
+/// Checks that a webhook URL only resolves to public addresses, unless private targets are
+/// allowed.
+///
+/// # Arguments
+/// - `url`: The webhook URL.
+///
+/// # Returns
+/// - `Ok(())`: If the URL may be posted to.
+/// - `Err(String)`: Why the URL is refused.
+pub async fn check_target(url: &str) -> Result<(), String> {
+    if private_targets_allowed() {
+        return Ok(());
+    }
+
+    let parsed = reqwest::Url::parse(url).map_err(|_| "Webhook URL is not valid".to_string())?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| "Webhook URL has no host".to_string())?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let addresses = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![ip],
+        Err(_) => {
+            let target = (host.clone(), parsed.port_or_known_default().unwrap_or(80));
+            web::block(move || target.to_socket_addrs())
+                .await
+                .map_err(|e| format!("Failed to resolve webhook host {}: {}", host, e))?
+                .map_err(|e| format!("Failed to resolve webhook host {}: {}", host, e))?
+                .map(|address| address.ip())
+                .collect()
+        }
+    };
+
+    if !addresses.is_empty() && addresses.into_iter().all(is_public) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Webhook host {} must not resolve to a loopback, private or link-local address",
+            host
+        ))
+    }
+}
+
+/// DNS resolver for webhook requests that drops loopback, private and link-local addresses.
+///
+/// `check_target` resolves the host once to report a clear error, but the request resolves it
+/// again when it connects. Filtering the addresses the request actually connects to keeps a DNS
+/// server that answers differently the second time from steering it onto the internal network.
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let lookup = host.clone();
+            let addresses: Vec<SocketAddr> =
+                tokio::task::spawn_blocking(move || (lookup.as_str(), 0).to_socket_addrs())
+                    .await??
+                    .filter(|address| private_targets_allowed() || is_public(address.ip()))
+                    .collect();
+            if addresses.is_empty() {
+                return Err(
+                    format!("Webhook host {} does not resolve to a public address", host).into(),
+                );
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Returns how long to wait before retrying a delivery that failed `attempts` times.
+fn retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    RETRY_BASE_DELAY
+        .saturating_mul(2u32.pow(exponent))
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Starts the webhook delivery worker on the current runtime.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+///
+/// # Panics
+/// If the HTTP client cannot be built; a default client would follow redirects and never time
+/// out, so the server refuses to start instead.
+pub fn spawn_worker(pool: DbPool) {
+    let poll_secs = match env::var("WEBHOOK_POLL_SECS") {
+        Ok(value) => value
+            .parse::<u64>()
+            .ok()
+            .filter(|&secs| secs > 0)
+            .unwrap_or_else(|| {
+                log::warn!(
+                    "Ignoring invalid WEBHOOK_POLL_SECS={:?}, using the default",
+                    value
+                );
+                5
+            }),
+        Err(_) => 5,
+    };
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("vimana-webhooks/", env!("CARGO_PKG_VERSION")))
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("Failed to build the webhook HTTP client");
+
+    actix_web::rt::spawn(async move {
+        let mut interval = actix_web::rt::time::interval(Duration::from_secs(poll_secs));
+        loop {
+            interval.tick().await;
+            if let Err(e) = deliver_due(&pool, &client).await {
+                log::error!("Failed to deliver webhooks: {}", e);
+            }
+        }
+    });
+}
+
+/// Attempts the due deliveries and records their outcome.
+///
+/// # Arguments
+/// - `pool`: Database connection pool.
+/// - `client`: The HTTP client to post with.
+///
+/// # Returns
+/// - `Ok(())`: If the deliveries were attempted; individual failures are recorded, not returned.
+/// - `Err(ServiceError)`: If the due deliveries could not be loaded or updated.
+async fn deliver_due(
+    pool: &DbPool,
+    client: &reqwest::Client,
+) -> Result<(), services::ServiceError> {
+    let due = services::run_blocking(pool, |pool| {
+        queries::get_due_webhook_deliveries(pool, DELIVERY_BATCH_SIZE)
+    })
+    .await?;
+
+    for DueWebhookDelivery { delivery, webhook } in due {
+        // The host may resolve differently than when the webhook was saved
+        let target = check_target(&webhook.url).await;
+        let (response_status, error) = match (target, serde_json::to_vec(&delivery.payload)) {
+            (Err(e), _) => (None, Some(e)),
+            (Ok(()), Ok(body)) => {
+                let result = client
+                    .post(&webhook.url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .header("X-Vimana-Event", delivery.event.as_str())
+                    .header("X-Vimana-Delivery", delivery.id.to_string())
+                    .header("X-Vimana-Signature", sign(&webhook.secret, &body))
+                    .body(body)
+                    .send()
+                    .await;
+                match result {
+                    Ok(response) if response.status().is_success() => {
+                        (Some(i32::from(response.status().as_u16())), None)
+                    }
+                    Ok(response) => (
+                        Some(i32::from(response.status().as_u16())),
+                        Some(format!("Webhook responded with {}", response.status())),
+                    ),
+                    Err(e) => (None, Some(format!("Webhook request failed: {}", e))),
+                }
+            }
+            (Ok(()), Err(e)) => (None, Some(format!("Failed to serialize payload: {}", e))),
+        };
+
+        let attempts = delivery.attempts + 1;
+        let retry_at = match &error {
+            Some(e) if attempts < MAX_WEBHOOK_ATTEMPTS => {
+                log::warn!(
+                    "Delivery {} of {} to webhook {} failed (attempt {}): {}",
+                    delivery.id,
+                    delivery.event,
+                    webhook.id,
+                    attempts,
+                    e
+                );
+                chrono::Duration::from_std(retry_delay(attempts))
+                    .ok()
+                    .map(|delay| Utc::now() + delay)
+            }
+            Some(e) => {
+                log::warn!(
+                    "Giving up delivery {} of {} to webhook {} after {} attempts: {}",
+                    delivery.id,
+                    delivery.event,
+                    webhook.id,
+                    attempts,
+                    e
+                );
+                None
+            }
+            None => None,
+        };
+
+        let attempt = WebhookAttempt {
+            response_status,
+            error,
+            retry_at,
+        };
+        services::run_blocking(pool, move |pool| {
+            queries::record_webhook_attempt(pool, delivery.id, attempt)
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_computes_hmac_sha256() {
+        let cases: &[(&str, &[u8], &str)] = &[
+            (
+                "",
+                b"",
+                "sha256=b613679a0814d9ec772f95d778c35fc5ff1697c493715653c6c712144292c5ad",
+            ),
+            (
+                "Jefe",
+                b"what do ya want for nothing?",
+                "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+        ];
+        for (secret, body, expected) in cases {
+            assert_eq!(sign(secret, body), *expected, "secret {:?}", secret);
+        }
+    }
+
+    #[test]
+    fn is_public_rejects_internal_addresses() {
+        let cases: &[(&str, bool)] = &[
+            ("93.184.216.34", true),
+            ("8.8.8.8", true),
+            ("0.0.0.0", false),
+            ("127.0.0.1", false),
+            ("10.1.2.3", false),
+            ("172.16.0.1", false),
+            ("192.168.1.1", false),
+            ("100.64.0.1", false),
+            ("169.254.169.254", false),
+            ("255.255.255.255", false),
+            ("224.0.0.1", false),
+            ("192.0.2.1", false),
+            ("2606:2800:220:1:248:1893:25c8:1946", true),
+            ("::", false),
+            ("::1", false),
+            ("fd00::1", false),
+            ("fe80::1", false),
+            ("ff02::1", false),
+            ("::ffff:93.184.216.34", true),
+            ("::ffff:127.0.0.1", false),
+            ("::ffff:169.254.169.254", false),
+            ("::10.0.0.1", false),
+            ("64:ff9b::93.184.216.34", true),
+            ("64:ff9b::10.0.0.1", false),
+            ("64:ff9b:1::a00:1", false),
+            ("2002:5db8:d822::1", true),
+            ("2002:c0a8:0101::1", false),
+            ("2002:7f00:1::1", false),
+        ];
+        for (ip, expected) in cases {
+            assert_eq!(is_public(ip.parse().unwrap()), *expected, "address {}", ip);
+        }
+    }
+}